@@ -3,11 +3,11 @@
 
 //! cargo run --example generate_mnemonic --release
 
-use iota_client::{Client, Result};
+use iota_client::{Client, MnemonicLength, Result};
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let mnemonic = Client::generate_mnemonic()?;
+    let mnemonic = Client::generate_mnemonic(MnemonicLength::Words24)?;
 
     println!("Mnemonic: {mnemonic}");
 
@@ -6,7 +6,7 @@
 use std::sync::{mpsc::channel, Arc, Mutex};
 
 use iota_client::{
-    mqtt::{MqttEvent, MqttPayload, Topic},
+    mqtt::{MqttEvent, MqttPayload, Topic, UnlockKind},
     Client, Result,
 };
 
@@ -36,20 +36,23 @@ async fn main() -> Result<()> {
     client
         .subscribe(
             vec![
-                Topic::try_from("milestone-info/latest".to_string())?,
-                Topic::try_from("blocks".to_string())?,
-                Topic::try_from(
-                    "outputs/unlock/address/atoi1qzt0nhsf38nh6rs4p6zs5knqp6psgha9wsv74uajqgjmwc75ugupx3y7x0r"
-                        .to_string(),
-                )?,
+                Topic::LatestMilestoneInfo,
+                Topic::Blocks,
+                Topic::OutputsByUnlock {
+                    kind: UnlockKind::Address,
+                    address: "atoi1qzt0nhsf38nh6rs4p6zs5knqp6psgha9wsv74uajqgjmwc75ugupx3y7x0r".to_string(),
+                    spent: false,
+                },
             ],
             move |event| {
                 println!("Topic: {}", event.topic);
                 match &event.payload {
-                    MqttPayload::Json(val) => println!("{}", serde_json::to_string(&val).unwrap()),
                     MqttPayload::Block(block) => println!("{block:?}"),
                     MqttPayload::MilestonePayload(ms) => println!("{ms:?}"),
                     MqttPayload::Receipt(receipt) => println!("{receipt:?}"),
+                    MqttPayload::MilestoneInfo(milestone_info) => println!("{milestone_info:?}"),
+                    MqttPayload::BlockMetadata(block_metadata) => println!("{block_metadata:?}"),
+                    MqttPayload::OutputWithMetadata(output) => println!("{output:?}"),
                 }
                 tx.lock().unwrap().send(()).unwrap();
             },
@@ -60,7 +63,7 @@ async fn main() -> Result<()> {
         rx.recv().unwrap();
         if i == 7 {
             // unsubscribe from topic "blocks", will continue to receive events for "milestones/latest"
-            client.unsubscribe(vec![Topic::try_from("blocks".to_string())?]).await?;
+            client.unsubscribe(vec![Topic::Blocks]).await?;
         }
     }
 
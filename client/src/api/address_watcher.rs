@@ -0,0 +1,327 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Watches addresses for created and spent outputs
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    time::Duration,
+};
+
+use futures::Stream;
+#[cfg(feature = "mqtt")]
+use futures::{future::Either, StreamExt};
+use iota_types::{
+    api::core::response::OutputWithMetadataResponse,
+    block::{address::Address, output::OutputId},
+};
+use log::warn;
+
+#[cfg(feature = "mqtt")]
+use crate::mqtt::{MqttPayload, Topic, TopicEvent, TopicEventStream, UnlockKind};
+use crate::{
+    constants::DEFAULT_ADDRESS_WATCHER_POLL_INTERVAL, node_api::indexer::query_parameters::QueryParameter, Client,
+    Error, Result,
+};
+
+/// A ledger change of a watched address, returned by [`Client::watch_addresses()`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AddressEvent {
+    /// An output was created on the address.
+    OutputCreated {
+        /// The Bech32 encoded address.
+        address: String,
+        /// The ID of the created output.
+        output_id: OutputId,
+        /// The created output with its metadata.
+        output: OutputWithMetadataResponse,
+    },
+    /// An output of the address was spent.
+    OutputSpent {
+        /// The Bech32 encoded address.
+        address: String,
+        /// The ID of the spent output.
+        output_id: OutputId,
+        /// The spent output with its metadata.
+        output: OutputWithMetadataResponse,
+    },
+}
+
+impl AddressEvent {
+    /// Returns the Bech32 encoded address of the event.
+    pub fn address(&self) -> &str {
+        match self {
+            Self::OutputCreated { address, .. } | Self::OutputSpent { address, .. } => address,
+        }
+    }
+
+    /// Returns the ID of the created or spent output.
+    pub fn output_id(&self) -> &OutputId {
+        match self {
+            Self::OutputCreated { output_id, .. } | Self::OutputSpent { output_id, .. } => output_id,
+        }
+    }
+}
+
+/// Builder of an address watcher, created with [`Client::watch_addresses()`].
+#[must_use]
+pub struct AddressWatcherBuilder<'a> {
+    client: &'a Client,
+    addresses: Vec<String>,
+    poll_interval: Duration,
+    include_existing_outputs: bool,
+}
+
+impl<'a> AddressWatcherBuilder<'a> {
+    /// Sets the interval in which the outputs of the addresses are requested from the indexer, to catch changes that
+    /// weren't received via MQTT. Without the `mqtt` feature, this is the only source of changes.
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Sets whether the outputs that are already unspent on the addresses are returned as
+    /// [`AddressEvent::OutputCreated`] on the first request to the indexer, disabled by default.
+    pub fn with_existing_outputs(mut self, include_existing_outputs: bool) -> Self {
+        self.include_existing_outputs = include_existing_outputs;
+        self
+    }
+
+    /// Subscribes to the MQTT topics of the addresses, if the `mqtt` feature is enabled, and returns the stream of
+    /// their ledger changes. Each output is returned at most once as created and once as spent.
+    pub async fn finish(self) -> Result<impl Stream<Item = Result<AddressEvent>> + 'a> {
+        for address in &self.addresses {
+            Address::try_from_bech32(address)?;
+        }
+
+        #[cfg(feature = "mqtt")]
+        let mqtt_events = self.subscribe().await;
+
+        let watcher = AddressWatcher {
+            client: self.client,
+            addresses: self.addresses,
+            poll_interval: self.poll_interval,
+            include_existing_outputs: self.include_existing_outputs,
+            initialized: false,
+            next_reconciliation: instant::Instant::now(),
+            unspent: HashMap::new(),
+            spent: HashSet::new(),
+            events: VecDeque::new(),
+            #[cfg(feature = "mqtt")]
+            mqtt_events,
+        };
+
+        Ok(futures::stream::unfold(watcher, |mut watcher| async move {
+            let event = watcher.next().await;
+            Some((event, watcher))
+        }))
+    }
+
+    // Subscribes to the created and spent outputs of the addresses, the indexer is polled only if that fails.
+    #[cfg(feature = "mqtt")]
+    async fn subscribe(&self) -> Option<TopicEventStream> {
+        if self.addresses.is_empty() {
+            return None;
+        }
+
+        let topics = self
+            .addresses
+            .iter()
+            .flat_map(|address| {
+                [false, true].map(|spent| Topic::OutputsByUnlock {
+                    kind: UnlockKind::Address,
+                    address: address.clone(),
+                    spent,
+                })
+            })
+            .collect();
+
+        self.client
+            .subscribe_stream(topics)
+            .await
+            .map_err(|e| {
+                warn!("Subscribing to the outputs of the watched addresses failed, only polling the indexer: {e}")
+            })
+            .ok()
+    }
+}
+
+struct AddressWatcher<'a> {
+    client: &'a Client,
+    addresses: Vec<String>,
+    poll_interval: Duration,
+    include_existing_outputs: bool,
+    // Set after the first complete reconciliation, before that unspent outputs are only returned if requested.
+    initialized: bool,
+    next_reconciliation: instant::Instant,
+    // The known unspent outputs with their address.
+    unspent: HashMap<OutputId, String>,
+    // The outputs that were returned as spent, kept while the indexer still lists them.
+    spent: HashSet<OutputId>,
+    events: VecDeque<Result<AddressEvent>>,
+    #[cfg(feature = "mqtt")]
+    mqtt_events: Option<TopicEventStream>,
+}
+
+impl AddressWatcher<'_> {
+    async fn next(&mut self) -> Result<AddressEvent> {
+        loop {
+            if let Some(event) = self.events.pop_front() {
+                return event;
+            }
+
+            let now = instant::Instant::now();
+            if now >= self.next_reconciliation {
+                self.next_reconciliation = now + self.poll_interval;
+                self.reconcile().await?;
+                continue;
+            }
+            let remaining = self.next_reconciliation - now;
+
+            #[cfg(feature = "mqtt")]
+            if let Some(mqtt_events) = &mut self.mqtt_events {
+                let event = match futures::future::select(mqtt_events.next(), Box::pin(super::sleep(remaining))).await {
+                    Either::Left((event, _)) => Some(event),
+                    Either::Right(_) => None,
+                };
+                match event {
+                    Some(Some(Ok(event))) => self.handle_mqtt_event(event)?,
+                    Some(Some(Err(e))) => {
+                        // Events were dropped, so the indexer is requested right away.
+                        warn!("Receiving the outputs of the watched addresses failed: {e}");
+                        self.next_reconciliation = instant::Instant::now();
+                    }
+                    Some(None) => {
+                        warn!("The MQTT subscription of the watched addresses ended, only polling the indexer");
+                        self.mqtt_events = None;
+                    }
+                    None => {}
+                }
+                continue;
+            }
+
+            super::sleep(remaining).await;
+        }
+    }
+
+    #[cfg(feature = "mqtt")]
+    fn handle_mqtt_event(&mut self, event: TopicEvent) -> Result<()> {
+        if let (Topic::OutputsByUnlock { address, spent, .. }, MqttPayload::OutputWithMetadata(output)) =
+            (event.topic, event.payload)
+        {
+            let output_id = output.metadata.output_id()?;
+            if spent {
+                self.output_spent(address, output_id, output);
+            } else {
+                self.output_created(address, output_id, output);
+            }
+        }
+
+        Ok(())
+    }
+
+    // Compares the outputs listed by the indexer with the known ones.
+    async fn reconcile(&mut self) -> Result<()> {
+        let mut listed = HashSet::new();
+
+        for address in self.addresses.clone() {
+            let mut output_ids = self
+                .client
+                .basic_output_ids(vec![QueryParameter::Address(address.clone())])
+                .await?
+                .items;
+            output_ids.extend(
+                self.client
+                    .nft_output_ids(vec![QueryParameter::Address(address.clone())])
+                    .await?
+                    .items,
+            );
+
+            let created = output_ids
+                .iter()
+                .filter(|output_id| !self.unspent.contains_key(output_id) && !self.spent.contains(output_id))
+                .copied()
+                .collect::<Vec<_>>();
+            for output in self.client.get_outputs(created).await? {
+                let output_id = output.metadata.output_id()?;
+                if self.initialized || self.include_existing_outputs {
+                    self.output_created(address.clone(), output_id, output);
+                } else {
+                    self.unspent.insert(output_id, address.clone());
+                }
+            }
+
+            // The indexer can lag behind MQTT, so outputs that aren't listed anymore are only spent if the node says
+            // so.
+            let unlisted = self
+                .unspent
+                .iter()
+                .filter(|(output_id, output_address)| **output_address == address && !output_ids.contains(output_id))
+                .map(|(output_id, _)| *output_id)
+                .collect::<Vec<_>>();
+            for output_id in unlisted {
+                match self.client.get_output(&output_id).await {
+                    Ok(output) => {
+                        if output.metadata.is_spent {
+                            self.output_spent(address.clone(), output_id, output);
+                        }
+                    }
+                    // Nodes prune spent outputs, so it can't be returned as spent anymore.
+                    Err(Error::NotFound(_)) => {
+                        warn!("The output {output_id} of a watched address was spent and pruned");
+                        self.unspent.remove(&output_id);
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+
+            listed.extend(output_ids);
+        }
+
+        // Spent outputs that aren't listed anymore can't be returned as created again.
+        self.spent.retain(|output_id| listed.contains(output_id));
+        self.initialized = true;
+
+        Ok(())
+    }
+
+    fn output_created(&mut self, address: String, output_id: OutputId, output: OutputWithMetadataResponse) {
+        if self.unspent.contains_key(&output_id) || self.spent.contains(&output_id) {
+            return;
+        }
+
+        self.unspent.insert(output_id, address.clone());
+        self.events.push_back(Ok(AddressEvent::OutputCreated {
+            address,
+            output_id,
+            output,
+        }));
+    }
+
+    fn output_spent(&mut self, address: String, output_id: OutputId, output: OutputWithMetadataResponse) {
+        self.unspent.remove(&output_id);
+        if !self.spent.insert(output_id) {
+            return;
+        }
+
+        self.events.push_back(Ok(AddressEvent::OutputSpent {
+            address,
+            output_id,
+            output,
+        }));
+    }
+}
+
+impl Client {
+    /// Watches the given Bech32 encoded addresses for outputs that are created on them or spent, for outputs with an
+    /// address unlock condition. Changes are received via the `outputs/unlock/address` MQTT topics if the `mqtt`
+    /// feature is enabled, and the indexer is polled periodically to catch changes that were missed.
+    pub fn watch_addresses(&self, addresses: Vec<String>) -> AddressWatcherBuilder<'_> {
+        AddressWatcherBuilder {
+            client: self,
+            addresses,
+            poll_interval: DEFAULT_ADDRESS_WATCHER_POLL_INTERVAL,
+            include_existing_outputs: false,
+        }
+    }
+}
@@ -30,34 +30,30 @@ use crate::{
 impl<'a> ClientBlockBuilder<'a> {
     // Get basic outputs for an address without storage deposit return unlock condition
     pub(crate) async fn basic_address_outputs(&self, address: String) -> Result<Vec<OutputWithMetadataResponse>> {
-        let mut output_ids = Vec::new();
-
         // First request to get all basic outputs that can directly be unlocked by the address.
-        output_ids.extend(
-            self.client
-                .basic_output_ids(vec![
-                    QueryParameter::Address(address.clone()),
-                    QueryParameter::HasStorageDepositReturn(false),
-                ])
-                .await?
-                .items,
-        );
+        let output_ids_response = self
+            .client
+            .basic_output_ids(vec![
+                QueryParameter::Address(address.clone()),
+                QueryParameter::HasStorageDepositReturn(false),
+            ])
+            .await?;
+        let mut outputs = self.client.get_outputs_for_listing(output_ids_response).await?;
 
         // Second request to get all basic outputs that can be unlocked by the address through the expiration condition.
-        output_ids.extend(
-            self.client
-                .basic_output_ids(vec![
-                    QueryParameter::ExpirationReturnAddress(address),
-                    QueryParameter::HasExpiration(true),
-                    QueryParameter::HasStorageDepositReturn(false),
-                    // Ignore outputs that aren't expired yet
-                    QueryParameter::ExpiresBefore(unix_timestamp_now()),
-                ])
-                .await?
-                .items,
-        );
-
-        self.client.get_outputs(output_ids).await
+        let output_ids_response = self
+            .client
+            .basic_output_ids(vec![
+                QueryParameter::ExpirationReturnAddress(address),
+                QueryParameter::HasExpiration(true),
+                QueryParameter::HasStorageDepositReturn(false),
+                // Ignore outputs that aren't expired yet
+                QueryParameter::ExpiresBefore(unix_timestamp_now()),
+            ])
+            .await?;
+        outputs.extend(self.client.get_outputs_for_listing(output_ids_response).await?);
+
+        Ok(outputs)
     }
 
     /// Searches inputs for provided outputs, by requesting the outputs from the account addresses or for
@@ -21,7 +21,7 @@ use iota_types::block::{
 };
 use packable::bounded::TryIntoBoundedU16Error;
 
-pub use self::transaction::verify_semantic;
+pub use self::transaction::{verify_semantic, verify_semantic_with_diagnostic};
 use crate::{
     api::block_builder::input_selection::Burn, constants::SHIMMER_COIN_TYPE, secret::SecretManager, Client, Error,
     Result,
@@ -10,7 +10,7 @@ use iota_types::block::{
         transaction::{RegularTransactionEssence, TransactionEssence, TransactionPayload},
         Payload, TaggedDataPayload,
     },
-    semantic::{semantic_validation, ConflictReason, ValidationContext},
+    semantic::{semantic_validation_with_diagnostic, ConflictDiagnostic, ConflictReason, ValidationContext},
     signature::Ed25519Signature,
     Block, BlockId,
 };
@@ -98,11 +98,11 @@ impl<'a> ClientBlockBuilder<'a> {
 
         validate_transaction_payload_length(&tx_payload)?;
 
-        let conflict = verify_semantic(&prepared_transaction_data.inputs_data, &tx_payload, current_time)?;
-
-        if conflict != ConflictReason::None {
-            log::debug!("[sign_transaction] conflict: {conflict:?} for {:#?}", tx_payload);
-            return Err(Error::TransactionSemantic(conflict));
+        if let Some(diagnostic) =
+            verify_semantic_with_diagnostic(&prepared_transaction_data.inputs_data, &tx_payload, current_time)?
+        {
+            log::debug!("[sign_transaction] conflict: {diagnostic} for {:#?}", tx_payload);
+            return Err(Error::TransactionSemantic(diagnostic.conflict_reason()));
         }
 
         Ok(Payload::from(tx_payload))
@@ -116,6 +116,18 @@ pub fn verify_semantic(
     transaction: &TransactionPayload,
     current_time: u32,
 ) -> crate::Result<ConflictReason> {
+    Ok(
+        verify_semantic_with_diagnostic(input_signing_data, transaction, current_time)?
+            .map_or(ConflictReason::None, |diagnostic| diagnostic.conflict_reason()),
+    )
+}
+
+/// Verifies the semantic of a prepared transaction and describes the conflict, if there is one.
+pub fn verify_semantic_with_diagnostic(
+    input_signing_data: &[InputSigningData],
+    transaction: &TransactionPayload,
+    current_time: u32,
+) -> crate::Result<Option<ConflictDiagnostic>> {
     let transaction_id = transaction.id();
     let TransactionEssence::Regular(essence) = transaction.essence();
     let output_ids = input_signing_data.iter().map(|input| *input.output_id());
@@ -136,7 +148,11 @@ pub fn verify_semantic(
         current_time,
     );
 
-    Ok(semantic_validation(context, inputs.as_slice(), transaction.unlocks())?)
+    Ok(semantic_validation_with_diagnostic(
+        context,
+        inputs.as_slice(),
+        transaction.unlocks(),
+    )?)
 }
 
 /// Verifies that the transaction payload doesn't exceed the block size limit with 8 parents.
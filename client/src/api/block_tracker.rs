@@ -0,0 +1,359 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tracks the inclusion state of a block
+
+#[cfg(feature = "mqtt")]
+use std::str::FromStr;
+use std::{collections::VecDeque, time::Duration};
+
+#[cfg(feature = "mqtt")]
+use futures::future::Either;
+use futures::{Stream, StreamExt};
+use iota_types::{
+    api::core::{dto::LedgerInclusionStateDto, response::BlockMetadataResponse},
+    block::{payload::Payload, semantic::ConflictReason, BlockId},
+};
+#[cfg(feature = "mqtt")]
+use log::warn;
+
+#[cfg(feature = "mqtt")]
+use crate::mqtt::{MqttPayload, Topic, TopicEvent, TopicEventStream};
+use crate::{
+    constants::{DEFAULT_RETRY_UNTIL_INCLUDED_INTERVAL, DEFAULT_RETRY_UNTIL_INCLUDED_MAX_AMOUNT},
+    Client, Error, Result,
+};
+
+/// A state transition of a block tracked by a [`BlockTracker`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BlockState {
+    /// The block isn't referenced by a milestone yet.
+    Pending {
+        /// The ID of the tracked block.
+        block_id: BlockId,
+    },
+    /// An attachment of the block was promoted by a new block approving it.
+    Promoted {
+        /// The ID of the promoted attachment.
+        block_id: BlockId,
+        /// The ID of the new block.
+        promotion_block_id: BlockId,
+    },
+    /// The payload of an attachment was reattached in a new block, which is tracked too.
+    Reattached {
+        /// The ID of the reattached attachment.
+        block_id: BlockId,
+        /// The ID of the new attachment.
+        reattachment_block_id: BlockId,
+    },
+    /// An attachment was referenced by a milestone.
+    Referenced {
+        /// The ID of the referenced attachment.
+        block_id: BlockId,
+        /// The index of the referencing milestone.
+        milestone_index: u32,
+    },
+    /// The transaction of an attachment was included in the ledger.
+    Included {
+        /// The ID of the block that included the transaction, which can be a block that isn't tracked if the
+        /// transaction was reattached by someone else.
+        block_id: BlockId,
+    },
+    /// The transaction of an attachment conflicts with the ledger.
+    Conflicting {
+        /// The ID of the conflicting attachment.
+        block_id: BlockId,
+        /// Why the transaction conflicts.
+        reason: ConflictReason,
+    },
+    /// The referencing milestone and with it the inclusion state are final, this is the last state.
+    Finalized {
+        /// The ID of the referenced attachment.
+        block_id: BlockId,
+        /// The index of the referencing milestone.
+        milestone_index: u32,
+    },
+}
+
+/// Tracks the inclusion state of a block, promoting or reattaching it if needed.
+///
+/// Created with [`Client::track_block()`]. The metadata of the block and its reattachments is received via the
+/// `block-metadata/{blockId}` MQTT topics if the `mqtt` feature is enabled, and requested periodically. Dropping the
+/// stream or future stops the tracking.
+#[must_use]
+pub struct BlockTracker<'a> {
+    client: &'a Client,
+    block_id: BlockId,
+    interval: Duration,
+    max_attempts: u64,
+    retry: bool,
+}
+
+impl<'a> BlockTracker<'a> {
+    /// Sets the interval in which the metadata of the block is requested and it gets promoted or reattached, 1
+    /// second by default.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sets after how many intervals the tracking fails with [`Error::TangleInclusion`], 40 by default.
+    pub fn with_max_attempts(mut self, max_attempts: u64) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Sets whether the block is promoted or reattached if the node suggests it, enabled by default.
+    pub fn with_retry(mut self, retry: bool) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Returns the stream of state transitions, which ends after [`BlockState::Finalized`] or
+    /// [`Error::TangleInclusion`]. Failed requests are returned as errors and retried in the next interval.
+    pub fn stream(self) -> impl Stream<Item = Result<BlockState>> + 'a {
+        let state = BlockTrackerState {
+            client: self.client,
+            interval: self.interval,
+            remaining_attempts: self.max_attempts,
+            retry: self.retry,
+            attachments: vec![self.block_id],
+            pending_reported: false,
+            ended: false,
+            next_check: instant::Instant::now(),
+            states: VecDeque::new(),
+            #[cfg(feature = "mqtt")]
+            subscribed: false,
+            #[cfg(feature = "mqtt")]
+            mqtt_events: None,
+        };
+
+        futures::stream::unfold(state, |mut state| async move {
+            state.next().await.map(|block_state| (block_state, state))
+        })
+    }
+
+    /// Tracks the block until it's finalized and returns its inclusion state, [`BlockState::Included`],
+    /// [`BlockState::Conflicting`] or [`BlockState::Referenced`] for blocks without a transaction.
+    pub async fn finish(self) -> Result<BlockState> {
+        let block_id = self.block_id;
+        let mut states = Box::pin(self.stream());
+        let mut inclusion_state = None;
+
+        while let Some(state) = states.next().await {
+            match state? {
+                BlockState::Finalized { .. } => break,
+                state @ (BlockState::Referenced { .. }
+                | BlockState::Included { .. }
+                | BlockState::Conflicting { .. }) => inclusion_state = Some(state),
+                _ => {}
+            }
+        }
+
+        inclusion_state.ok_or_else(|| Error::TangleInclusion(block_id.to_string()))
+    }
+}
+
+struct BlockTrackerState<'a> {
+    client: &'a Client,
+    interval: Duration,
+    remaining_attempts: u64,
+    retry: bool,
+    // The tracked block followed by its reattachments.
+    attachments: Vec<BlockId>,
+    pending_reported: bool,
+    ended: bool,
+    next_check: instant::Instant,
+    states: VecDeque<BlockState>,
+    // Set once the topics of the current attachments are subscribed, reset on reattachments.
+    #[cfg(feature = "mqtt")]
+    subscribed: bool,
+    #[cfg(feature = "mqtt")]
+    mqtt_events: Option<TopicEventStream>,
+}
+
+impl BlockTrackerState<'_> {
+    async fn next(&mut self) -> Option<Result<BlockState>> {
+        loop {
+            if let Some(state) = self.states.pop_front() {
+                return Some(Ok(state));
+            }
+            if self.ended {
+                return None;
+            }
+
+            #[cfg(feature = "mqtt")]
+            if !self.subscribed {
+                self.subscribe().await;
+            }
+
+            let now = instant::Instant::now();
+            if now >= self.next_check {
+                if self.remaining_attempts == 0 {
+                    self.ended = true;
+                    return Some(Err(Error::TangleInclusion(self.attachments[0].to_string())));
+                }
+                self.remaining_attempts -= 1;
+                self.next_check = now + self.interval;
+                if let Err(e) = self.check().await {
+                    return Some(Err(e));
+                }
+                continue;
+            }
+            let remaining = self.next_check - now;
+
+            #[cfg(feature = "mqtt")]
+            if let Some(mqtt_events) = &mut self.mqtt_events {
+                let event = match futures::future::select(mqtt_events.next(), Box::pin(super::sleep(remaining))).await {
+                    Either::Left((event, _)) => Some(event),
+                    Either::Right(_) => None,
+                };
+                match event {
+                    Some(Some(Ok(event))) => {
+                        if let Err(e) = self.handle_mqtt_event(event).await {
+                            return Some(Err(e));
+                        }
+                    }
+                    Some(Some(Err(e))) => warn!("Receiving the metadata of the tracked block failed: {e}"),
+                    Some(None) => self.mqtt_events = None,
+                    None => {}
+                }
+                continue;
+            }
+
+            super::sleep(remaining).await;
+        }
+    }
+
+    // Subscribes to the metadata of all attachments, only polling if that fails.
+    #[cfg(feature = "mqtt")]
+    async fn subscribe(&mut self) {
+        self.subscribed = true;
+        let topics = self.attachments.iter().copied().map(Topic::BlockMetadata).collect();
+        // Replacing the previous stream only unsubscribes the topics of attachments that aren't tracked anymore.
+        self.mqtt_events = self
+            .client
+            .subscribe_stream(topics)
+            .await
+            .map_err(|e| warn!("Subscribing to the metadata of the tracked block failed, only polling: {e}"))
+            .ok();
+    }
+
+    #[cfg(feature = "mqtt")]
+    async fn handle_mqtt_event(&mut self, event: TopicEvent) -> Result<()> {
+        if let MqttPayload::BlockMetadata(metadata) = event.payload {
+            let block_id = BlockId::from_str(&metadata.block_id)?;
+            self.referenced(block_id, &metadata).await?;
+        }
+
+        Ok(())
+    }
+
+    // Requests the metadata of all attachments and promotes or reattaches the latest one if needed.
+    async fn check(&mut self) -> Result<()> {
+        let latest = self.attachments.len() - 1;
+
+        for (index, block_id) in self.attachments.clone().into_iter().enumerate() {
+            let metadata = self.client.get_block_metadata(&block_id).await?;
+            if self.referenced(block_id, &metadata).await? {
+                return Ok(());
+            }
+
+            if index == 0 && !self.pending_reported {
+                self.pending_reported = true;
+                self.states.push_back(BlockState::Pending { block_id });
+            }
+            if index == latest && self.retry {
+                self.promote_or_reattach(block_id, &metadata).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Adds the final states if the attachment is referenced by a milestone. They're only added once all of them are
+    // known, so that a failed request doesn't add some of them again on the next check.
+    async fn referenced(&mut self, block_id: BlockId, metadata: &BlockMetadataResponse) -> Result<bool> {
+        let Some(milestone_index) = metadata.referenced_by_milestone_index else {
+            return Ok(false);
+        };
+
+        let mut states = vec![BlockState::Referenced {
+            block_id,
+            milestone_index,
+        }];
+        match metadata.ledger_inclusion_state {
+            Some(LedgerInclusionStateDto::Included) => states.push(BlockState::Included { block_id }),
+            Some(LedgerInclusionStateDto::Conflicting) => {
+                let reason = ConflictReason::try_from(metadata.conflict_reason.ok_or(Error::UnexpectedApiResponse)?)?;
+                states.push(BlockState::Conflicting { block_id, reason });
+                // The transaction could have been included by another attachment.
+                if let Some(included_block_id) = self.included_block_id(&block_id).await? {
+                    states.push(BlockState::Included {
+                        block_id: included_block_id,
+                    });
+                }
+            }
+            Some(LedgerInclusionStateDto::NoTransaction) | None => {}
+        }
+        states.push(BlockState::Finalized {
+            block_id,
+            milestone_index,
+        });
+        self.states.extend(states);
+        self.ended = true;
+
+        Ok(true)
+    }
+
+    // Returns the ID of the block that included the transaction of the conflicting block, if there is one.
+    async fn included_block_id(&self, block_id: &BlockId) -> Result<Option<BlockId>> {
+        let block = self.client.get_block(block_id).await?;
+        let Some(Payload::Transaction(transaction_payload)) = block.payload() else {
+            return Ok(None);
+        };
+
+        match self.client.get_included_block(&transaction_payload.id()).await {
+            Ok(included_block) if included_block.id() != *block_id => Ok(Some(included_block.id())),
+            Ok(_) | Err(Error::NotFound(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn promote_or_reattach(&mut self, block_id: BlockId, metadata: &BlockMetadataResponse) -> Result<()> {
+        if metadata.should_promote.unwrap_or(false) {
+            let (promotion_block_id, _) = self.client.promote_unchecked(&block_id).await?;
+            self.states.push_back(BlockState::Promoted {
+                block_id,
+                promotion_block_id,
+            });
+        } else if metadata.should_reattach.unwrap_or(false) {
+            let (reattachment_block_id, _) = self.client.reattach_unchecked(&block_id).await?;
+            self.attachments.push(reattachment_block_id);
+            self.states.push_back(BlockState::Reattached {
+                block_id,
+                reattachment_block_id,
+            });
+            #[cfg(feature = "mqtt")]
+            {
+                self.subscribed = false;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Client {
+    /// Creates a [`BlockTracker`] for the given block, which returns each change of its inclusion state. Unlike
+    /// [`Client::retry_until_included()`], the states are returned while the block is promoted or reattached.
+    pub fn track_block(&self, block_id: BlockId) -> BlockTracker<'_> {
+        BlockTracker {
+            client: self,
+            block_id,
+            interval: Duration::from_secs(DEFAULT_RETRY_UNTIL_INCLUDED_INTERVAL),
+            max_attempts: DEFAULT_RETRY_UNTIL_INCLUDED_MAX_AMOUNT,
+            retry: true,
+        }
+    }
+}
@@ -20,7 +20,8 @@ use iota_types::{
 use crate::{
     api::{input_selection::Error as InputSelectionError, ClientBlockBuilder, GetAddressesBuilder},
     constants::{
-        DEFAULT_RETRY_UNTIL_INCLUDED_INTERVAL, DEFAULT_RETRY_UNTIL_INCLUDED_MAX_AMOUNT, FIVE_MINUTES_IN_SECONDS,
+        DEFAULT_BATCH_MAX_PARALLEL_REQUESTS, DEFAULT_RETRY_UNTIL_INCLUDED_INTERVAL,
+        DEFAULT_RETRY_UNTIL_INCLUDED_MAX_AMOUNT, FIVE_MINUTES_IN_SECONDS,
     },
     error::{Error, Result},
     node_api::indexer::query_parameters::QueryParameter,
@@ -70,22 +71,19 @@ impl Client {
 
     /// Find all blocks by provided block IDs.
     pub async fn find_blocks(&self, block_ids: &[BlockId]) -> Result<Vec<Block>> {
-        let mut blocks = Vec::new();
-
         // Use a `HashSet` to prevent duplicate block_ids.
-        let mut block_ids_to_query = HashSet::<BlockId>::new();
-
-        // Collect the `BlockId` in the HashSet.
-        for block_id in block_ids {
-            block_ids_to_query.insert(*block_id);
-        }
+        let block_ids_to_query = block_ids
+            .iter()
+            .copied()
+            .collect::<HashSet<BlockId>>()
+            .into_iter()
+            .collect::<Vec<_>>();
 
-        // Use `get_block()` API to get the `Block`.
-        for block_id in block_ids_to_query {
-            let block = self.get_block(&block_id).await?;
-            blocks.push(block);
-        }
-        Ok(blocks)
+        // Use `get_blocks()` API to get the `Block`s in parallel.
+        self.get_blocks(&block_ids_to_query, DEFAULT_BATCH_MAX_PARALLEL_REQUESTS)
+            .await
+            .into_iter()
+            .collect()
     }
 
     /// Retries (promotes or reattaches) a block for provided block id. Block should only be
@@ -200,7 +198,7 @@ impl Client {
                 ])
                 .await?;
 
-            available_outputs.extend(self.get_outputs(output_ids_response.items).await?);
+            available_outputs.extend(self.get_outputs_for_listing(output_ids_response).await?);
         }
 
         let mut basic_outputs = Vec::new();
@@ -266,7 +264,7 @@ impl Client {
                 ])
                 .await?;
 
-            output_responses.extend(self.get_outputs(output_ids_response.items).await?);
+            output_responses.extend(self.get_outputs_for_listing(output_ids_response).await?);
         }
 
         Ok(output_responses.clone())
@@ -4,11 +4,24 @@
 //! High level APIs
 
 mod address;
+mod address_watcher;
 mod block_builder;
+mod block_tracker;
 mod consolidation;
 mod high_level;
+mod transaction_validator;
 mod types;
 
-pub use self::{address::*, block_builder::*, types::*};
+pub use self::{
+    address::*, address_watcher::*, block_builder::*, block_tracker::*, transaction_validator::*, types::*,
+};
 
 const ADDRESS_GAP_RANGE: u32 = 20;
+
+async fn sleep(duration: std::time::Duration) {
+    #[cfg(target_family = "wasm")]
+    gloo_timers::future::TimeoutFuture::new(duration.as_millis().try_into().unwrap_or(u32::MAX)).await;
+
+    #[cfg(not(target_family = "wasm"))]
+    tokio::time::sleep(duration).await;
+}
@@ -0,0 +1,199 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Semantic validation of received transactions against a view of the ledger
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use iota_types::block::{
+    input::{Input, TreasuryInput},
+    output::{Output, OutputId},
+    payload::transaction::{TransactionEssence, TransactionPayload},
+    semantic::{semantic_validation_with_diagnostic, ConflictDiagnostic, ConflictReason, ValidationContext},
+};
+
+use crate::{Client, Error, Result};
+
+/// An output of a [`LedgerView`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LedgerOutput {
+    /// The output.
+    pub output: Output,
+    /// Whether the output is already spent.
+    pub is_spent: bool,
+}
+
+/// A view of the ledger that provides the outputs consumed by transactions and the milestone timestamp they are
+/// validated against, e.g. a node, a cache or an in-memory ledger.
+#[async_trait]
+pub trait LedgerView: Send + Sync {
+    /// Returns the output with the given ID, `None` if it doesn't exist.
+    async fn output(&self, output_id: &OutputId) -> Result<Option<LedgerOutput>>;
+
+    /// Returns the timestamp of the milestone transactions are validated against.
+    async fn milestone_timestamp(&self) -> Result<u32>;
+}
+
+#[async_trait]
+impl<T: LedgerView + ?Sized> LedgerView for &T {
+    async fn output(&self, output_id: &OutputId) -> Result<Option<LedgerOutput>> {
+        (**self).output(output_id).await
+    }
+
+    async fn milestone_timestamp(&self) -> Result<u32> {
+        (**self).milestone_timestamp().await
+    }
+}
+
+/// Uses the ledger of the node, validating against the timestamp of its latest confirmed milestone.
+#[async_trait]
+impl LedgerView for Client {
+    async fn output(&self, output_id: &OutputId) -> Result<Option<LedgerOutput>> {
+        let response = match self.get_output(output_id).await {
+            Ok(response) => response,
+            Err(Error::NotFound(_)) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        Ok(Some(LedgerOutput {
+            output: Output::try_from_dto(&response.output, self.get_token_supply().await?)?,
+            is_spent: response.metadata.is_spent,
+        }))
+    }
+
+    async fn milestone_timestamp(&self) -> Result<u32> {
+        self.get_info()
+            .await?
+            .node_info
+            .status
+            .confirmed_milestone
+            .timestamp
+            .ok_or(Error::UnexpectedApiResponse)
+    }
+}
+
+/// A [`LedgerView`] of outputs held in memory.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct InMemoryLedger {
+    outputs: HashMap<OutputId, LedgerOutput>,
+    milestone_timestamp: u32,
+}
+
+impl InMemoryLedger {
+    /// Creates a new empty [`InMemoryLedger`] with the given milestone timestamp.
+    pub fn new(milestone_timestamp: u32) -> Self {
+        Self {
+            outputs: HashMap::new(),
+            milestone_timestamp,
+        }
+    }
+
+    /// Adds an unspent output to the ledger.
+    pub fn with_output(mut self, output_id: OutputId, output: Output) -> Self {
+        self.insert_output(output_id, output, false);
+        self
+    }
+
+    /// Inserts or replaces an output of the ledger.
+    pub fn insert_output(&mut self, output_id: OutputId, output: Output, is_spent: bool) {
+        self.outputs.insert(output_id, LedgerOutput { output, is_spent });
+    }
+
+    /// Sets the milestone timestamp transactions are validated against.
+    pub fn set_milestone_timestamp(&mut self, milestone_timestamp: u32) {
+        self.milestone_timestamp = milestone_timestamp;
+    }
+}
+
+#[async_trait]
+impl LedgerView for InMemoryLedger {
+    async fn output(&self, output_id: &OutputId) -> Result<Option<LedgerOutput>> {
+        Ok(self.outputs.get(output_id).cloned())
+    }
+
+    async fn milestone_timestamp(&self) -> Result<u32> {
+        Ok(self.milestone_timestamp)
+    }
+}
+
+/// Fully validates the semantic of transactions, fetching the consumed outputs and the milestone timestamp from a
+/// [`LedgerView`].
+#[derive(Clone, Debug)]
+pub struct TransactionValidator<L> {
+    ledger: L,
+}
+
+impl<L: LedgerView> TransactionValidator<L> {
+    /// Creates a new [`TransactionValidator`] using the given ledger.
+    pub fn new(ledger: L) -> Self {
+        Self { ledger }
+    }
+
+    /// Returns the ledger of the validator.
+    pub fn ledger(&self) -> &L {
+        &self.ledger
+    }
+
+    /// Validates the transaction and returns why it conflicts with the ledger, [`ConflictReason::None`] if it's valid.
+    pub async fn validate(&self, transaction: &TransactionPayload) -> Result<ConflictReason> {
+        Ok(self
+            .validate_with_diagnostic(transaction)
+            .await?
+            .map_or(ConflictReason::None, |diagnostic| diagnostic.conflict_reason()))
+    }
+
+    /// Validates the transaction and describes why it conflicts with the ledger, `None` if it's valid.
+    pub async fn validate_with_diagnostic(
+        &self,
+        transaction: &TransactionPayload,
+    ) -> Result<Option<ConflictDiagnostic>> {
+        let TransactionEssence::Regular(essence) = transaction.essence();
+        let mut consumed_outputs = Vec::with_capacity(essence.inputs().len());
+
+        for (input_index, input) in (0u16..).zip(essence.inputs()) {
+            let output_id = match input {
+                Input::Utxo(input) => *input.output_id(),
+                Input::Treasury(_) => {
+                    return Err(iota_types::block::Error::InvalidInputKind(TreasuryInput::KIND).into());
+                }
+            };
+
+            match self.ledger.output(&output_id).await? {
+                Some(LedgerOutput { is_spent: true, .. }) => {
+                    return Ok(Some(ConflictDiagnostic::InputUtxoAlreadySpent { input_index }));
+                }
+                Some(LedgerOutput { output, .. }) => consumed_outputs.push((output_id, output)),
+                None => return Ok(Some(ConflictDiagnostic::InputUtxoNotFound { input_index })),
+            }
+        }
+
+        let milestone_timestamp = self.ledger.milestone_timestamp().await?;
+        let transaction_id = transaction.id();
+        let inputs = consumed_outputs
+            .iter()
+            .map(|(output_id, output)| (*output_id, output))
+            .collect::<Vec<(OutputId, &Output)>>();
+
+        let context = ValidationContext::new(
+            &transaction_id,
+            essence,
+            inputs.iter().map(|(output_id, output)| (output_id, *output)),
+            transaction.unlocks(),
+            milestone_timestamp,
+        );
+
+        Ok(semantic_validation_with_diagnostic(
+            context,
+            inputs.as_slice(),
+            transaction.unlocks(),
+        )?)
+    }
+}
+
+impl Client {
+    /// Creates a [`TransactionValidator`] that validates transactions against the ledger of the node.
+    pub fn transaction_validator(&self) -> TransactionValidator<&Self> {
+        TransactionValidator::new(self)
+    }
+}
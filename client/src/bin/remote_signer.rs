@@ -0,0 +1,94 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Reference remote signer serving the requests of a `RemoteSecretManager` with any secret manager.
+//!
+//! Generate a key to share with the clients:
+//! `cargo run --bin remote_signer --features remote_signer,stronghold -- generate-key`
+//!
+//! Run the signer with a JSON config:
+//! `cargo run --bin remote_signer --features remote_signer,stronghold -- remote_signer.json`
+//!
+//! ```json
+//! {
+//!   "listen": { "unix": "/tmp/remote_signer.sock" },
+//!   "secretManager": { "stronghold": { "password": "...", "snapshotPath": "signer.stronghold" } },
+//!   "key": "0x...",
+//!   "allowList": {
+//!     "requests": ["generateAddresses", "signatureUnlock"],
+//!     "coinTypes": [4219],
+//!     "accountIndexes": [0]
+//!   }
+//! }
+//! ```
+//!
+//! Instead of a Unix domain socket, the signer can listen for HTTP requests with `{ "http": "127.0.0.1:14300" }`.
+
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::{net::SocketAddr, sync::Arc};
+
+use iota_client::{
+    crypto::utils::rand,
+    secret::{
+        remote::{RemoteSignerAllowList, RemoteSignerKey, RemoteSignerService},
+        SecretManager, SecretManagerDto,
+    },
+    Error, Result,
+};
+use serde::Deserialize;
+
+/// Where the signer listens for requests.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum Listen {
+    /// Address of the HTTP listener.
+    Http(SocketAddr),
+    /// Path of the Unix domain socket.
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Config {
+    listen: Listen,
+    secret_manager: SecretManagerDto,
+    key: String,
+    allow_list: RemoteSignerAllowList,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let argument = std::env::args()
+        .nth(1)
+        .ok_or(Error::MissingParameter("path of the config or `generate-key`"))?;
+
+    if argument == "generate-key" {
+        let mut key = [0u8; 32];
+        rand::fill(&mut key)?;
+        println!("{}", prefix_hex::encode(key));
+        return Ok(());
+    }
+
+    let config: Config = serde_json::from_str(&std::fs::read_to_string(argument)?)?;
+    let service = Arc::new(RemoteSignerService::new(
+        SecretManager::try_from(&config.secret_manager)?,
+        RemoteSignerKey::try_from_hex(&config.key)?,
+        config.allow_list.clone(),
+    ));
+
+    match &config.listen {
+        Listen::Http(address) => {
+            let listener = tokio::net::TcpListener::bind(address).await?;
+            println!("Remote signer listening on http://{address}");
+            service.serve_http(listener).await
+        }
+        #[cfg(unix)]
+        Listen::Unix(path) => {
+            let listener = tokio::net::UnixListener::bind(path)?;
+            println!("Remote signer listening on {}", path.display());
+            service.serve_unix(listener).await
+        }
+    }
+}
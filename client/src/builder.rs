@@ -21,9 +21,13 @@ use crate::{
     client::Client,
     constants::{DEFAULT_API_TIMEOUT, DEFAULT_REMOTE_POW_API_TIMEOUT, DEFAULT_TIPS_INTERVAL},
     error::Result,
+    node_api::{core::milestone_verifier::MilestoneVerifier, indexer::SnapshotReadMode},
     node_manager::{
         builder::validate_url,
+        cache::ResponseCache,
+        http_client::HttpTransport,
         node::{Node, NodeAuth},
+        retry::RetryPolicy,
     },
 };
 
@@ -131,6 +135,12 @@ pub struct ClientBuilder {
     /// The amount of threads to be used for proof of work
     #[serde(rename = "powWorkerCount", default)]
     pub pow_worker_count: Option<usize>,
+    /// How multi-page reads handle a ledger index that changes between requests
+    #[serde(rename = "snapshotRead", default)]
+    pub snapshot_read: SnapshotReadMode,
+    /// Verifies the milestones returned by nodes
+    #[serde(skip)]
+    pub(crate) milestone_verifier: Option<MilestoneVerifier>,
 }
 
 fn default_api_timeout() -> Duration {
@@ -164,6 +174,8 @@ impl Default for ClientBuilder {
             api_timeout: DEFAULT_API_TIMEOUT,
             remote_pow_timeout: DEFAULT_REMOTE_POW_API_TIMEOUT,
             pow_worker_count: None,
+            snapshot_read: SnapshotReadMode::default(),
+            milestone_verifier: None,
         }
     }
 }
@@ -274,6 +286,18 @@ impl ClientBuilder {
         self
     }
 
+    /// Sets how failed requests to nodes are retried, use [`RetryPolicy::no_retry()`] to disable retries.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.node_manager_builder = self.node_manager_builder.with_retry_policy(retry_policy);
+        self
+    }
+
+    /// Sets a cache for node API responses, like blocks, milestones and outputs, it's disabled by default.
+    pub fn with_response_cache(mut self, response_cache: ResponseCache) -> Self {
+        self.node_manager_builder = self.node_manager_builder.with_response_cache(response_cache);
+        self
+    }
+
     /// Sets the MQTT broker options.
     #[cfg(feature = "mqtt")]
     #[cfg_attr(docsrs, doc(cfg(feature = "mqtt")))]
@@ -318,6 +342,20 @@ impl ClientBuilder {
         self
     }
 
+    /// Sets how multi-page reads like [`Client::get_output_ids()`] handle a ledger index that changes between
+    /// requests.
+    pub fn with_snapshot_read(mut self, snapshot_read: SnapshotReadMode) -> Self {
+        self.snapshot_read = snapshot_read;
+        self
+    }
+
+    /// Sets a verifier for the milestones returned by nodes, which rejects milestones that aren't signed by the
+    /// committee or don't match the already verified milestones.
+    pub fn with_milestone_verifier(mut self, milestone_verifier: MilestoneVerifier) -> Self {
+        self.milestone_verifier.replace(milestone_verifier);
+        self
+    }
+
     /// Set User-Agent header for requests
     /// Default is "iota-client/{version}"
     pub fn with_user_agent(mut self, user_agent: String) -> Self {
@@ -325,42 +363,30 @@ impl ClientBuilder {
         self
     }
 
+    /// Sets a custom transport for HTTP requests to nodes, e.g. a caching proxy or an in-process mock.
+    /// Default is [`ReqwestTransport`](crate::node_manager::http_client::ReqwestTransport).
+    pub fn with_http_transport(mut self, http_transport: impl HttpTransport + 'static) -> Self {
+        self.node_manager_builder = self.node_manager_builder.with_http_transport(Arc::new(http_transport));
+        self
+    }
+
     /// Build the Client instance.
     pub fn finish(self) -> Result<Client> {
         let network_info = Arc::new(RwLock::new(self.network_info));
         let healthy_nodes = Arc::new(RwLock::new(HashMap::new()));
+        let node_manager = self.node_manager_builder.build(healthy_nodes);
 
         #[cfg(not(target_family = "wasm"))]
         let (runtime, sync_handle) = {
-            let nodes = self
-                .node_manager_builder
-                .primary_node
-                .iter()
-                .chain(self.node_manager_builder.nodes.iter())
-                .map(|node| node.clone().into())
-                .collect();
-
-            let healthy_nodes_ = healthy_nodes.clone();
+            let node_manager_ = node_manager.clone();
             let network_info_ = network_info.clone();
 
             let (runtime, sync_handle) = std::thread::spawn(move || {
                 let runtime = Runtime::new().expect("failed to create Tokio runtime");
-                if let Err(e) = runtime.block_on(Client::sync_nodes(
-                    &healthy_nodes_,
-                    &nodes,
-                    &network_info_,
-                    self.node_manager_builder.ignore_node_health,
-                )) {
+                if let Err(e) = runtime.block_on(Client::sync_nodes(&node_manager_, &network_info_)) {
                     panic!("failed to sync nodes: {e:?}");
                 }
-                let sync_handle = Client::start_sync_process(
-                    &runtime,
-                    healthy_nodes_,
-                    nodes,
-                    self.node_manager_builder.node_sync_interval,
-                    network_info_,
-                    self.node_manager_builder.ignore_node_health,
-                );
+                let sync_handle = Client::start_sync_process(&runtime, node_manager_, network_info_);
                 (runtime, sync_handle)
             })
             .join()
@@ -371,13 +397,13 @@ impl ClientBuilder {
         #[cfg(feature = "mqtt")]
         let (mqtt_event_tx, mqtt_event_rx) = tokio::sync::watch::channel(MqttEvent::Connected);
         let client = Client {
-            node_manager: self.node_manager_builder.build(healthy_nodes),
+            node_manager,
             #[cfg(not(target_family = "wasm"))]
             runtime,
             #[cfg(not(target_family = "wasm"))]
             sync_handle: sync_handle.map(Arc::new),
             #[cfg(feature = "mqtt")]
-            mqtt_client: Arc::new(tokio::sync::RwLock::new(None)),
+            mqtt_client: Default::default(),
             #[cfg(feature = "mqtt")]
             mqtt_topic_handlers: Default::default(),
             #[cfg(feature = "mqtt")]
@@ -388,6 +414,8 @@ impl ClientBuilder {
             api_timeout: self.api_timeout,
             remote_pow_timeout: self.remote_pow_timeout,
             pow_worker_count: self.pow_worker_count,
+            snapshot_read: self.snapshot_read,
+            milestone_verifier: self.milestone_verifier,
         };
         Ok(client)
     }
@@ -13,8 +13,7 @@ use iota_types::block::{output::RentStructure, protocol::ProtocolParameters};
 use tokio::runtime::Runtime;
 #[cfg(feature = "mqtt")]
 use {
-    crate::node_api::mqtt::{BrokerOptions, MqttEvent, TopicHandlerMap},
-    rumqttc::AsyncClient as MqttClient,
+    crate::node_api::mqtt::{BrokerOptions, MqttClientState, MqttEvent, TopicHandlerMap},
     tokio::sync::watch::{Receiver as WatchReceiver, Sender as WatchSender},
 };
 
@@ -22,6 +21,7 @@ use crate::{
     builder::{ClientBuilder, NetworkInfo},
     constants::DEFAULT_TIPS_INTERVAL,
     error::Result,
+    node_api::{core::milestone_verifier::MilestoneVerifier, indexer::SnapshotReadMode},
 };
 
 /// An instance of the client using HORNET or Bee URI
@@ -35,9 +35,9 @@ pub struct Client {
     /// Flag to stop the node syncing
     #[cfg(not(target_family = "wasm"))]
     pub(crate) sync_handle: Option<Arc<tokio::task::JoinHandle<()>>>,
-    /// A MQTT client to subscribe/unsubscribe to topics.
+    /// A MQTT client to subscribe/unsubscribe to topics, disconnected when the last client clone gets dropped.
     #[cfg(feature = "mqtt")]
-    pub(crate) mqtt_client: Arc<tokio::sync::RwLock<Option<MqttClient>>>,
+    pub(crate) mqtt_client: Arc<MqttClientState>,
     #[cfg(feature = "mqtt")]
     pub(crate) mqtt_topic_handlers: Arc<tokio::sync::RwLock<TopicHandlerMap>>,
     #[cfg(feature = "mqtt")]
@@ -49,6 +49,10 @@ pub struct Client {
     pub(crate) api_timeout: Duration,
     /// HTTP request timeout for remote PoW API call.
     pub(crate) remote_pow_timeout: Duration,
+    /// How multi-page reads handle a changing ledger index.
+    pub(crate) snapshot_read: SnapshotReadMode,
+    /// Verifies the milestones returned by nodes.
+    pub(crate) milestone_verifier: Option<MilestoneVerifier>,
     #[allow(dead_code)] // not used for wasm
     /// pow_worker_count for local PoW.
     pub(crate) pow_worker_count: Option<usize>,
@@ -82,19 +86,6 @@ impl Drop for Client {
                 runtime.shutdown_background();
             }
         }
-
-        #[cfg(feature = "mqtt")]
-        let mqtt_client = self.mqtt_client.clone();
-        #[cfg(feature = "mqtt")]
-        std::thread::spawn(move || {
-            crate::async_runtime::block_on(async move {
-                if let Some(mqtt_client) = mqtt_client.write().await.take() {
-                    mqtt_client.disconnect().await.unwrap();
-                }
-            });
-        })
-        .join()
-        .unwrap();
     }
 }
 
@@ -192,6 +183,16 @@ impl Client {
         self.remote_pow_timeout
     }
 
+    /// returns how multi-page reads handle a changing ledger index
+    pub fn get_snapshot_read(&self) -> SnapshotReadMode {
+        self.snapshot_read
+    }
+
+    /// returns the verifier of the milestones returned by nodes, if one is set
+    pub fn get_milestone_verifier(&self) -> Option<&MilestoneVerifier> {
+        self.milestone_verifier.as_ref()
+    }
+
     /// returns the fallback_to_local_pow
     pub fn get_fallback_to_local_pow(&self) -> bool {
         self.network_info
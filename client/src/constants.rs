@@ -15,11 +15,42 @@ pub(crate) const DEFAULT_RETRY_UNTIL_INCLUDED_MAX_AMOUNT: u64 = 40;
 pub(crate) const DEFAULT_TIPS_INTERVAL: u64 = 5;
 /// Interval in which the node info will be requested and healthy nodes will be added to the healthy node pool
 pub(crate) const NODE_SYNC_INTERVAL: Duration = Duration::from_secs(60);
+/// Amount of consecutive failed requests after which a node gets benched
+pub(crate) const NODE_BENCH_FAILURE_THRESHOLD: u32 = 3;
+/// Duration for which a node gets benched, doubled with every further failed request
+pub(crate) const NODE_BENCH_BASE_DURATION: Duration = Duration::from_secs(5);
+/// Maximum duration for which a node gets benched
+pub(crate) const NODE_BENCH_MAX_DURATION: Duration = Duration::from_secs(300);
+/// Default amount of attempts for failed requests, including the first one
+pub(crate) const DEFAULT_RETRY_MAX_ATTEMPTS: u32 = 3;
+pub(crate) const DEFAULT_RETRY_INITIAL_BACKOFF: Duration = Duration::from_millis(200);
+pub(crate) const DEFAULT_RETRY_MAX_BACKOFF: Duration = Duration::from_secs(5);
+/// HTTP status codes of transient node errors which are retried by default
+pub(crate) const DEFAULT_RETRY_STATUS_CODES: [u16; 4] = [429, 502, 503, 504];
 pub(crate) const DEFAULT_MIN_QUORUM_SIZE: usize = 3;
 pub(crate) const DEFAULT_QUORUM_THRESHOLD: usize = 66;
+/// How often a quorum request is sent, if indexer responses aren't pinned to the same ledger index
+pub(crate) const QUORUM_LEDGER_INDEX_ROUNDS: usize = 3;
+pub(crate) const QUORUM_LEDGER_INDEX_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+/// Default size bound of the in-memory response cache, in bytes
+pub(crate) const DEFAULT_RESPONSE_CACHE_MAX_SIZE: usize = 16 * 1024 * 1024;
+/// Default time for which mutable responses, like output metadata, are cached
+pub(crate) const DEFAULT_RESPONSE_CACHE_MUTABLE_TTL: Duration = Duration::from_secs(5);
 pub(crate) const DEFAULT_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
 #[cfg(not(target_family = "wasm"))]
 pub(crate) const MAX_PARALLEL_API_REQUESTS: usize = 100;
+/// Default amount of requests of a batch, like [`Client::get_blocks()`](crate::Client::get_blocks), that are sent at
+/// the same time
+pub(crate) const DEFAULT_BATCH_MAX_PARALLEL_REQUESTS: usize = 25;
+/// Max amount of milestones a [`MilestoneVerifier`](crate::node_api::core::milestone_verifier::MilestoneVerifier)
+/// keeps to verify the chain
+pub(crate) const MAX_VERIFIED_MILESTONES: usize = 1000;
+/// Default interval in which the indexer is requested by an
+/// [`AddressWatcherBuilder`](crate::api::AddressWatcherBuilder)
+pub(crate) const DEFAULT_ADDRESS_WATCHER_POLL_INTERVAL: Duration = Duration::from_secs(30);
+/// Default amount of MQTT events that are buffered for a [`TopicEventStream`](crate::mqtt::TopicEventStream)
+#[cfg(feature = "mqtt")]
+pub(crate) const DEFAULT_MQTT_STREAM_CAPACITY: usize = 1000;
 /// Max allowed difference between the local time and latest milestone time, 5 minutes in seconds
 pub(crate) const FIVE_MINUTES_IN_SECONDS: u32 = 300;
 
@@ -5,14 +5,22 @@
 
 use std::fmt::Debug;
 
-use iota_types::block::semantic::ConflictReason;
+use iota_types::block::{
+    output::OutputId,
+    payload::milestone::MilestoneValidationError,
+    semantic::{ConflictError, ConflictReason},
+};
 use packable::error::UnexpectedEOF;
 use serde::{
     ser::{SerializeMap, Serializer},
     Serialize,
 };
 
-use crate::{api::input_selection::Error as InputSelectionError, node_api::indexer::QueryParameter};
+use crate::{
+    api::input_selection::Error as InputSelectionError,
+    node_api::{core::milestone_verifier::MilestoneChainError, indexer::QueryParameter},
+    node_manager::quorum::QuorumResponse,
+};
 
 /// Type alias of `Result` in iota-client
 pub type Result<T> = std::result::Result<T, Error>;
@@ -33,12 +41,18 @@ pub enum Error {
     /// Block types error
     #[error("{0}")]
     Block(#[from] iota_types::block::Error),
+    /// Invalid conflict reason in an API response
+    #[error("{0}")]
+    Conflict(#[from] ConflictError),
     /// The wallet account has enough funds, but split on too many outputs
     #[error("the wallet account has enough funds, but split on too many outputs: {0}, max. is 128, consolidate them")]
     ConsolidationRequired(usize),
     /// Crypto.rs error
     #[error("{0}")]
     Crypto(#[from] crypto::Error),
+    /// A custom secret manager can't be created from its DTO
+    #[error("a custom secret manager can't be created from its DTO")]
+    CustomSecretManagerDto,
     /// Address not found
     #[error("address: {address} not found in range: {range}")]
     InputAddressNotFound {
@@ -53,9 +67,20 @@ pub enum Error {
     /// Invalid BIP32 chain data
     #[error("invalid BIP32 chain data")]
     InvalidBIP32ChainData,
+    /// A milestone isn't signed by enough keys applicable to its index
+    #[error("invalid milestone {index}: {error}")]
+    InvalidMilestone {
+        /// The index of the milestone.
+        index: u32,
+        /// Why the signatures are invalid.
+        error: MilestoneValidationError,
+    },
     /// Invalid mnemonic error
     #[error("invalid mnemonic {0}")]
     InvalidMnemonic(String),
+    /// A typed indexer query is invalid
+    #[error("invalid output query: {0}")]
+    InvalidOutputQuery(String),
     /// The transaction essence is too large
     #[error("the transaction essence is too large. Its length is {length}, max length is {max_length}")]
     InvalidRegularTransactionEssenceLength {
@@ -72,9 +97,36 @@ pub enum Error {
         /// The max length.
         max_length: usize,
     },
+    /// IO error
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
     /// JSON error
     #[error("{0}")]
     Json(#[from] serde_json::Error),
+    /// The ledger of the node is older than required
+    #[error("the ledger index of the node {actual} is older than the required ledger index {required}")]
+    LedgerIndexBehind {
+        /// The required ledger index.
+        required: u32,
+        /// The ledger index of the node.
+        actual: u32,
+    },
+    /// The ledger index changed while reading multiple pages
+    #[error("ledger index changed during snapshot read: {expected} != {actual}")]
+    LedgerIndexChanged {
+        /// The ledger index of the first page.
+        expected: u32,
+        /// The ledger index of the later page.
+        actual: u32,
+    },
+    /// A milestone doesn't match the chain of verified milestones
+    #[error("milestone {index} doesn't match the verified milestone chain: {error}")]
+    MilestoneChain {
+        /// The index of the milestone.
+        index: u32,
+        /// Why the milestone doesn't match.
+        error: MilestoneChainError,
+    },
     /// Missing required parameters
     #[error("must provide required parameter: {0}")]
     MissingParameter(&'static str),
@@ -87,6 +139,9 @@ pub enum Error {
     /// The requested data was not found.
     #[error("the requested data {0} was not found.")]
     NotFound(String),
+    /// Neither the primary node nor another node with a JWT is available for a protected route
+    #[error("no primary node or node with a JWT available for the protected route {0}")]
+    NoProtectedRouteNode(String),
     /// Output Error
     #[error("output error: {0}")]
     Output(&'static str),
@@ -118,6 +173,19 @@ pub enum Error {
         /// The minimum quorum threshold.
         minimum_threshold: usize,
     },
+    /// Error on quorum because the nodes returned different responses
+    #[error(
+        "failed to reach quorum: {quorum_size} < {minimum_threshold}, nodes returned different responses: {}",
+        .responses.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+    )]
+    QuorumMismatch {
+        /// The amount of nodes that returned the most common response.
+        quorum_size: usize,
+        /// The minimum quorum threshold.
+        minimum_threshold: usize,
+        /// The responses of the nodes.
+        responses: Vec<QuorumResponse>,
+    },
     /// Error from RestAPI calls with unexpected status code response
     #[error("response error with status code {code}: {text}, URL: {url}")]
     ResponseError {
@@ -137,6 +205,18 @@ pub enum Error {
     /// No node available in the healthy node pool
     #[error("no healthy node available")]
     HealthyNodePoolEmpty,
+    /// An output was read at an older ledger index than the listing of its ID
+    #[error(
+        "output {output_id} was read at ledger index {ledger_index}, older than the listing at {listing_ledger_index}"
+    )]
+    StaleOutput {
+        /// The output ID.
+        output_id: OutputId,
+        /// The ledger index the output was read at.
+        ledger_index: u32,
+        /// The ledger index of the output ID listing.
+        listing_ledger_index: u32,
+    },
     /// Error when building tagged_data blocks
     #[error("error when building tagged_data block: {0}")]
     TaggedData(String),
@@ -163,6 +243,14 @@ pub enum Error {
     /// Unexpected API response error
     #[error("unexpected API response")]
     UnexpectedApiResponse,
+    /// The node returned another milestone than the requested one
+    #[error("requested milestone {requested}, but the node returned milestone {returned}")]
+    UnexpectedMilestone {
+        /// The ID or index of the requested milestone.
+        requested: String,
+        /// The ID or index of the returned milestone.
+        returned: String,
+    },
     /// An indexer API request contains a query parameter not supported by the endpoint.
     #[error("an indexer API request contains a query parameter not supported by the endpoint: {0}.")]
     UnsupportedQueryParameter(QueryParameter),
@@ -223,6 +311,25 @@ pub enum Error {
     #[error("MQTT error {0}")]
     Mqtt(#[from] crate::node_api::mqtt::Error),
 
+    //////////////////////////////////////////////////////////////////////
+    // Remote signer
+    //////////////////////////////////////////////////////////////////////
+    /// Remote signer error
+    #[cfg(feature = "remote_signer")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "remote_signer")))]
+    #[error("remote signer error: {0}")]
+    RemoteSigner(String),
+    /// Remote signer message failed authentication
+    #[cfg(feature = "remote_signer")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "remote_signer")))]
+    #[error("remote signer message failed authentication")]
+    RemoteSignerAuthentication,
+    /// Remote signer request not allowed
+    #[cfg(feature = "remote_signer")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "remote_signer")))]
+    #[error("remote signer request not allowed: {0}")]
+    RemoteSignerRequestNotAllowed(String),
+
     //////////////////////////////////////////////////////////////////////
     // Stronghold
     //////////////////////////////////////////////////////////////////////
@@ -236,6 +343,11 @@ pub enum Error {
     #[cfg_attr(docsrs, doc(cfg(feature = "stronghold")))]
     #[error("invalid stronghold password")]
     StrongholdInvalidPassword,
+    /// A file isn't a Stronghold snapshot
+    #[cfg(feature = "stronghold")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "stronghold")))]
+    #[error("the file isn't a Stronghold snapshot")]
+    StrongholdInvalidSnapshot,
     /// No password has been supplied to a Stronghold vault, or it has been cleared
     #[cfg(feature = "stronghold")]
     #[cfg_attr(docsrs, doc(cfg(feature = "stronghold")))]
@@ -246,6 +358,11 @@ pub enum Error {
     #[cfg_attr(docsrs, doc(cfg(feature = "stronghold")))]
     #[error("stronghold memory error: {0}")]
     StrongholdMemory(#[from] iota_stronghold::MemoryError),
+    /// A Stronghold snapshot has another key derivation than the one it's loaded with and needs to be migrated
+    #[cfg(feature = "stronghold")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "stronghold")))]
+    #[error("the Stronghold snapshot has the version {0:?} and needs to be migrated")]
+    StrongholdMigrationRequired(crate::stronghold::migrate::SnapshotVersion),
     /// A mnemonic has been already stored into a Stronghold vault
     #[cfg(feature = "stronghold")]
     #[cfg_attr(docsrs, doc(cfg(feature = "stronghold")))]
@@ -261,6 +378,26 @@ pub enum Error {
     #[cfg_attr(docsrs, doc(cfg(feature = "stronghold")))]
     #[error("Stronghold reported a procedure error: {0}")]
     StrongholdProcedure(#[from] iota_stronghold::procedures::ProcedureError),
+    /// A seed slot already holds a seed
+    #[cfg(feature = "stronghold")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "stronghold")))]
+    #[error("the seed slot `{0}` already holds a seed")]
+    StrongholdSeedSlotAlreadyExists(String),
+    /// A seed slot doesn't hold a seed
+    #[cfg(feature = "stronghold")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "stronghold")))]
+    #[error("the seed slot `{0}` doesn't hold a seed")]
+    StrongholdSeedSlotNotFound(String),
+    /// Unsupported version of a seed slot backup
+    #[cfg(feature = "stronghold")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "stronghold")))]
+    #[error("unsupported seed slot backup version: {0}")]
+    StrongholdUnsupportedSeedSlotBackupVersion(u16),
+    /// Unsupported file format version of a Stronghold snapshot
+    #[cfg(feature = "stronghold")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "stronghold")))]
+    #[error("unsupported Stronghold snapshot file format version: {0}")]
+    StrongholdUnsupportedSnapshotFileVersion(u16),
 }
 
 // map most errors to a single error but there are some errors that
@@ -88,11 +88,6 @@ mod async_runtime {
 
     static RUNTIME: OnceCell<Mutex<Runtime>> = OnceCell::new();
 
-    pub(crate) fn block_on<C: futures::Future>(cb: C) -> C::Output {
-        let runtime = RUNTIME.get_or_init(|| Mutex::new(Runtime::new().expect("failed to create Tokio runtime")));
-        runtime.lock().expect("failed to lock the runtime.").block_on(cb)
-    }
-
     pub(crate) fn spawn<F>(future: F)
     where
         F: futures::Future + Send + 'static,
@@ -104,8 +99,15 @@ mod async_runtime {
 }
 
 fn unix_timestamp_now() -> u32 {
+    unix_time().as_secs() as u32
+}
+
+fn unix_timestamp_millis() -> u64 {
+    unix_time().as_millis() as u64
+}
+
+fn unix_time() -> std::time::Duration {
     instant::SystemTime::now()
         .duration_since(instant::SystemTime::UNIX_EPOCH)
         .expect("time went backwards")
-        .as_secs() as u32
 }
@@ -1,6 +1,9 @@
 // Copyright 2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+#[cfg(feature = "stronghold")]
+use std::path::PathBuf;
+
 use iota_types::block::{
     address::AliasAddress,
     output::{
@@ -23,14 +26,17 @@ use serde::Deserialize;
 
 #[cfg(feature = "mqtt")]
 use crate::mqtt::Topic;
+#[cfg(feature = "stronghold")]
+use crate::stronghold::migrate::SnapshotKeyDerivation;
 use crate::{
     api::{
         ClientBlockBuilderOptions as BuildBlockOptions, GetAddressesBuilderOptions as GenerateAddressesOptions,
         PreparedTransactionDataDto, RemainderDataDto,
     },
-    node_api::indexer::query_parameters::QueryParameter,
+    node_api::{core::routes::PruneDatabaseTarget, indexer::query_parameters::QueryParameter},
     node_manager::node::NodeAuth,
     secret::{types::InputSigningDataDto, SecretManagerDto},
+    MnemonicLanguage, MnemonicLength,
 };
 
 /// Each public client method.
@@ -202,6 +208,27 @@ pub enum Message {
         /// Mnemonic
         mnemonic: String,
     },
+    /// Migrate a Stronghold snapshot to a key derivation, in place with a backup or to a new path.
+    /// Expected response:
+    /// [`MigratedStrongholdSnapshot`](crate::message_interface::Response::MigratedStrongholdSnapshot)
+    #[cfg(feature = "stronghold")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "stronghold")))]
+    MigrateStrongholdSnapshot {
+        /// Path of the snapshot
+        #[serde(rename = "snapshotPath")]
+        snapshot_path: PathBuf,
+        /// Password of the snapshot
+        password: String,
+        /// Path to write the migrated snapshot to, instead of migrating it in place
+        #[serde(rename = "newSnapshotPath")]
+        new_snapshot_path: Option<PathBuf>,
+        /// Key derivation to migrate the snapshot to, the default one if not set
+        #[serde(rename = "keyDerivation")]
+        key_derivation: Option<SnapshotKeyDerivation>,
+        /// Password to encrypt the migrated snapshot with, instead of `password`
+        #[serde(rename = "newPassword")]
+        new_password: Option<String>,
+    },
     /// Build a block containing the specified payload and post it to the network.
     PostBlockPayload {
         /// The payload to send
@@ -227,6 +254,27 @@ pub enum Message {
     GetInfo,
     /// Get peers
     GetPeers,
+    /// Get a peer by its peer ID
+    GetPeer {
+        /// Peer ID
+        #[serde(rename = "peerId")]
+        peer_id: String,
+    },
+    /// Add a peer by its multiaddress
+    AddPeer {
+        /// Multiaddress of the peer
+        #[serde(rename = "multiAddress")]
+        multi_address: String,
+        /// Alias of the peer
+        alias: Option<String>,
+    },
+    /// Remove a peer by its peer ID
+    /// Expected response: [`Ok`](crate::message_interface::Response::Ok)
+    RemovePeer {
+        /// Peer ID
+        #[serde(rename = "peerId")]
+        peer_id: String,
+    },
     /// Get tips
     GetTips,
     /// Post block (JSON)
@@ -270,6 +318,15 @@ pub enum Message {
         #[serde(rename = "outputId")]
         output_id: OutputId,
     },
+    /// Get output in its latest state, read by a node whose ledger is at least at the given ledger index
+    GetOutputAtLeastAtLedgerIndex {
+        /// Output ID
+        #[serde(rename = "outputId")]
+        output_id: OutputId,
+        /// Ledger index
+        #[serde(rename = "ledgerIndex")]
+        ledger_index: u32,
+    },
     /// Get the milestone by the given milestone id.
     GetMilestoneById {
         /// Milestone ID
@@ -325,6 +382,28 @@ pub enum Message {
         #[serde(rename = "transactionId")]
         transaction_id: TransactionId,
     },
+    /// Compute the merkle roots of the white flag traversal of a milestone with the given parents
+    PostWhiteFlag {
+        /// Milestone index
+        index: u32,
+        /// Milestone timestamp
+        timestamp: u32,
+        /// Parents of the milestone
+        parents: Vec<BlockId>,
+        /// Milestone ID of the previous milestone
+        #[serde(rename = "previousMilestoneId")]
+        previous_milestone_id: MilestoneId,
+    },
+    /// Prune the database of the node
+    PruneDatabase {
+        /// Target up to which the database is pruned
+        target: PruneDatabaseTarget,
+    },
+    /// Create a snapshot of the node at the given milestone index
+    CreateSnapshot {
+        /// Milestone index
+        index: u32,
+    },
 
     //////////////////////////////////////////////////////////////////////
     // Node indexer API
@@ -388,6 +467,72 @@ pub enum Message {
         #[serde(rename = "outputIds")]
         output_ids: Vec<OutputId>,
     },
+    /// Get blocks by their block IDs, requests are sent in parallel and failed requests don't abort the batch
+    GetBlocks {
+        /// Block IDs
+        #[serde(rename = "blockIds")]
+        block_ids: Vec<BlockId>,
+        /// Maximum amount of requests that are sent at the same time
+        #[serde(rename = "maxParallelRequests")]
+        max_parallel_requests: Option<usize>,
+    },
+    /// Get the metadata of blocks by their block IDs, requests are sent in parallel and failed requests don't abort
+    /// the batch
+    GetBlocksMetadata {
+        /// Block IDs
+        #[serde(rename = "blockIds")]
+        block_ids: Vec<BlockId>,
+        /// Maximum amount of requests that are sent at the same time
+        #[serde(rename = "maxParallelRequests")]
+        max_parallel_requests: Option<usize>,
+    },
+    /// Get the metadata of outputs by their output IDs, requests are sent in parallel and failed requests don't abort
+    /// the batch
+    GetOutputsMetadata {
+        /// Output IDs
+        #[serde(rename = "outputIds")]
+        output_ids: Vec<OutputId>,
+        /// Maximum amount of requests that are sent at the same time
+        #[serde(rename = "maxParallelRequests")]
+        max_parallel_requests: Option<usize>,
+    },
+    /// Get the included blocks of transactions by their transaction IDs, requests are sent in parallel and failed
+    /// requests don't abort the batch
+    GetIncludedBlocks {
+        /// Transaction IDs
+        #[serde(rename = "transactionIds")]
+        transaction_ids: Vec<TransactionId>,
+        /// Maximum amount of requests that are sent at the same time
+        #[serde(rename = "maxParallelRequests")]
+        max_parallel_requests: Option<usize>,
+    },
+    /// Get milestones by their milestone indexes, requests are sent in parallel and failed requests don't abort the
+    /// batch
+    GetMilestones {
+        /// Milestone indexes
+        indexes: Vec<u32>,
+        /// Maximum amount of requests that are sent at the same time
+        #[serde(rename = "maxParallelRequests")]
+        max_parallel_requests: Option<usize>,
+    },
+    /// Get milestones by their milestone IDs, requests are sent in parallel and failed requests don't abort the batch
+    GetMilestonesById {
+        /// Milestone IDs
+        #[serde(rename = "milestoneIds")]
+        milestone_ids: Vec<MilestoneId>,
+        /// Maximum amount of requests that are sent at the same time
+        #[serde(rename = "maxParallelRequests")]
+        max_parallel_requests: Option<usize>,
+    },
+    /// Get the UTXO changes of milestones by their milestone indexes, requests are sent in parallel and failed
+    /// requests don't abort the batch
+    GetUtxoChanges {
+        /// Milestone indexes
+        indexes: Vec<u32>,
+        /// Maximum amount of requests that are sent at the same time
+        #[serde(rename = "maxParallelRequests")]
+        max_parallel_requests: Option<usize>,
+    },
     /// Find all blocks by provided block IDs.
     FindBlocks {
         /// BlockIDs
@@ -520,7 +665,14 @@ pub enum Message {
         address: String,
     },
     /// Generates a new mnemonic.
-    GenerateMnemonic,
+    GenerateMnemonic {
+        /// Number of words, 24 if not set
+        #[serde(default)]
+        length: Option<MnemonicLength>,
+        /// Language of the wordlist, English if not set
+        #[serde(default)]
+        language: Option<MnemonicLanguage>,
+    },
     /// Returns a hex encoded seed for a mnemonic.
     MnemonicToHexSeed {
         /// Mnemonic
@@ -30,8 +30,11 @@ use {
 
 #[cfg(feature = "ledger_nano")]
 use crate::secret::ledger_nano::LedgerSecretManager;
+#[cfg(feature = "stronghold")]
+use crate::stronghold::migrate::{migrate_snapshot, SnapshotMigrationTarget};
 use crate::{
     api::{PreparedTransactionData, PreparedTransactionDataDto, RemainderData},
+    constants::DEFAULT_BATCH_MAX_PARALLEL_REQUESTS,
     message_interface::{message::Message, response::Response},
     request_funds_from_faucet,
     secret::{types::InputSigningData, SecretManage, SecretManager},
@@ -111,9 +114,6 @@ impl ClientMessageHandler {
                 }
                 // convert types to DTOs
                 let payload = match &topic_event.payload {
-                    MqttPayload::Json(val) => {
-                        serde_json::to_string(&val).expect("failed to serialize MqttPayload::Json")
-                    }
                     MqttPayload::Block(block) => {
                         serde_json::to_string(&BlockDto::from(block)).expect("failed to serialize MqttPayload::Block")
                     }
@@ -121,9 +121,18 @@ impl ClientMessageHandler {
                         .expect("failed to serialize MqttPayload::MilestonePayload"),
                     MqttPayload::Receipt(receipt) => serde_json::to_string(&ReceiptMilestoneOptionDto::from(receipt))
                         .expect("failed to serialize MqttPayload::Receipt"),
+                    MqttPayload::MilestoneInfo(milestone_info) => {
+                        serde_json::to_string(milestone_info).expect("failed to serialize MqttPayload::MilestoneInfo")
+                    }
+                    MqttPayload::BlockMetadata(block_metadata) => {
+                        serde_json::to_string(block_metadata).expect("failed to serialize MqttPayload::BlockMetadata")
+                    }
+                    MqttPayload::OutputWithMetadata(output) => {
+                        serde_json::to_string(output).expect("failed to serialize MqttPayload::OutputWithMetadata")
+                    }
                 };
                 let response = MqttResponse {
-                    topic: topic_event.topic.clone(),
+                    topic: topic_event.topic.to_string(),
                     payload,
                 };
 
@@ -167,6 +176,16 @@ impl ClientMessageHandler {
             Message::StoreMnemonic { .. } => {
                 log::debug!("Response: StoreMnemonic{{ <omitted> }}")
             }
+            #[cfg(feature = "stronghold")]
+            Message::MigrateStrongholdSnapshot {
+                snapshot_path,
+                new_snapshot_path,
+                ..
+            } => {
+                log::debug!(
+                    "Response: MigrateStrongholdSnapshot{{ snapshot_path: {snapshot_path:?}, new_snapshot_path: {new_snapshot_path:?}, <omitted> }}"
+                )
+            }
             Message::ConsolidateFunds {
                 secret_manager: _,
                 generate_addresses_options,
@@ -459,6 +478,29 @@ impl ClientMessageHandler {
 
                 Ok(Response::Ok)
             }
+            #[cfg(feature = "stronghold")]
+            Message::MigrateStrongholdSnapshot {
+                snapshot_path,
+                mut password,
+                new_snapshot_path,
+                key_derivation,
+                mut new_password,
+            } => {
+                let target =
+                    new_snapshot_path.map_or(SnapshotMigrationTarget::InPlace, SnapshotMigrationTarget::NewPath);
+                let migration = migrate_snapshot(
+                    snapshot_path,
+                    &password,
+                    target,
+                    key_derivation.unwrap_or_default(),
+                    new_password.as_deref(),
+                );
+
+                password.zeroize();
+                new_password.zeroize();
+
+                Ok(Response::MigratedStrongholdSnapshot(migration?))
+            }
             Message::PostBlockPayload { payload_dto } => {
                 let block_builder = self.client.block();
 
@@ -481,6 +523,14 @@ impl ClientMessageHandler {
             Message::GetNodeInfo { url, auth } => Ok(Response::NodeInfo(Client::get_node_info(&url, auth).await?)),
             Message::GetInfo => Ok(Response::Info(self.client.get_info().await?)),
             Message::GetPeers => Ok(Response::Peers(self.client.get_peers().await?)),
+            Message::GetPeer { peer_id } => Ok(Response::Peer(self.client.get_peer(&peer_id).await?)),
+            Message::AddPeer { multi_address, alias } => Ok(Response::Peer(
+                self.client.add_peer(&multi_address, alias.as_deref()).await?,
+            )),
+            Message::RemovePeer { peer_id } => {
+                self.client.remove_peer(&peer_id).await?;
+                Ok(Response::Ok)
+            }
             Message::GetTips => Ok(Response::Tips(self.client.get_tips().await?)),
             Message::PostBlockRaw { block_bytes } => Ok(Response::BlockId(
                 self.client
@@ -506,6 +556,14 @@ impl ClientMessageHandler {
             )),
             Message::GetBlockRaw { block_id } => Ok(Response::BlockRaw(self.client.get_block_raw(&block_id).await?)),
             Message::GetOutput { output_id } => Ok(Response::Output(self.client.get_output(&output_id).await?)),
+            Message::GetOutputAtLeastAtLedgerIndex {
+                output_id,
+                ledger_index,
+            } => Ok(Response::Output(
+                self.client
+                    .get_output_at_least_at_ledger_index(&output_id, ledger_index)
+                    .await?,
+            )),
             Message::GetOutputMetadata { output_id } => Ok(Response::OutputMetadata(
                 self.client.get_output_metadata(&output_id).await?,
             )),
@@ -538,6 +596,20 @@ impl ClientMessageHandler {
             Message::GetIncludedBlockMetadata { transaction_id } => Ok(Response::BlockMetadata(
                 self.client.get_included_block_metadata(&transaction_id).await?,
             )),
+            Message::PostWhiteFlag {
+                index,
+                timestamp,
+                parents,
+                previous_milestone_id,
+            } => Ok(Response::WhiteFlag(
+                self.client
+                    .post_white_flag(index, timestamp, &parents, &previous_milestone_id)
+                    .await?,
+            )),
+            Message::PruneDatabase { target } => {
+                Ok(Response::PrunedDatabase(self.client.prune_database(target).await?))
+            }
+            Message::CreateSnapshot { index } => Ok(Response::Snapshot(self.client.create_snapshot(index).await?)),
             Message::BasicOutputIds { query_parameters } => Ok(Response::OutputIdsResponse(
                 self.client.basic_output_ids(query_parameters).await?,
             )),
@@ -559,6 +631,104 @@ impl ClientMessageHandler {
             Message::TryGetOutputs { output_ids } => {
                 Ok(Response::Outputs(self.client.try_get_outputs(output_ids).await?))
             }
+            Message::GetBlocks {
+                block_ids,
+                max_parallel_requests,
+            } => Ok(Response::BatchBlocks(
+                self.client
+                    .get_blocks(
+                        &block_ids,
+                        max_parallel_requests.unwrap_or(DEFAULT_BATCH_MAX_PARALLEL_REQUESTS),
+                    )
+                    .await
+                    .into_iter()
+                    .map(|block| block.map(|block| BlockDto::from(&block)).into())
+                    .collect(),
+            )),
+            Message::GetBlocksMetadata {
+                block_ids,
+                max_parallel_requests,
+            } => Ok(Response::BatchBlocksMetadata(
+                self.client
+                    .get_blocks_metadata(
+                        &block_ids,
+                        max_parallel_requests.unwrap_or(DEFAULT_BATCH_MAX_PARALLEL_REQUESTS),
+                    )
+                    .await
+                    .into_iter()
+                    .map(Into::into)
+                    .collect(),
+            )),
+            Message::GetOutputsMetadata {
+                output_ids,
+                max_parallel_requests,
+            } => Ok(Response::BatchOutputsMetadata(
+                self.client
+                    .get_outputs_metadata(
+                        &output_ids,
+                        max_parallel_requests.unwrap_or(DEFAULT_BATCH_MAX_PARALLEL_REQUESTS),
+                    )
+                    .await
+                    .into_iter()
+                    .map(Into::into)
+                    .collect(),
+            )),
+            Message::GetIncludedBlocks {
+                transaction_ids,
+                max_parallel_requests,
+            } => Ok(Response::BatchBlocks(
+                self.client
+                    .get_included_blocks(
+                        &transaction_ids,
+                        max_parallel_requests.unwrap_or(DEFAULT_BATCH_MAX_PARALLEL_REQUESTS),
+                    )
+                    .await
+                    .into_iter()
+                    .map(|block| block.map(|block| BlockDto::from(&block)).into())
+                    .collect(),
+            )),
+            Message::GetMilestones {
+                indexes,
+                max_parallel_requests,
+            } => Ok(Response::BatchMilestones(
+                self.client
+                    .get_milestones(
+                        &indexes,
+                        max_parallel_requests.unwrap_or(DEFAULT_BATCH_MAX_PARALLEL_REQUESTS),
+                    )
+                    .await
+                    .into_iter()
+                    .map(|milestone| milestone.map(|milestone| MilestonePayloadDto::from(&milestone)).into())
+                    .collect(),
+            )),
+            Message::GetMilestonesById {
+                milestone_ids,
+                max_parallel_requests,
+            } => Ok(Response::BatchMilestones(
+                self.client
+                    .get_milestones_by_id(
+                        &milestone_ids,
+                        max_parallel_requests.unwrap_or(DEFAULT_BATCH_MAX_PARALLEL_REQUESTS),
+                    )
+                    .await
+                    .into_iter()
+                    .map(|milestone| milestone.map(|milestone| MilestonePayloadDto::from(&milestone)).into())
+                    .collect(),
+            )),
+            Message::GetUtxoChanges {
+                indexes,
+                max_parallel_requests,
+            } => Ok(Response::BatchMilestoneUtxoChanges(
+                self.client
+                    .get_utxo_changes(
+                        &indexes,
+                        max_parallel_requests.unwrap_or(DEFAULT_BATCH_MAX_PARALLEL_REQUESTS),
+                    )
+                    .await
+                    .into_iter()
+                    .map(Into::into)
+                    .collect(),
+            )),
             Message::FindBlocks { block_ids } => Ok(Response::Blocks(
                 self.client
                     .find_blocks(&block_ids)
@@ -643,7 +813,9 @@ impl ClientMessageHandler {
                 &Client::parse_bech32_address(&address)?,
             ))),
             Message::IsAddressValid { address } => Ok(Response::IsAddressValid(Client::is_address_valid(&address))),
-            Message::GenerateMnemonic => Ok(Response::GeneratedMnemonic(Client::generate_mnemonic()?)),
+            Message::GenerateMnemonic { length, language } => Ok(Response::GeneratedMnemonic(
+                Client::generate_mnemonic_with_language(length.unwrap_or_default(), language.unwrap_or_default())?,
+            )),
             Message::MnemonicToHexSeed { mut mnemonic } => {
                 let response = Response::MnemonicHexSeed(Client::mnemonic_to_hex_seed(&mnemonic)?);
 
@@ -7,7 +7,11 @@ mod message;
 mod message_handler;
 mod response;
 
-pub use self::{message::Message, message_handler::ClientMessageHandler, response::Response};
+pub use self::{
+    message::Message,
+    message_handler::ClientMessageHandler,
+    response::{BatchResult, Response},
+};
 use crate::{ClientBuilder, Result};
 
 /// Create message handler with client options
@@ -9,8 +9,9 @@ use iota_types::{
         core::{
             dto::{PeerDto, ReceiptDto},
             response::{
-                BlockMetadataResponse, InfoResponse as NodeInfo, OutputWithMetadataResponse, TreasuryResponse,
-                UtxoChangesResponse as MilestoneUTXOChanges,
+                BlockMetadataResponse, CreateSnapshotsResponse, InfoResponse as NodeInfo, OutputWithMetadataResponse,
+                PruneDatabaseResponse, TreasuryResponse, UtxoChangesResponse as MilestoneUTXOChanges,
+                WhiteFlagResponse,
             },
         },
         plugins::indexer::OutputIdsResponse,
@@ -35,6 +36,8 @@ use serde::Serialize;
 
 #[cfg(feature = "ledger_nano")]
 use crate::secret::LedgerNanoStatus;
+#[cfg(feature = "stronghold")]
+use crate::stronghold::migrate::SnapshotMigration;
 use crate::{api::PreparedTransactionDataDto, node_manager::node::Node, Error, NetworkInfoDto, NodeInfoWrapper};
 
 /// The response message.
@@ -83,6 +86,11 @@ pub enum Response {
     #[cfg_attr(docsrs, doc(cfg(feature = "ledger_nano")))]
     LedgerNanoStatus(LedgerNanoStatus),
     /// Response for:
+    /// - [`MigrateStrongholdSnapshot`](crate::message_interface::Message::MigrateStrongholdSnapshot)
+    #[cfg(feature = "stronghold")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "stronghold")))]
+    MigratedStrongholdSnapshot(SnapshotMigration),
+    /// Response for:
     /// - [`PrepareTransaction`](crate::message_interface::Message::PrepareTransaction)
     PreparedTransactionData(PreparedTransactionDataDto),
     /// Response for:
@@ -108,6 +116,19 @@ pub enum Response {
     /// - [`GetPeers`](crate::message_interface::Message::GetPeers)
     Peers(Vec<PeerDto>),
     /// Response for:
+    /// - [`GetPeer`](crate::message_interface::Message::GetPeer)
+    /// - [`AddPeer`](crate::message_interface::Message::AddPeer)
+    Peer(PeerDto),
+    /// Response for:
+    /// - [`PostWhiteFlag`](crate::message_interface::Message::PostWhiteFlag)
+    WhiteFlag(WhiteFlagResponse),
+    /// Response for:
+    /// - [`PruneDatabase`](crate::message_interface::Message::PruneDatabase)
+    PrunedDatabase(PruneDatabaseResponse),
+    /// Response for:
+    /// - [`CreateSnapshot`](crate::message_interface::Message::CreateSnapshot)
+    Snapshot(CreateSnapshotsResponse),
+    /// Response for:
     /// - [`GetTips`](crate::message_interface::Message::GetTips)
     Tips(Vec<BlockId>),
     /// Response for:
@@ -127,6 +148,7 @@ pub enum Response {
     BlockRaw(Vec<u8>),
     /// Response for:
     /// - [`GetOutput`](crate::message_interface::Message::GetOutput)
+    /// - [`GetOutputAtLeastAtLedgerIndex`](crate::message_interface::Message::GetOutputAtLeastAtLedgerIndex)
     Output(OutputWithMetadataResponse),
     /// Response for:
     /// - [`GetOutputMetadata`](crate::message_interface::Message::GetOutputMetadata)
@@ -170,6 +192,23 @@ pub enum Response {
     /// - [`FindBlocks`](crate::message_interface::Message::FindBlocks)
     Blocks(Vec<BlockDto>),
     /// Response for:
+    /// - [`GetBlocks`](crate::message_interface::Message::GetBlocks)
+    /// - [`GetIncludedBlocks`](crate::message_interface::Message::GetIncludedBlocks)
+    BatchBlocks(Vec<BatchResult<BlockDto>>),
+    /// Response for:
+    /// - [`GetBlocksMetadata`](crate::message_interface::Message::GetBlocksMetadata)
+    BatchBlocksMetadata(Vec<BatchResult<BlockMetadataResponse>>),
+    /// Response for:
+    /// - [`GetOutputsMetadata`](crate::message_interface::Message::GetOutputsMetadata)
+    BatchOutputsMetadata(Vec<BatchResult<OutputMetadataDto>>),
+    /// Response for:
+    /// - [`GetMilestones`](crate::message_interface::Message::GetMilestones)
+    /// - [`GetMilestonesById`](crate::message_interface::Message::GetMilestonesById)
+    BatchMilestones(Vec<BatchResult<MilestonePayloadDto>>),
+    /// Response for:
+    /// - [`GetUtxoChanges`](crate::message_interface::Message::GetUtxoChanges)
+    BatchMilestoneUtxoChanges(Vec<BatchResult<MilestoneUTXOChanges>>),
+    /// Response for:
     /// - [`RetryUntilIncluded`](crate::message_interface::Message::RetryUntilIncluded)
     RetryUntilIncludedSuccessful(Vec<(BlockId, BlockDto)>),
     /// Response for:
@@ -232,6 +271,7 @@ pub enum Response {
     TransactionEssenceHash(String),
     /// Response for:
     /// - [`ClearListeners`](crate::message_interface::Message::ClearListeners)
+    /// - [`RemovePeer`](crate::message_interface::Message::RemovePeer)
     /// - [`StoreMnemonic`](crate::message_interface::Message::StoreMnemonic)
     Ok,
     /// Response for any method that returns an error.
@@ -239,3 +279,22 @@ pub enum Response {
     /// Response for any method that panics.
     Panic(String),
 }
+
+/// The result of a single request of a batch, like [`GetBlocks`](crate::message_interface::Message::GetBlocks).
+#[derive(Serialize, Debug)]
+#[serde(tag = "type", content = "payload", rename_all = "camelCase")]
+pub enum BatchResult<T> {
+    /// The requested item.
+    Ok(T),
+    /// The error of the failed request.
+    Error(Error),
+}
+
+impl<T> From<crate::Result<T>> for BatchResult<T> {
+    fn from(result: crate::Result<T>) -> Self {
+        match result {
+            Ok(value) => Self::Ok(value),
+            Err(e) => Self::Error(e),
+        }
+    }
+}
@@ -0,0 +1,365 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Verification of milestones against committee keys and the chain of already verified milestones
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+use iota_types::block::payload::milestone::{MilestoneId, MilestonePayload};
+use serde::{Deserialize, Serialize};
+
+use crate::{constants::MAX_VERIFIED_MILESTONES, Error, Result};
+
+/// A public key of the milestone committee and the milestone indexes it's applicable to.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MilestoneKeyRange {
+    /// The hex encoded Ed25519 public key.
+    pub public_key: String,
+    /// The first milestone index the key is applicable to.
+    pub start_index: u32,
+    /// The last milestone index the key is applicable to, `None` if it doesn't expire.
+    pub end_index: Option<u32>,
+}
+
+impl MilestoneKeyRange {
+    /// Creates a new [`MilestoneKeyRange`].
+    pub fn new(public_key: impl Into<String>, start_index: u32, end_index: Option<u32>) -> Self {
+        Self {
+            public_key: public_key.into(),
+            start_index,
+            end_index,
+        }
+    }
+
+    /// Returns whether the key is applicable to the milestone index.
+    pub fn contains(&self, index: u32) -> bool {
+        self.start_index <= index && self.end_index.map_or(true, |end_index| index <= end_index)
+    }
+}
+
+/// Why a milestone doesn't match the chain of verified milestones.
+#[derive(Clone, Debug, Eq, PartialEq, thiserror::Error)]
+pub enum MilestoneChainError {
+    /// Another milestone with the same index was verified.
+    #[error("conflicts with the verified milestone {0}")]
+    Fork(MilestoneId),
+    /// The milestone doesn't reference the verified milestone before it, or the one after it doesn't reference it.
+    #[error("expected the previous milestone {expected}, found {found}")]
+    PreviousMilestoneMismatch {
+        /// The ID of the verified milestone.
+        expected: MilestoneId,
+        /// The ID referenced as previous milestone.
+        found: MilestoneId,
+    },
+    /// The timestamp is older than the one of a verified milestone with a lower index, or newer than the one of a
+    /// verified milestone with a higher index.
+    #[error(
+        "timestamp {timestamp} isn't in order with the timestamp {verified_timestamp} of milestone {verified_index}"
+    )]
+    TimestampNotMonotonic {
+        /// The timestamp of the milestone.
+        timestamp: u32,
+        /// The index of the verified milestone.
+        verified_index: u32,
+        /// The timestamp of the verified milestone.
+        verified_timestamp: u32,
+    },
+}
+
+#[derive(Clone, Copy, Debug)]
+struct VerifiedMilestone {
+    id: MilestoneId,
+    previous_milestone_id: MilestoneId,
+    timestamp: u32,
+}
+
+impl From<&MilestonePayload> for VerifiedMilestone {
+    fn from(milestone: &MilestonePayload) -> Self {
+        Self {
+            id: milestone.id(),
+            previous_milestone_id: *milestone.essence().previous_milestone_id(),
+            timestamp: milestone.essence().timestamp(),
+        }
+    }
+}
+
+/// Verifies the signatures of milestones and that they form a single chain.
+///
+/// The signatures are checked against the keys applicable to the milestone index. With the previously verified
+/// milestones, the previous milestone IDs have to link up, timestamps can't decrease with the index and no two
+/// milestones can share an index.
+///
+/// Set with [`ClientBuilder::with_milestone_verifier()`](crate::ClientBuilder::with_milestone_verifier) to verify
+/// the milestones returned by [`Client::get_milestone_by_id()`](crate::Client::get_milestone_by_id),
+/// [`Client::get_milestone_by_index()`](crate::Client::get_milestone_by_index) and received via MQTT. Clones share
+/// the verified milestones.
+#[derive(Clone)]
+pub struct MilestoneVerifier {
+    key_ranges: Arc<Vec<MilestoneKeyRange>>,
+    threshold: usize,
+    verified: Arc<Mutex<BTreeMap<u32, VerifiedMilestone>>>,
+}
+
+impl MilestoneVerifier {
+    /// Creates a verifier requiring `threshold` valid signatures of the keys applicable to a milestone index.
+    pub fn new(key_ranges: Vec<MilestoneKeyRange>, threshold: usize) -> Self {
+        Self {
+            key_ranges: Arc::new(key_ranges),
+            threshold,
+            verified: Default::default(),
+        }
+    }
+
+    /// Adds a milestone that's trusted without verification, e.g. from a checkpoint, which following milestones have
+    /// to link up with.
+    pub fn with_trusted_milestone(self, milestone: &MilestonePayload) -> Self {
+        self.verified
+            .lock()
+            .expect("failed to lock the verified milestones")
+            .insert(*milestone.essence().index(), VerifiedMilestone::from(milestone));
+        self
+    }
+
+    /// Returns the hex encoded public keys applicable to the milestone index.
+    pub fn applicable_public_keys(&self, index: u32) -> Vec<String> {
+        self.key_ranges
+            .iter()
+            .filter(|key_range| key_range.contains(index))
+            .map(|key_range| {
+                key_range
+                    .public_key
+                    .strip_prefix("0x")
+                    .unwrap_or(&key_range.public_key)
+                    .to_lowercase()
+            })
+            .collect()
+    }
+
+    /// Returns the index of the latest verified milestone.
+    pub fn latest_verified_index(&self) -> Option<u32> {
+        self.verified
+            .lock()
+            .expect("failed to lock the verified milestones")
+            .keys()
+            .next_back()
+            .copied()
+    }
+
+    /// Verifies the milestone and adds it to the verified milestones.
+    pub fn verify(&self, milestone: &MilestonePayload) -> Result<()> {
+        let index = *milestone.essence().index();
+        let mut verified = self.verified.lock().expect("failed to lock the verified milestones");
+
+        let milestone_id = milestone.id();
+        if let Some(verified_milestone) = verified.get(&index) {
+            return if verified_milestone.id == milestone_id {
+                Ok(())
+            } else {
+                Err(Error::MilestoneChain {
+                    index,
+                    error: MilestoneChainError::Fork(verified_milestone.id),
+                })
+            };
+        }
+
+        milestone
+            .validate(&self.applicable_public_keys(index), self.threshold)
+            .map_err(|error| Error::InvalidMilestone { index, error })?;
+
+        let milestone = VerifiedMilestone::from(milestone);
+        let chain_error = |error| Error::MilestoneChain { index, error };
+
+        if let Some((&previous_index, previous)) = verified.range(..index).next_back() {
+            if previous.timestamp > milestone.timestamp {
+                return Err(chain_error(MilestoneChainError::TimestampNotMonotonic {
+                    timestamp: milestone.timestamp,
+                    verified_index: previous_index,
+                    verified_timestamp: previous.timestamp,
+                }));
+            }
+            if previous_index + 1 == index && previous.id != milestone.previous_milestone_id {
+                return Err(chain_error(MilestoneChainError::PreviousMilestoneMismatch {
+                    expected: previous.id,
+                    found: milestone.previous_milestone_id,
+                }));
+            }
+        }
+
+        if let Some((&next_index, next)) = verified.range(index..).next() {
+            if next.timestamp < milestone.timestamp {
+                return Err(chain_error(MilestoneChainError::TimestampNotMonotonic {
+                    timestamp: milestone.timestamp,
+                    verified_index: next_index,
+                    verified_timestamp: next.timestamp,
+                }));
+            }
+            if next_index - 1 == index && next.previous_milestone_id != milestone_id {
+                return Err(chain_error(MilestoneChainError::PreviousMilestoneMismatch {
+                    expected: milestone_id,
+                    found: next.previous_milestone_id,
+                }));
+            }
+        }
+
+        verified.insert(index, milestone);
+        // Forget the milestone furthest away from the verified one, so the chain can grow in both directions.
+        if verified.len() > MAX_VERIFIED_MILESTONES {
+            let (&first, &last) = (verified.keys().next().unwrap(), verified.keys().next_back().unwrap());
+            verified.remove(if index - first > last - index { &first } else { &last });
+        }
+
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for MilestoneVerifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MilestoneVerifier")
+            .field("key_ranges", &self.key_ranges)
+            .field("threshold", &self.threshold)
+            .finish()
+    }
+}
+
+impl PartialEq for MilestoneVerifier {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.verified, &other.verified)
+    }
+}
+
+impl Eq for MilestoneVerifier {}
+
+#[cfg(test)]
+mod tests {
+    use crypto::signatures::ed25519::SecretKey;
+    use iota_types::block::{
+        parent::Parents,
+        payload::milestone::{MerkleRoot, MilestoneEssence, MilestoneOptions},
+        rand::block::rand_block_ids,
+        signature::{Ed25519Signature, Signature},
+    };
+
+    use super::*;
+
+    fn secret_key(seed: u8) -> SecretKey {
+        SecretKey::from_bytes([seed; 32])
+    }
+
+    fn public_key(seed: u8) -> String {
+        prefix_hex::encode(secret_key(seed).public_key().to_bytes())
+    }
+
+    fn milestone(index: u32, timestamp: u32, previous_milestone_id: MilestoneId, signers: &[u8]) -> MilestonePayload {
+        let essence = MilestoneEssence::new(
+            index.into(),
+            timestamp,
+            2,
+            previous_milestone_id,
+            Parents::new(rand_block_ids(1)).unwrap(),
+            MerkleRoot::null(),
+            MerkleRoot::null(),
+            Vec::new(),
+            MilestoneOptions::new(Vec::new()).unwrap(),
+        )
+        .unwrap();
+        let hash = essence.hash();
+        let mut signatures = signers
+            .iter()
+            .map(|seed| {
+                let secret_key = secret_key(*seed);
+                Signature::Ed25519(Ed25519Signature::new(
+                    secret_key.public_key().to_bytes(),
+                    secret_key.sign(&hash).to_bytes(),
+                ))
+            })
+            .collect::<Vec<_>>();
+        signatures.sort_by_key(|Signature::Ed25519(signature)| *signature.public_key());
+
+        MilestonePayload::new(essence, signatures).unwrap()
+    }
+
+    fn verifier() -> MilestoneVerifier {
+        // Key 3 replaces key 1 from milestone 11 on.
+        MilestoneVerifier::new(
+            vec![
+                MilestoneKeyRange::new(public_key(1), 0, Some(10)),
+                MilestoneKeyRange::new(public_key(2), 0, None),
+                MilestoneKeyRange::new(public_key(3), 11, None),
+            ],
+            2,
+        )
+    }
+
+    #[test]
+    fn verify_chain() {
+        let verifier = verifier();
+        let first = milestone(10, 100, MilestoneId::null(), &[1, 2]);
+        let second = milestone(11, 110, first.id(), &[2, 3]);
+
+        verifier.verify(&second).unwrap();
+        verifier.verify(&first).unwrap();
+        // Verifying the same milestone again is fine.
+        verifier.verify(&first).unwrap();
+        assert_eq!(verifier.latest_verified_index(), Some(11));
+    }
+
+    #[test]
+    fn verify_key_rotation() {
+        let verifier = verifier();
+
+        // Key 1 expired, so only one applicable signature is left.
+        assert!(matches!(
+            verifier.verify(&milestone(11, 110, MilestoneId::null(), &[1, 2])),
+            Err(Error::InvalidMilestone { index: 11, .. })
+        ));
+        assert!(matches!(
+            verifier.verify(&milestone(10, 100, MilestoneId::null(), &[2])),
+            Err(Error::InvalidMilestone { index: 10, .. })
+        ));
+    }
+
+    #[test]
+    fn reject_fork() {
+        let verifier = verifier();
+        let first = milestone(10, 100, MilestoneId::null(), &[1, 2]);
+        verifier.verify(&first).unwrap();
+
+        assert!(matches!(
+            verifier.verify(&milestone(10, 101, MilestoneId::null(), &[1, 2])),
+            Err(Error::MilestoneChain {
+                index: 10,
+                error: MilestoneChainError::Fork(_)
+            })
+        ));
+        assert!(matches!(
+            verifier.verify(&milestone(11, 110, MilestoneId::null(), &[2, 3])),
+            Err(Error::MilestoneChain {
+                index: 11,
+                error: MilestoneChainError::PreviousMilestoneMismatch { .. }
+            })
+        ));
+        assert!(matches!(
+            verifier.verify(&milestone(12, 90, MilestoneId::null(), &[2, 3])),
+            Err(Error::MilestoneChain {
+                index: 12,
+                error: MilestoneChainError::TimestampNotMonotonic { verified_index: 10, .. }
+            })
+        ));
+    }
+
+    #[test]
+    fn trusted_milestone() {
+        // The trusted milestone isn't signed by applicable keys.
+        let trusted = milestone(10, 100, MilestoneId::null(), &[4]);
+        let verifier = verifier().with_trusted_milestone(&trusted);
+
+        verifier.verify(&milestone(11, 110, trusted.id(), &[2, 3])).unwrap();
+        assert!(verifier
+            .verify(&milestone(11, 110, MilestoneId::null(), &[2, 3]))
+            .is_err());
+    }
+}
@@ -3,11 +3,20 @@
 
 //! IOTA node core API
 
+pub mod milestone_verifier;
 pub mod routes;
 
+use futures::{Future, StreamExt};
 use iota_types::{
-    api::core::response::OutputWithMetadataResponse,
-    block::output::{dto::OutputMetadataDto, OutputId},
+    api::core::response::{BlockMetadataResponse, OutputWithMetadataResponse, UtxoChangesResponse},
+    block::{
+        output::{dto::OutputMetadataDto, OutputId},
+        payload::{
+            milestone::{MilestoneId, MilestonePayload},
+            transaction::TransactionId,
+        },
+        Block, BlockId,
+    },
 };
 
 #[cfg(not(target_family = "wasm"))]
@@ -101,4 +110,104 @@ impl Client {
 
         Ok(output_metadata_responses)
     }
+
+    /// Requests blocks by their block ID, at most `max_parallel_requests` at the same time.
+    /// The results are in the order of the block IDs, a failed request doesn't abort the others.
+    pub async fn get_blocks(&self, block_ids: &[BlockId], max_parallel_requests: usize) -> Vec<Result<Block>> {
+        batch(block_ids, max_parallel_requests, |block_id| self.get_block(block_id)).await
+    }
+
+    /// Requests the metadata of blocks by their block ID, at most `max_parallel_requests` at the same time.
+    /// The results are in the order of the block IDs, a failed request doesn't abort the others.
+    pub async fn get_blocks_metadata(
+        &self,
+        block_ids: &[BlockId],
+        max_parallel_requests: usize,
+    ) -> Vec<Result<BlockMetadataResponse>> {
+        batch(block_ids, max_parallel_requests, |block_id| {
+            self.get_block_metadata(block_id)
+        })
+        .await
+    }
+
+    /// Requests the metadata of outputs by their output ID, at most `max_parallel_requests` at the same time.
+    /// The results are in the order of the output IDs, a failed request doesn't abort the others.
+    pub async fn get_outputs_metadata(
+        &self,
+        output_ids: &[OutputId],
+        max_parallel_requests: usize,
+    ) -> Vec<Result<OutputMetadataDto>> {
+        batch(output_ids, max_parallel_requests, |output_id| {
+            self.get_output_metadata(output_id)
+        })
+        .await
+    }
+
+    /// Requests the blocks that were included in the ledger for the given transaction IDs, at most
+    /// `max_parallel_requests` at the same time.
+    /// The results are in the order of the transaction IDs, a failed request doesn't abort the others.
+    pub async fn get_included_blocks(
+        &self,
+        transaction_ids: &[TransactionId],
+        max_parallel_requests: usize,
+    ) -> Vec<Result<Block>> {
+        batch(transaction_ids, max_parallel_requests, |transaction_id| {
+            self.get_included_block(transaction_id)
+        })
+        .await
+    }
+
+    /// Requests milestones by their milestone ID, at most `max_parallel_requests` at the same time.
+    /// The results are in the order of the milestone IDs, a failed request doesn't abort the others.
+    pub async fn get_milestones_by_id(
+        &self,
+        milestone_ids: &[MilestoneId],
+        max_parallel_requests: usize,
+    ) -> Vec<Result<MilestonePayload>> {
+        batch(milestone_ids, max_parallel_requests, |milestone_id| {
+            self.get_milestone_by_id(milestone_id)
+        })
+        .await
+    }
+
+    /// Requests milestones by their milestone index, at most `max_parallel_requests` at the same time.
+    /// The results are in the order of the milestone indexes, a failed request doesn't abort the others.
+    pub async fn get_milestones(&self, indexes: &[u32], max_parallel_requests: usize) -> Vec<Result<MilestonePayload>> {
+        batch(indexes, max_parallel_requests, |index| {
+            self.get_milestone_by_index(*index)
+        })
+        .await
+    }
+
+    /// Requests the UTXO changes of milestones by their milestone index, at most `max_parallel_requests` at the same
+    /// time.
+    /// The results are in the order of the milestone indexes, a failed request doesn't abort the others.
+    pub async fn get_utxo_changes(
+        &self,
+        indexes: &[u32],
+        max_parallel_requests: usize,
+    ) -> Vec<Result<UtxoChangesResponse>> {
+        batch(indexes, max_parallel_requests, |index| {
+            self.get_utxo_changes_by_index(*index)
+        })
+        .await
+    }
+}
+
+// Sends a request for every item, at most `max_parallel_requests` at the same time, and returns the results in the
+// order of the items.
+async fn batch<'a, I, T, F, Fut>(items: &'a [I], max_parallel_requests: usize, request: F) -> Vec<Result<T>>
+where
+    I: Sync,
+    F: FnMut(&'a I) -> Fut + Send,
+    Fut: Future<Output = Result<T>> + Send,
+    T: Send,
+{
+    // The futures are created upfront, they only send their request once they're polled by the buffered stream.
+    let requests = items.iter().map(request).collect::<Vec<_>>();
+
+    futures::stream::iter(requests)
+        .buffered(max_parallel_requests.max(1))
+        .collect()
+        .await
 }
@@ -9,9 +9,10 @@ use iota_types::{
     api::core::{
         dto::{PeerDto, ReceiptDto},
         response::{
-            BlockMetadataResponse, BlockResponse, InfoResponse, MilestoneResponse, OutputWithMetadataResponse,
-            PeersResponse, ReceiptsResponse, RoutesResponse, SubmitBlockResponse, TipsResponse, TreasuryResponse,
-            UtxoChangesResponse,
+            AddPeerResponse, BlockMetadataResponse, BlockResponse, CreateSnapshotsResponse, InfoResponse,
+            MilestoneResponse, OutputWithMetadataResponse, PeerResponse, PeersResponse, PruneDatabaseResponse,
+            ReceiptsResponse, RoutesResponse, SubmitBlockResponse, TipsResponse, TreasuryResponse, UtxoChangesResponse,
+            WhiteFlagResponse,
         },
     },
     block::{
@@ -20,6 +21,7 @@ use iota_types::{
             milestone::{MilestoneId, MilestonePayload},
             transaction::TransactionId,
         },
+        protocol::ProtocolParameters,
         Block, BlockDto, BlockId,
     },
 };
@@ -28,10 +30,26 @@ use url::Url;
 
 use crate::{
     constants::{DEFAULT_API_TIMEOUT, DEFAULT_USER_AGENT},
-    node_manager::node::{Node, NodeAuth},
+    node_manager::{
+        cache::CachePolicy,
+        http_client::{HttpClient, HttpMethod},
+        node::{Node, NodeAuth},
+    },
     Client, Error, Result,
 };
 
+/// The target up to which [`Client::prune_database()`] prunes the database of a node.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PruneDatabaseTarget {
+    /// Prunes all milestones up to and including this milestone index.
+    Index(u32),
+    /// Keeps this amount of the latest milestones and prunes the older ones.
+    Depth(u32),
+    /// Prunes the oldest milestones until the database is smaller than this size, e.g. "30GB".
+    TargetDatabaseSize(String),
+}
+
 /// NodeInfo wrapper which contains the node info and the url from the node (useful when multiple nodes are used)
 #[derive(Debug, Serialize, Deserialize)]
 pub struct NodeInfoWrapper {
@@ -52,7 +70,9 @@ impl Client {
 
         let mut url = Url::parse(url)?;
         url.set_path(path);
-        let status = crate::node_manager::http_client::HttpClient::new(DEFAULT_USER_AGENT.to_string())
+        let status = self
+            .node_manager
+            .http_client
             .get(
                 Node {
                     url,
@@ -92,6 +112,15 @@ impl Client {
 
     /// GET /api/core/v2/info endpoint
     pub async fn get_node_info(url: &str, auth: Option<NodeAuth>) -> Result<InfoResponse> {
+        Self::get_node_info_with_http_client(&HttpClient::new(DEFAULT_USER_AGENT.to_string()), url, auth).await
+    }
+
+    /// GET /api/core/v2/info endpoint, requested with the given [`HttpClient`].
+    pub(crate) async fn get_node_info_with_http_client(
+        http_client: &HttpClient,
+        url: &str,
+        auth: Option<NodeAuth>,
+    ) -> Result<InfoResponse> {
         let mut url = crate::node_manager::builder::validate_url(Url::parse(url)?)?;
         if let Some(auth) = &auth {
             if let Some((name, password)) = &auth.basic_auth_name_pwd {
@@ -103,7 +132,7 @@ impl Client {
         let path = "api/core/v2/info";
         url.set_path(path);
 
-        let resp: InfoResponse = crate::node_manager::http_client::HttpClient::new(DEFAULT_USER_AGENT.to_string())
+        let resp: InfoResponse = http_client
             .get(
                 Node {
                     url,
@@ -113,8 +142,7 @@ impl Client {
                 DEFAULT_API_TIMEOUT,
             )
             .await?
-            .into_json()
-            .await?;
+            .into_json()?;
 
         Ok(resp)
     }
@@ -284,8 +312,7 @@ impl Client {
         let path = &format!("api/core/v2/blocks/{block_id}");
 
         let resp = self
-            .node_manager
-            .get_request::<BlockResponse>(path, None, self.get_timeout(), false, true)
+            .get_cached_request::<BlockResponse>(path, false, true, CachePolicy::Immutable)
             .await?;
 
         match resp {
@@ -300,7 +327,7 @@ impl Client {
         let path = &format!("api/core/v2/blocks/{block_id}");
 
         self.node_manager
-            .get_request_bytes(path, None, self.get_timeout())
+            .get_request_bytes(path, None, self.get_timeout(), true)
             .await
     }
 
@@ -321,9 +348,7 @@ impl Client {
     pub async fn get_output(&self, output_id: &OutputId) -> Result<OutputWithMetadataResponse> {
         let path = &format!("api/core/v2/outputs/{output_id}");
 
-        self.node_manager
-            .get_request(path, None, self.get_timeout(), false, true)
-            .await
+        self.get_cached_request(path, false, true, CachePolicy::Mutable).await
     }
 
     /// Finds an output, as raw bytes, by its OutputId (TransactionId + output_index).
@@ -332,17 +357,43 @@ impl Client {
         let path = &format!("api/core/v2/outputs/{output_id}");
 
         self.node_manager
-            .get_request_bytes(path, None, self.get_timeout())
+            .get_request_bytes(path, None, self.get_timeout(), true)
             .await
     }
 
+    /// Finds an output, as JSON, by its OutputId, as read by a node whose ledger is at least at `ledger_index`.
+    /// This only checks the ledger index of the node, the output is returned in its latest state and not as it was at
+    /// `ledger_index`. Returns [`Error::LedgerIndexBehind`] if the ledger of the node is older, e.g. because it's not
+    /// synced yet.
+    /// GET /api/core/v2/outputs/{outputId}
+    pub async fn get_output_at_least_at_ledger_index(
+        &self,
+        output_id: &OutputId,
+        ledger_index: u32,
+    ) -> Result<OutputWithMetadataResponse> {
+        let path = &format!("api/core/v2/outputs/{output_id}");
+
+        let output = self
+            .node_manager
+            .get_request::<OutputWithMetadataResponse>(path, None, self.get_timeout(), false, true)
+            .await?;
+
+        if output.metadata.ledger_index < ledger_index {
+            return Err(Error::LedgerIndexBehind {
+                required: ledger_index,
+                actual: output.metadata.ledger_index,
+            });
+        }
+
+        Ok(output)
+    }
+
     /// Get the metadata for a given `OutputId` (TransactionId + output_index).
     /// GET /api/core/v2/outputs/{outputId}/metadata
     pub async fn get_output_metadata(&self, output_id: &OutputId) -> Result<OutputMetadataDto> {
         let path = &format!("api/core/v2/outputs/{output_id}/metadata");
 
-        self.node_manager
-            .get_request::<OutputMetadataDto>(path, None, self.get_timeout(), false, true)
+        self.get_cached_request::<OutputMetadataDto>(path, false, true, CachePolicy::Mutable)
             .await
     }
 
@@ -389,8 +440,7 @@ impl Client {
         let path = &format!("api/core/v2/transactions/{transaction_id}/included-block");
 
         let resp = self
-            .node_manager
-            .get_request::<BlockResponse>(path, None, self.get_timeout(), true, true)
+            .get_cached_request::<BlockResponse>(path, true, true, CachePolicy::Immutable)
             .await?;
 
         match resp {
@@ -405,7 +455,7 @@ impl Client {
         let path = &format!("api/core/v2/transactions/{transaction_id}/included-block");
 
         self.node_manager
-            .get_request_bytes(path, None, self.get_timeout())
+            .get_request_bytes(path, None, self.get_timeout(), true)
             .await
     }
 
@@ -425,19 +475,20 @@ impl Client {
     /// GET /api/core/v2/milestones/{milestoneId}
     pub async fn get_milestone_by_id(&self, milestone_id: &MilestoneId) -> Result<MilestonePayload> {
         let path = &format!("api/core/v2/milestones/{milestone_id}");
-
-        let resp = self
-            .node_manager
-            .get_request::<MilestoneResponse>(path, None, self.get_timeout(), false, true)
-            .await?;
-
-        match resp {
-            MilestoneResponse::Json(dto) => Ok(MilestonePayload::try_from_dto(
-                &dto,
-                &self.get_protocol_parameters().await?,
-            )?),
-            MilestoneResponse::Raw(_) => Err(crate::Error::UnexpectedApiResponse),
-        }
+        let protocol_parameters = self.get_protocol_parameters().await?;
+
+        self.get_verified_cached_request(
+            path,
+            false,
+            true,
+            CachePolicy::Immutable,
+            |response: &MilestoneResponse| {
+                self.verify_milestone_response(response, &protocol_parameters, |milestone| {
+                    (milestone.id() != *milestone_id).then(|| (milestone_id.to_string(), milestone.id().to_string()))
+                })
+            },
+        )
+        .await
     }
 
     /// Gets the milestone by the given milestone id.
@@ -446,7 +497,7 @@ impl Client {
         let path = &format!("api/core/v2/milestones/{milestone_id}");
 
         self.node_manager
-            .get_request_bytes(path, None, self.get_timeout())
+            .get_request_bytes(path, None, self.get_timeout(), true)
             .await
     }
 
@@ -455,8 +506,7 @@ impl Client {
     pub async fn get_utxo_changes_by_id(&self, milestone_id: &MilestoneId) -> Result<UtxoChangesResponse> {
         let path = &format!("api/core/v2/milestones/{milestone_id}/utxo-changes");
 
-        self.node_manager
-            .get_request(path, None, self.get_timeout(), false, false)
+        self.get_cached_request(path, false, false, CachePolicy::Immutable)
             .await
     }
 
@@ -464,19 +514,44 @@ impl Client {
     /// GET /api/core/v2/milestones/{index}
     pub async fn get_milestone_by_index(&self, index: u32) -> Result<MilestonePayload> {
         let path = &format!("api/core/v2/milestones/by-index/{index}");
+        let protocol_parameters = self.get_protocol_parameters().await?;
+
+        self.get_verified_cached_request(
+            path,
+            false,
+            true,
+            CachePolicy::Immutable,
+            |response: &MilestoneResponse| {
+                self.verify_milestone_response(response, &protocol_parameters, |milestone| {
+                    let returned = *milestone.essence().index();
+                    (returned != index).then(|| (index.to_string(), returned.to_string()))
+                })
+            },
+        )
+        .await
+    }
 
-        let resp = self
-            .node_manager
-            .get_request::<MilestoneResponse>(path, None, self.get_timeout(), false, true)
-            .await?;
-
-        match resp {
-            MilestoneResponse::Json(dto) => Ok(MilestonePayload::try_from_dto(
-                &dto,
-                &self.get_protocol_parameters().await?,
-            )?),
-            MilestoneResponse::Raw(_) => Err(crate::Error::UnexpectedApiResponse),
+    // Converts a milestone response, rejects it if `mismatch` returns the requested and the returned milestone, and
+    // checks it with the milestone verifier, if one is set.
+    fn verify_milestone_response(
+        &self,
+        response: &MilestoneResponse,
+        protocol_parameters: &ProtocolParameters,
+        mismatch: impl FnOnce(&MilestonePayload) -> Option<(String, String)>,
+    ) -> Result<MilestonePayload> {
+        let milestone = match response {
+            MilestoneResponse::Json(dto) => MilestonePayload::try_from_dto(dto, protocol_parameters)?,
+            MilestoneResponse::Raw(_) => return Err(crate::Error::UnexpectedApiResponse),
+        };
+        // Checked first, so a wrong milestone never reaches the milestone chain of the verifier.
+        if let Some((requested, returned)) = mismatch(&milestone) {
+            return Err(crate::Error::UnexpectedMilestone { requested, returned });
         }
+        if let Some(milestone_verifier) = &self.milestone_verifier {
+            milestone_verifier.verify(&milestone)?;
+        }
+
+        Ok(milestone)
     }
 
     /// Gets the milestone by the given milestone index.
@@ -485,7 +560,7 @@ impl Client {
         let path = &format!("api/core/v2/milestones/by-index/{index}");
 
         self.node_manager
-            .get_request_bytes(path, None, self.get_timeout())
+            .get_request_bytes(path, None, self.get_timeout(), true)
             .await
     }
 
@@ -494,42 +569,136 @@ impl Client {
     pub async fn get_utxo_changes_by_index(&self, index: u32) -> Result<UtxoChangesResponse> {
         let path = &format!("api/core/v2/milestones/by-index/{index}/utxo-changes");
 
-        self.node_manager
-            .get_request(path, None, self.get_timeout(), false, false)
+        self.get_cached_request(path, false, false, CachePolicy::Immutable)
             .await
     }
 
     // Peers routes.
 
+    /// Returns all peers of the node. This is a protected route, if neither a primary node nor a node with a JWT is
+    /// set, the request is sent to any node.
     /// GET /api/core/v2/peers
     pub async fn get_peers(&self) -> Result<Vec<PeerDto>> {
         let path = "api/core/v2/peers";
 
+        let resp = match self
+            .node_manager
+            .protected_request(HttpMethod::Get, path, self.get_timeout(), None)
+            .await
+        {
+            // Nodes that don't protect the route still answer it.
+            Err(Error::NoProtectedRouteNode(_)) => {
+                self.node_manager
+                    .get_request::<PeersResponse>(path, None, self.get_timeout(), false, false)
+                    .await?
+            }
+            resp => resp?.into_json::<PeersResponse>()?,
+        };
+
+        Ok(resp.0)
+    }
+
+    /// Returns a peer of the node by its peer ID. This is a protected route.
+    /// GET /api/core/v2/peers/{peerId}
+    pub async fn get_peer(&self, peer_id: &str) -> Result<PeerDto> {
+        let path = &format!("api/core/v2/peers/{peer_id}");
+
+        let resp = self
+            .node_manager
+            .protected_request(HttpMethod::Get, path, self.get_timeout(), None)
+            .await?
+            .into_json::<PeerResponse>()?;
+
+        Ok(resp.0)
+    }
+
+    /// Adds a peer to the node by its multiaddress, with an optional alias. This is a protected route.
+    /// POST /api/core/v2/peers
+    pub async fn add_peer(&self, multi_address: &str, alias: Option<&str>) -> Result<PeerDto> {
+        let path = "api/core/v2/peers";
+        let mut body = serde_json::json!({ "multiAddress": multi_address });
+        if let Some(alias) = alias {
+            body["alias"] = alias.into();
+        }
+
         let resp = self
             .node_manager
-            .get_request::<PeersResponse>(path, None, self.get_timeout(), false, false)
-            .await?;
+            .protected_request(HttpMethod::Post, path, self.get_timeout(), Some(body))
+            .await?
+            .into_json::<AddPeerResponse>()?;
 
         Ok(resp.0)
     }
 
-    // // RoutePeer is the route for getting peers by their peerID.
-    // // GET returns the peer
-    // // DELETE deletes the peer.
-    // RoutePeer = "/peers/:" + restapipkg.ParameterPeerID
+    /// Removes a peer from the node by its peer ID. This is a protected route.
+    /// DELETE /api/core/v2/peers/{peerId}
+    pub async fn remove_peer(&self, peer_id: &str) -> Result<()> {
+        let path = &format!("api/core/v2/peers/{peer_id}");
 
-    // // RoutePeers is the route for getting all peers of the node.
-    // // GET returns a list of all peers.
-    // // POST adds a new peer.
-    // RoutePeers = "/peers"
+        self.node_manager
+            .protected_request(HttpMethod::Delete, path, self.get_timeout(), None)
+            .await?;
+
+        Ok(())
+    }
+
+    // White flag routes.
+
+    /// Computes the merkle roots of the white flag traversal of a milestone with the given parents, without applying
+    /// it to the ledger. This is a protected route.
+    /// POST /api/core/v2/whiteflag
+    pub async fn post_white_flag(
+        &self,
+        index: u32,
+        timestamp: u32,
+        parents: &[BlockId],
+        previous_milestone_id: &MilestoneId,
+    ) -> Result<WhiteFlagResponse> {
+        let path = "api/core/v2/whiteflag";
+        let body = serde_json::json!({
+            "index": index,
+            "timestamp": timestamp,
+            "parents": parents.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            "previousMilestoneId": previous_milestone_id.to_string(),
+        });
+
+        self.node_manager
+            .protected_request(HttpMethod::Post, path, self.get_timeout(), Some(body))
+            .await?
+            .into_json()
+    }
 
     // Control routes.
 
-    // // RouteControlDatabasePrune is the control route to manually prune the database.
-    // // POST prunes the database.
-    // RouteControlDatabasePrune = "/control/database/prune"
+    /// Prunes the database of the node up to the given target. This is a protected route.
+    /// POST /api/core/v2/control/database/prune
+    pub async fn prune_database(&self, target: PruneDatabaseTarget) -> Result<PruneDatabaseResponse> {
+        let path = "api/core/v2/control/database/prune";
+
+        self.node_manager
+            .protected_request(
+                HttpMethod::Post,
+                path,
+                self.get_timeout(),
+                Some(serde_json::to_value(target)?),
+            )
+            .await?
+            .into_json()
+    }
+
+    /// Creates a snapshot file of the node at the given milestone index. This is a protected route.
+    /// POST /api/core/v2/control/snapshots/create
+    pub async fn create_snapshot(&self, index: u32) -> Result<CreateSnapshotsResponse> {
+        let path = "api/core/v2/control/snapshots/create";
 
-    // // RouteControlSnapshotsCreate is the control route to manually create a snapshot files.
-    // // POST creates a snapshot (full, delta or both).
-    // RouteControlSnapshotsCreate = "/control/snapshots/create"
+        self.node_manager
+            .protected_request(
+                HttpMethod::Post,
+                path,
+                self.get_timeout(),
+                Some(serde_json::json!({ "index": index })),
+            )
+            .await?
+            .into_json()
+    }
 }
@@ -3,34 +3,156 @@
 
 //! Node indexer API.
 
+pub mod query_builder;
 pub mod query_parameters;
 pub mod routes;
 
-use iota_types::api::plugins::indexer::OutputIdsResponse;
+use futures::{Stream, StreamExt, TryStreamExt};
+use iota_types::{
+    api::{core::response::OutputWithMetadataResponse, plugins::indexer::OutputIdsResponse},
+    block::output::OutputId,
+};
 
 pub(crate) use self::query_parameters::{QueryParameter, QueryParameters};
-use crate::{Client, Result};
+use crate::{Client, Error, Result};
+
+/// Defines how multi-page reads like [`Client::get_output_ids()`] handle a `ledgerIndex` that changes between
+/// requests, so results don't mix two ledger states.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SnapshotReadMode {
+    /// Pages and outputs are merged regardless of their ledger index.
+    Disabled,
+    /// Restarts the pagination from the first page, at most the given amount of times before failing.
+    Restart(usize),
+    /// Fails as soon as the ledger index changes.
+    Fail,
+}
+
+impl Default for SnapshotReadMode {
+    fn default() -> Self {
+        Self::Disabled
+    }
+}
 
 impl Client {
     /// Get all output ids for a provided URL route and query parameters.
     /// If a `QueryParameter::Cursor(_)` is provided, only a single page will be queried.
+    /// With a [`SnapshotReadMode`] other than `Disabled`, all pages have to be read at the same ledger index.
     pub async fn get_output_ids(
         &self,
         route: &str,
-        mut query_parameters: QueryParameters,
+        query_parameters: QueryParameters,
         need_quorum: bool,
         prefer_permanode: bool,
     ) -> Result<OutputIdsResponse> {
-        let mut merged_output_ids_response = OutputIdsResponse {
-            ledger_index: 0,
-            cursor: None,
-            items: Vec::new(),
-        };
-
         // Return early with only a single page if a `QueryParameter::Cursor(_)` is provided.
         let return_early = query_parameters.contains(QueryParameter::Cursor(String::new()).kind());
+        let snapshot_read = self.get_snapshot_read();
+        let mut restarts = 0;
+
+        'pagination: loop {
+            let mut query_parameters = query_parameters.clone();
+            let mut merged_output_ids_response = OutputIdsResponse {
+                ledger_index: 0,
+                cursor: None,
+                items: Vec::new(),
+            };
+            let mut first_page = true;
+
+            while let Some(cursor) = {
+                let output_ids_response = self
+                    .node_manager
+                    .get_request::<OutputIdsResponse>(
+                        route,
+                        query_parameters.to_query_string().as_deref(),
+                        self.get_timeout(),
+                        need_quorum,
+                        prefer_permanode,
+                    )
+                    .await?;
+
+                if return_early {
+                    return Ok(output_ids_response);
+                }
+
+                if !first_page
+                    && snapshot_read != SnapshotReadMode::Disabled
+                    && merged_output_ids_response.ledger_index != output_ids_response.ledger_index
+                {
+                    match snapshot_read {
+                        SnapshotReadMode::Restart(max_restarts) if restarts < max_restarts => {
+                            log::debug!(
+                                "ledger index changed from {} to {} during pagination, restarting",
+                                merged_output_ids_response.ledger_index,
+                                output_ids_response.ledger_index
+                            );
+                            restarts += 1;
+                            continue 'pagination;
+                        }
+                        _ => {
+                            return Err(Error::LedgerIndexChanged {
+                                expected: merged_output_ids_response.ledger_index,
+                                actual: output_ids_response.ledger_index,
+                            });
+                        }
+                    }
+                }
+                first_page = false;
+
+                merged_output_ids_response.ledger_index = output_ids_response.ledger_index;
+                merged_output_ids_response.cursor = output_ids_response.cursor;
+                merged_output_ids_response.items.extend(output_ids_response.items);
+
+                &merged_output_ids_response.cursor
+            } {
+                query_parameters.replace(QueryParameter::Cursor(cursor.to_string()));
+            }
+
+            return Ok(merged_output_ids_response);
+        }
+    }
+
+    /// Requests the outputs of an output ID listing in parallel. With a [`SnapshotReadMode`] other than `Disabled`,
+    /// fails if an output was read at an older ledger index than the listing.
+    pub async fn get_outputs_for_listing(
+        &self,
+        output_ids_response: OutputIdsResponse,
+    ) -> Result<Vec<OutputWithMetadataResponse>> {
+        let outputs = self.get_outputs(output_ids_response.items).await?;
+
+        if self.get_snapshot_read() != SnapshotReadMode::Disabled {
+            if let Some(output) = outputs
+                .iter()
+                .find(|output| output.metadata.ledger_index < output_ids_response.ledger_index)
+            {
+                return Err(Error::StaleOutput {
+                    output_id: output.metadata.output_id()?,
+                    ledger_index: output.metadata.ledger_index,
+                    listing_ledger_index: output_ids_response.ledger_index,
+                });
+            }
+        }
+
+        Ok(outputs)
+    }
+
+    /// Returns a stream of output ID pages for a provided URL route and query parameters. The next page is only
+    /// requested when the stream is polled, so dropping the stream stops the pagination. If a
+    /// `QueryParameter::Cursor(_)` is provided, the pagination starts at this cursor.
+    pub fn output_ids_stream<'a>(
+        &'a self,
+        route: &'a str,
+        query_parameters: QueryParameters,
+        need_quorum: bool,
+        prefer_permanode: bool,
+    ) -> impl Stream<Item = Result<OutputIdsResponse>> + 'a {
+        futures::stream::try_unfold(Some(query_parameters), move |query_parameters| async move {
+            let mut query_parameters = match query_parameters {
+                Some(query_parameters) => query_parameters,
+                None => return Ok(None),
+            };
 
-        while let Some(cursor) = {
             let output_ids_response = self
                 .node_manager
                 .get_request::<OutputIdsResponse>(
@@ -42,19 +164,26 @@ impl Client {
                 )
                 .await?;
 
-            if return_early {
-                return Ok(output_ids_response);
-            }
+            let next_query_parameters = output_ids_response.cursor.as_ref().map(|cursor| {
+                query_parameters.replace(QueryParameter::Cursor(cursor.to_string()));
+                query_parameters
+            });
 
-            merged_output_ids_response.ledger_index = output_ids_response.ledger_index;
-            merged_output_ids_response.cursor = output_ids_response.cursor;
-            merged_output_ids_response.items.extend(output_ids_response.items);
-
-            &merged_output_ids_response.cursor
-        } {
-            query_parameters.replace(QueryParameter::Cursor(cursor.to_string()));
-        }
+            Ok(Some((output_ids_response, next_query_parameters)))
+        })
+    }
 
-        Ok(merged_output_ids_response)
+    /// Resolves the output IDs of a stream of pages, like the one of [`Client::output_ids_stream()`], to their outputs.
+    /// At most `max_parallel_requests` outputs are requested at the same time, the order of the output IDs is kept.
+    pub fn outputs_stream<'a>(
+        &'a self,
+        output_ids_stream: impl Stream<Item = Result<OutputIdsResponse>> + 'a,
+        max_parallel_requests: usize,
+    ) -> impl Stream<Item = Result<OutputWithMetadataResponse>> + 'a {
+        output_ids_stream
+            .map_ok(|output_ids_response| futures::stream::iter(output_ids_response.items.into_iter().map(Ok)))
+            .try_flatten()
+            .map(move |output_id: Result<OutputId>| async move { self.get_output(&output_id?).await })
+            .buffered(max_parallel_requests.max(1))
     }
 }
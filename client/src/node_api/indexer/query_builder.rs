@@ -0,0 +1,563 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Typed queries for the indexer routes, which only allow the query parameters supported by their route and validate
+//! them before a request is sent.
+
+use iota_types::block::{
+    address::{Address, AliasAddress},
+    output::feature::TagFeature,
+};
+
+use crate::{
+    node_api::indexer::{QueryParameter, QueryParameters},
+    Error, Result,
+};
+
+/// A typed query for one of the indexer routes.
+pub trait OutputQuery: Send + Sync {
+    /// The route the query is sent to.
+    fn route(&self) -> &'static str;
+
+    /// Validates the query and converts it into query parameters, encoding addresses with the given bech32 HRP.
+    fn to_query_parameters(&self, bech32_hrp: &str) -> Result<QueryParameters>;
+}
+
+// Generates a setter for every given field of a query.
+macro_rules! query_setters {
+    ($($(#[$meta:meta])* $field:ident: $ty:ty),* $(,)?) => {
+        $(
+            $(#[$meta])*
+            pub fn $field(mut self, $field: $ty) -> Self {
+                self.$field.replace($field);
+                self
+            }
+        )*
+    };
+}
+
+// Collects query parameters and encodes addresses.
+struct QueryParametersCollector<'a> {
+    bech32_hrp: &'a str,
+    query_parameters: Vec<QueryParameter>,
+}
+
+impl<'a> QueryParametersCollector<'a> {
+    fn new(bech32_hrp: &'a str) -> Self {
+        Self {
+            bech32_hrp,
+            query_parameters: Vec::new(),
+        }
+    }
+
+    fn value<T: Clone>(mut self, value: &Option<T>, query_parameter: fn(T) -> QueryParameter) -> Self {
+        if let Some(value) = value {
+            self.query_parameters.push(query_parameter(value.clone()));
+        }
+        self
+    }
+
+    fn address(mut self, address: &Option<Address>, query_parameter: fn(String) -> QueryParameter) -> Self {
+        if let Some(address) = address {
+            self.query_parameters
+                .push(query_parameter(address.to_bech32(self.bech32_hrp)));
+        }
+        self
+    }
+
+    fn finish(self) -> QueryParameters {
+        QueryParameters::new(self.query_parameters)
+    }
+}
+
+fn invalid_query(message: impl Into<String>) -> Error {
+    Error::InvalidOutputQuery(message.into())
+}
+
+// Checks that an `after` timestamp is before a `before` timestamp.
+fn validate_time_range(name: &str, after: Option<u32>, before: Option<u32>) -> Result<()> {
+    match (after, before) {
+        (Some(after), Some(before)) if after >= before => Err(invalid_query(format!(
+            "{name}After {after} must be smaller than {name}Before {before}"
+        ))),
+        _ => Ok(()),
+    }
+}
+
+// Checks that no filter is set for a feature that is excluded with `has_*(false)`.
+fn validate_excluded(name: &str, has: Option<bool>, filters: &[bool]) -> Result<()> {
+    if has == Some(false) && filters.iter().any(|set| *set) {
+        Err(invalid_query(format!(
+            "{name} filters can't be combined with has{name}(false)"
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+fn validate_native_tokens(has_native_tokens: Option<bool>, min: Option<u32>, max: Option<u32>) -> Result<()> {
+    if let (Some(min), Some(max)) = (min, max) {
+        if min > max {
+            return Err(invalid_query(format!(
+                "minNativeTokenCount {min} must not be larger than maxNativeTokenCount {max}"
+            )));
+        }
+    }
+    validate_excluded("NativeTokens", has_native_tokens, &[min.unwrap_or(0) > 0])
+}
+
+fn validate_page_size(page_size: Option<usize>) -> Result<()> {
+    if page_size == Some(0) {
+        Err(invalid_query("pageSize must not be 0"))
+    } else {
+        Ok(())
+    }
+}
+
+fn validate_tag(tag: &Option<Vec<u8>>) -> Result<()> {
+    match tag {
+        Some(tag) if tag.is_empty() || tag.len() > usize::from(*TagFeature::LENGTH_RANGE.end()) => Err(invalid_query(
+            format!("tag length {} is not in {:?}", tag.len(), TagFeature::LENGTH_RANGE),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Query for basic outputs, e.g. `BasicOutputQuery::new().address(address).has_expiration(false)`.
+/// GET api/indexer/v1/outputs/basic
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[must_use]
+pub struct BasicOutputQuery {
+    address: Option<Address>,
+    has_native_tokens: Option<bool>,
+    min_native_token_count: Option<u32>,
+    max_native_token_count: Option<u32>,
+    has_storage_deposit_return: Option<bool>,
+    storage_deposit_return_address: Option<Address>,
+    has_timelock: Option<bool>,
+    timelocked_before: Option<u32>,
+    timelocked_after: Option<u32>,
+    has_expiration: Option<bool>,
+    expires_before: Option<u32>,
+    expires_after: Option<u32>,
+    expiration_return_address: Option<Address>,
+    sender: Option<Address>,
+    tag: Option<Vec<u8>>,
+    created_before: Option<u32>,
+    created_after: Option<u32>,
+    page_size: Option<usize>,
+    cursor: Option<String>,
+}
+
+impl BasicOutputQuery {
+    /// Creates a new query without filters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    query_setters! {
+        /// Filters outputs that can be unlocked by the address.
+        address: Address,
+        /// Filters outputs based on the presence of native tokens.
+        has_native_tokens: bool,
+        /// Filters outputs that have at least a certain number of distinct native tokens.
+        min_native_token_count: u32,
+        /// Filters outputs that have at most a certain number of distinct native tokens.
+        max_native_token_count: u32,
+        /// Filters outputs based on the presence of a storage deposit return unlock condition.
+        has_storage_deposit_return: bool,
+        /// Filters outputs based on the return address of the storage deposit return unlock condition.
+        storage_deposit_return_address: Address,
+        /// Filters outputs based on the presence of a timelock unlock condition.
+        has_timelock: bool,
+        /// Returns outputs that are timelocked before a certain Unix timestamp.
+        timelocked_before: u32,
+        /// Returns outputs that are timelocked after a certain Unix timestamp.
+        timelocked_after: u32,
+        /// Filters outputs based on the presence of an expiration unlock condition.
+        has_expiration: bool,
+        /// Returns outputs that expire before a certain Unix timestamp.
+        expires_before: u32,
+        /// Returns outputs that expire after a certain Unix timestamp.
+        expires_after: u32,
+        /// Filters outputs based on the return address of the expiration unlock condition.
+        expiration_return_address: Address,
+        /// Filters outputs based on the sender feature.
+        sender: Address,
+        /// Filters outputs based on the tag feature.
+        tag: Vec<u8>,
+        /// Returns outputs that were created before a certain Unix timestamp.
+        created_before: u32,
+        /// Returns outputs that were created after a certain Unix timestamp.
+        created_after: u32,
+        /// The maximum amount of items returned in one call.
+        page_size: usize,
+        /// Starts the search from the cursor (confirmationMS+outputId.pageSize).
+        cursor: String,
+    }
+}
+
+impl OutputQuery for BasicOutputQuery {
+    fn route(&self) -> &'static str {
+        "api/indexer/v1/outputs/basic"
+    }
+
+    fn to_query_parameters(&self, bech32_hrp: &str) -> Result<QueryParameters> {
+        validate_native_tokens(
+            self.has_native_tokens,
+            self.min_native_token_count,
+            self.max_native_token_count,
+        )?;
+        validate_excluded(
+            "StorageDepositReturn",
+            self.has_storage_deposit_return,
+            &[self.storage_deposit_return_address.is_some()],
+        )?;
+        validate_excluded(
+            "Timelock",
+            self.has_timelock,
+            &[self.timelocked_before.is_some(), self.timelocked_after.is_some()],
+        )?;
+        validate_excluded(
+            "Expiration",
+            self.has_expiration,
+            &[
+                self.expires_before.is_some(),
+                self.expires_after.is_some(),
+                self.expiration_return_address.is_some(),
+            ],
+        )?;
+        validate_time_range("timelocked", self.timelocked_after, self.timelocked_before)?;
+        validate_time_range("expires", self.expires_after, self.expires_before)?;
+        validate_time_range("created", self.created_after, self.created_before)?;
+        validate_tag(&self.tag)?;
+        validate_page_size(self.page_size)?;
+
+        Ok(QueryParametersCollector::new(bech32_hrp)
+            .address(&self.address, QueryParameter::Address)
+            .value(&self.has_native_tokens, QueryParameter::HasNativeTokens)
+            .value(&self.min_native_token_count, QueryParameter::MinNativeTokenCount)
+            .value(&self.max_native_token_count, QueryParameter::MaxNativeTokenCount)
+            .value(
+                &self.has_storage_deposit_return,
+                QueryParameter::HasStorageDepositReturn,
+            )
+            .address(
+                &self.storage_deposit_return_address,
+                QueryParameter::StorageDepositReturnAddress,
+            )
+            .value(&self.has_timelock, QueryParameter::HasTimelock)
+            .value(&self.timelocked_before, QueryParameter::TimelockedBefore)
+            .value(&self.timelocked_after, QueryParameter::TimelockedAfter)
+            .value(&self.has_expiration, QueryParameter::HasExpiration)
+            .value(&self.expires_before, QueryParameter::ExpiresBefore)
+            .value(&self.expires_after, QueryParameter::ExpiresAfter)
+            .address(&self.expiration_return_address, QueryParameter::ExpirationReturnAddress)
+            .address(&self.sender, QueryParameter::Sender)
+            .value(&self.tag.clone().map(prefix_hex::encode), QueryParameter::Tag)
+            .value(&self.created_before, QueryParameter::CreatedBefore)
+            .value(&self.created_after, QueryParameter::CreatedAfter)
+            .value(&self.page_size, QueryParameter::PageSize)
+            .value(&self.cursor, QueryParameter::Cursor)
+            .finish())
+    }
+}
+
+/// Query for alias outputs, e.g. `AliasOutputQuery::new().governor(address)`.
+/// GET api/indexer/v1/outputs/alias
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[must_use]
+pub struct AliasOutputQuery {
+    state_controller: Option<Address>,
+    governor: Option<Address>,
+    issuer: Option<Address>,
+    sender: Option<Address>,
+    has_native_tokens: Option<bool>,
+    min_native_token_count: Option<u32>,
+    max_native_token_count: Option<u32>,
+    created_before: Option<u32>,
+    created_after: Option<u32>,
+    page_size: Option<usize>,
+    cursor: Option<String>,
+}
+
+impl AliasOutputQuery {
+    /// Creates a new query without filters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    query_setters! {
+        /// Filters outputs based on the state controller address.
+        state_controller: Address,
+        /// Filters outputs based on the governor address.
+        governor: Address,
+        /// Filters outputs based on the issuer feature.
+        issuer: Address,
+        /// Filters outputs based on the sender feature.
+        sender: Address,
+        /// Filters outputs based on the presence of native tokens.
+        has_native_tokens: bool,
+        /// Filters outputs that have at least a certain number of distinct native tokens.
+        min_native_token_count: u32,
+        /// Filters outputs that have at most a certain number of distinct native tokens.
+        max_native_token_count: u32,
+        /// Returns outputs that were created before a certain Unix timestamp.
+        created_before: u32,
+        /// Returns outputs that were created after a certain Unix timestamp.
+        created_after: u32,
+        /// The maximum amount of items returned in one call.
+        page_size: usize,
+        /// Starts the search from the cursor (confirmationMS+outputId.pageSize).
+        cursor: String,
+    }
+}
+
+impl OutputQuery for AliasOutputQuery {
+    fn route(&self) -> &'static str {
+        "api/indexer/v1/outputs/alias"
+    }
+
+    fn to_query_parameters(&self, bech32_hrp: &str) -> Result<QueryParameters> {
+        validate_native_tokens(
+            self.has_native_tokens,
+            self.min_native_token_count,
+            self.max_native_token_count,
+        )?;
+        validate_time_range("created", self.created_after, self.created_before)?;
+        validate_page_size(self.page_size)?;
+
+        Ok(QueryParametersCollector::new(bech32_hrp)
+            .address(&self.state_controller, QueryParameter::StateController)
+            .address(&self.governor, QueryParameter::Governor)
+            .address(&self.issuer, QueryParameter::Issuer)
+            .address(&self.sender, QueryParameter::Sender)
+            .value(&self.has_native_tokens, QueryParameter::HasNativeTokens)
+            .value(&self.min_native_token_count, QueryParameter::MinNativeTokenCount)
+            .value(&self.max_native_token_count, QueryParameter::MaxNativeTokenCount)
+            .value(&self.created_before, QueryParameter::CreatedBefore)
+            .value(&self.created_after, QueryParameter::CreatedAfter)
+            .value(&self.page_size, QueryParameter::PageSize)
+            .value(&self.cursor, QueryParameter::Cursor)
+            .finish())
+    }
+}
+
+/// Query for foundry outputs, e.g. `FoundryOutputQuery::new().alias_address(alias_address)`.
+/// GET api/indexer/v1/outputs/foundry
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[must_use]
+pub struct FoundryOutputQuery {
+    alias_address: Option<AliasAddress>,
+    has_native_tokens: Option<bool>,
+    min_native_token_count: Option<u32>,
+    max_native_token_count: Option<u32>,
+    created_before: Option<u32>,
+    created_after: Option<u32>,
+    page_size: Option<usize>,
+    cursor: Option<String>,
+}
+
+impl FoundryOutputQuery {
+    /// Creates a new query without filters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    query_setters! {
+        /// Filters outputs based on the address of the controlling alias.
+        alias_address: AliasAddress,
+        /// Filters outputs based on the presence of native tokens.
+        has_native_tokens: bool,
+        /// Filters outputs that have at least a certain number of distinct native tokens.
+        min_native_token_count: u32,
+        /// Filters outputs that have at most a certain number of distinct native tokens.
+        max_native_token_count: u32,
+        /// Returns outputs that were created before a certain Unix timestamp.
+        created_before: u32,
+        /// Returns outputs that were created after a certain Unix timestamp.
+        created_after: u32,
+        /// The maximum amount of items returned in one call.
+        page_size: usize,
+        /// Starts the search from the cursor (confirmationMS+outputId.pageSize).
+        cursor: String,
+    }
+}
+
+impl OutputQuery for FoundryOutputQuery {
+    fn route(&self) -> &'static str {
+        "api/indexer/v1/outputs/foundry"
+    }
+
+    fn to_query_parameters(&self, bech32_hrp: &str) -> Result<QueryParameters> {
+        validate_native_tokens(
+            self.has_native_tokens,
+            self.min_native_token_count,
+            self.max_native_token_count,
+        )?;
+        validate_time_range("created", self.created_after, self.created_before)?;
+        validate_page_size(self.page_size)?;
+
+        Ok(QueryParametersCollector::new(bech32_hrp)
+            .address(&self.alias_address.map(Address::Alias), QueryParameter::AliasAddress)
+            .value(&self.has_native_tokens, QueryParameter::HasNativeTokens)
+            .value(&self.min_native_token_count, QueryParameter::MinNativeTokenCount)
+            .value(&self.max_native_token_count, QueryParameter::MaxNativeTokenCount)
+            .value(&self.created_before, QueryParameter::CreatedBefore)
+            .value(&self.created_after, QueryParameter::CreatedAfter)
+            .value(&self.page_size, QueryParameter::PageSize)
+            .value(&self.cursor, QueryParameter::Cursor)
+            .finish())
+    }
+}
+
+/// Query for NFT outputs, e.g. `NftOutputQuery::new().address(address).has_timelock(false)`.
+/// GET api/indexer/v1/outputs/nft
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[must_use]
+pub struct NftOutputQuery(BasicOutputQuery);
+
+impl NftOutputQuery {
+    /// Creates a new query without filters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+// NFT outputs support the same query parameters as basic outputs.
+macro_rules! forward_setters {
+    ($($(#[$meta:meta])* $field:ident: $ty:ty),* $(,)?) => {
+        $(
+            $(#[$meta])*
+            pub fn $field(self, $field: $ty) -> Self {
+                Self(self.0.$field($field))
+            }
+        )*
+    };
+}
+
+impl NftOutputQuery {
+    forward_setters! {
+        /// Filters outputs that can be unlocked by the address.
+        address: Address,
+        /// Filters outputs based on the presence of native tokens.
+        has_native_tokens: bool,
+        /// Filters outputs that have at least a certain number of distinct native tokens.
+        min_native_token_count: u32,
+        /// Filters outputs that have at most a certain number of distinct native tokens.
+        max_native_token_count: u32,
+        /// Filters outputs based on the presence of a storage deposit return unlock condition.
+        has_storage_deposit_return: bool,
+        /// Filters outputs based on the return address of the storage deposit return unlock condition.
+        storage_deposit_return_address: Address,
+        /// Filters outputs based on the presence of a timelock unlock condition.
+        has_timelock: bool,
+        /// Returns outputs that are timelocked before a certain Unix timestamp.
+        timelocked_before: u32,
+        /// Returns outputs that are timelocked after a certain Unix timestamp.
+        timelocked_after: u32,
+        /// Filters outputs based on the presence of an expiration unlock condition.
+        has_expiration: bool,
+        /// Returns outputs that expire before a certain Unix timestamp.
+        expires_before: u32,
+        /// Returns outputs that expire after a certain Unix timestamp.
+        expires_after: u32,
+        /// Filters outputs based on the return address of the expiration unlock condition.
+        expiration_return_address: Address,
+        /// Filters outputs based on the sender feature.
+        sender: Address,
+        /// Filters outputs based on the tag feature.
+        tag: Vec<u8>,
+        /// Returns outputs that were created before a certain Unix timestamp.
+        created_before: u32,
+        /// Returns outputs that were created after a certain Unix timestamp.
+        created_after: u32,
+        /// The maximum amount of items returned in one call.
+        page_size: usize,
+        /// Starts the search from the cursor (confirmationMS+outputId.pageSize).
+        cursor: String,
+    }
+}
+
+impl OutputQuery for NftOutputQuery {
+    fn route(&self) -> &'static str {
+        "api/indexer/v1/outputs/nft"
+    }
+
+    fn to_query_parameters(&self, bech32_hrp: &str) -> Result<QueryParameters> {
+        self.0.to_query_parameters(bech32_hrp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use iota_types::block::{address::Ed25519Address, output::AliasId};
+
+    use super::*;
+
+    #[test]
+    fn typed_query_parameters() {
+        let address = Address::Ed25519(Ed25519Address::new([1; 32]));
+
+        let query_parameters = BasicOutputQuery::new()
+            .address(address)
+            .has_expiration(false)
+            .tag(b"tag".to_vec())
+            .to_query_parameters("rms")
+            .unwrap();
+        assert_eq!(
+            query_parameters.to_query_string().unwrap(),
+            format!("address={}&hasExpiration=false&tag=0x746167", address.to_bech32("rms"))
+        );
+
+        let query_parameters = FoundryOutputQuery::new()
+            .alias_address(AliasAddress::new(AliasId::new([2; 32])))
+            .to_query_parameters("rms")
+            .unwrap();
+        assert!(query_parameters
+            .to_query_string()
+            .unwrap()
+            .starts_with("aliasAddress=rms1p"));
+
+        assert!(NftOutputQuery::new()
+            .to_query_parameters("rms")
+            .unwrap()
+            .to_query_string()
+            .is_none());
+    }
+
+    #[test]
+    fn invalid_typed_query() {
+        let address = Address::Ed25519(Ed25519Address::new([1; 32]));
+
+        assert!(matches!(
+            BasicOutputQuery::new()
+                .has_expiration(false)
+                .expiration_return_address(address)
+                .to_query_parameters("rms"),
+            Err(Error::InvalidOutputQuery(_))
+        ));
+        assert!(matches!(
+            NftOutputQuery::new()
+                .created_after(10)
+                .created_before(5)
+                .to_query_parameters("rms"),
+            Err(Error::InvalidOutputQuery(_))
+        ));
+        assert!(matches!(
+            AliasOutputQuery::new()
+                .min_native_token_count(2)
+                .max_native_token_count(1)
+                .to_query_parameters("rms"),
+            Err(Error::InvalidOutputQuery(_))
+        ));
+        assert!(matches!(
+            FoundryOutputQuery::new().page_size(0).to_query_parameters("rms"),
+            Err(Error::InvalidOutputQuery(_))
+        ));
+        assert!(matches!(
+            BasicOutputQuery::new().tag(Vec::new()).to_query_parameters("rms"),
+            Err(Error::InvalidOutputQuery(_))
+        ));
+    }
+}
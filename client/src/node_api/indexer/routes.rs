@@ -3,6 +3,7 @@
 
 //! IOTA node indexer routes
 
+use futures::Stream;
 use iota_types::{
     api::plugins::indexer::OutputIdsResponse,
     block::output::{AliasId, FoundryId, NftId, OutputId},
@@ -10,6 +11,7 @@ use iota_types::{
 
 use crate::{
     node_api::indexer::{
+        query_builder::OutputQuery,
         query_parameters::{
             verify_query_parameters_alias_outputs, verify_query_parameters_basic_outputs,
             verify_query_parameters_foundry_outputs, verify_query_parameters_nft_outputs, QueryParameter,
@@ -36,6 +38,20 @@ impl Client {
         self.get_output_ids(route, query_parameters, true, false).await
     }
 
+    /// Returns a stream of pages of basic output IDs filtered by the given parameters, see
+    /// [`Client::basic_output_ids()`] for the query parameters. Pages are requested lazily while the stream is polled.
+    /// api/indexer/v1/outputs/basic
+    pub fn basic_output_ids_stream(
+        &self,
+        query_parameters: Vec<QueryParameter>,
+    ) -> Result<impl Stream<Item = Result<OutputIdsResponse>> + '_> {
+        let route = "api/indexer/v1/outputs/basic";
+
+        let query_parameters = verify_query_parameters_basic_outputs(query_parameters)?;
+
+        Ok(self.output_ids_stream(route, query_parameters, true, false))
+    }
+
     /// Get alias outputs filtered by the given parameters.
     /// GET with query parameter returns all outputIDs that fit these filter criteria.
     /// Query parameters: "stateController", "governor", "issuer", "sender", "createdBefore", "createdAfter"
@@ -49,6 +65,20 @@ impl Client {
         self.get_output_ids(route, query_parameters, true, false).await
     }
 
+    /// Returns a stream of pages of alias output IDs filtered by the given parameters, see
+    /// [`Client::alias_output_ids()`] for the query parameters. Pages are requested lazily while the stream is polled.
+    /// api/indexer/v1/outputs/alias
+    pub fn alias_output_ids_stream(
+        &self,
+        query_parameters: Vec<QueryParameter>,
+    ) -> Result<impl Stream<Item = Result<OutputIdsResponse>> + '_> {
+        let route = "api/indexer/v1/outputs/alias";
+
+        let query_parameters = verify_query_parameters_alias_outputs(query_parameters)?;
+
+        Ok(self.output_ids_stream(route, query_parameters, true, false))
+    }
+
     /// Get alias output by its aliasID.
     /// api/indexer/v1/outputs/alias/:{AliasId}
     pub async fn alias_output_id(&self, alias_id: AliasId) -> Result<OutputId> {
@@ -74,6 +104,20 @@ impl Client {
         self.get_output_ids(route, query_parameters, true, false).await
     }
 
+    /// Returns a stream of pages of foundry output IDs filtered by the given parameters, see
+    /// [`Client::foundry_output_ids()`] for the query parameters. Pages are requested lazily while the stream is
+    /// polled. api/indexer/v1/outputs/foundry
+    pub fn foundry_output_ids_stream(
+        &self,
+        query_parameters: Vec<QueryParameter>,
+    ) -> Result<impl Stream<Item = Result<OutputIdsResponse>> + '_> {
+        let route = "api/indexer/v1/outputs/foundry";
+
+        let query_parameters = verify_query_parameters_foundry_outputs(query_parameters)?;
+
+        Ok(self.output_ids_stream(route, query_parameters, true, false))
+    }
+
     /// Get foundry output by its foundryID.
     /// api/indexer/v1/outputs/foundry/:{FoundryID}
     pub async fn foundry_output_id(&self, foundry_id: FoundryId) -> Result<OutputId> {
@@ -100,6 +144,20 @@ impl Client {
         self.get_output_ids(route, query_parameters, true, false).await
     }
 
+    /// Returns a stream of pages of NFT output IDs filtered by the given parameters, see
+    /// [`Client::nft_output_ids()`] for the query parameters. Pages are requested lazily while the stream is polled.
+    /// api/indexer/v1/outputs/nft
+    pub fn nft_output_ids_stream(
+        &self,
+        query_parameters: Vec<QueryParameter>,
+    ) -> Result<impl Stream<Item = Result<OutputIdsResponse>> + '_> {
+        let route = "api/indexer/v1/outputs/nft";
+
+        let query_parameters = verify_query_parameters_nft_outputs(query_parameters)?;
+
+        Ok(self.output_ids_stream(route, query_parameters, true, false))
+    }
+
     /// Get NFT output by its nftID.
     /// api/indexer/v1/outputs/nft/:{NftId}
     pub async fn nft_output_id(&self, nft_id: NftId) -> Result<OutputId> {
@@ -111,4 +169,23 @@ impl Client {
             .first()
             .ok_or_else(|| crate::Error::Node("no output id for nft".to_string()))?))
     }
+
+    /// Get all output IDs that match a typed query, e.g. a
+    /// [`BasicOutputQuery`](crate::node_api::indexer::query_builder::BasicOutputQuery).
+    pub async fn query_output_ids(&self, query: impl OutputQuery) -> Result<OutputIdsResponse> {
+        let query_parameters = query.to_query_parameters(&self.get_bech32_hrp().await?)?;
+
+        self.get_output_ids(query.route(), query_parameters, true, false).await
+    }
+
+    /// Returns a lazily paginated stream of output ID pages that match a typed query, see
+    /// [`Client::output_ids_stream()`].
+    pub async fn query_output_ids_stream(
+        &self,
+        query: impl OutputQuery + 'static,
+    ) -> Result<impl Stream<Item = Result<OutputIdsResponse>> + '_> {
+        let query_parameters = query.to_query_parameters(&self.get_bech32_hrp().await?)?;
+
+        Ok(self.output_ids_stream(query.route(), query_parameters, true, false))
+    }
 }
@@ -17,4 +17,7 @@ pub enum Error {
     /// Invalid topic.
     #[error("invalid topic {0}")]
     InvalidTopic(String),
+    /// The event buffer of a stream subscription is full.
+    #[error("stream buffer overflow, more than {0} events weren't polled")]
+    StreamOverflow(usize),
 }
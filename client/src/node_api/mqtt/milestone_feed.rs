@@ -0,0 +1,166 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Gap-free milestone feed
+
+use std::{collections::VecDeque, ops::Range};
+
+use futures::{Stream, StreamExt};
+use iota_types::{api::core::response::UtxoChangesResponse, block::payload::MilestonePayload};
+
+use super::{Error as MqttError, MqttPayload, Topic, TopicEvent};
+use crate::{Client, Result};
+
+/// A milestone of the [`Client::milestone_feed()`], with the ledger changes it applied.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MilestoneFeedEvent {
+    /// The index of the milestone.
+    pub index: u32,
+    /// The milestone payload.
+    pub milestone: MilestonePayload,
+    /// The outputs created and consumed by the milestone.
+    pub utxo_changes: UtxoChangesResponse,
+    /// Whether the milestone was missed on the MQTT feed, e.g. during a reconnection, and requested from a node.
+    pub backfilled: bool,
+}
+
+// Tracks the last seen milestone index to detect duplicates and gaps.
+#[derive(Debug, Default)]
+struct MilestoneGapTracker {
+    next_index: Option<u32>,
+}
+
+impl MilestoneGapTracker {
+    // Returns the missed indexes before `index`, or `None` if `index` was already seen.
+    fn track(&mut self, index: u32) -> Option<Range<u32>> {
+        let missing = match self.next_index {
+            Some(next_index) if index < next_index => return None,
+            Some(next_index) => next_index..index,
+            None => index..index,
+        };
+        self.next_index = Some(index + 1);
+
+        Some(missing)
+    }
+}
+
+struct MilestoneFeedState<'a, S> {
+    client: &'a Client,
+    events: S,
+    gaps: MilestoneGapTracker,
+    // Milestones that are returned next, in order, with their payload if it was received from the MQTT feed.
+    queue: VecDeque<(u32, Option<MilestonePayload>)>,
+}
+
+impl<S: Stream<Item = std::result::Result<TopicEvent, MqttError>> + Unpin> MilestoneFeedState<'_, S> {
+    async fn next(&mut self) -> Option<Result<MilestoneFeedEvent>> {
+        loop {
+            if let Some((index, milestone)) = self.queue.pop_front() {
+                return match self.resolve(index, milestone.clone()).await {
+                    Ok(event) => Some(Ok(event)),
+                    Err(e) => {
+                        // The milestone is requested again on the next poll, so the feed stays gap-free.
+                        self.queue.push_front((index, milestone));
+                        Some(Err(e))
+                    }
+                };
+            }
+
+            let event = match self.events.next().await? {
+                Ok(event) => event,
+                Err(e) => return Some(Err(e.into())),
+            };
+
+            if let MqttPayload::MilestonePayload(milestone) = event.payload {
+                let index = *milestone.essence().index();
+                if let Some(missing) = self.gaps.track(index) {
+                    self.queue.extend(missing.map(|index| (index, None)));
+                    self.queue.push_back((index, Some(milestone)));
+                }
+            }
+        }
+    }
+
+    async fn resolve(&self, index: u32, milestone: Option<MilestonePayload>) -> Result<MilestoneFeedEvent> {
+        let backfilled = milestone.is_none();
+        let milestone = match milestone {
+            Some(milestone) => milestone,
+            None => self.client.get_milestone_by_index(index).await?,
+        };
+        let utxo_changes = self.client.get_utxo_changes_by_index(index).await?;
+
+        Ok(MilestoneFeedEvent {
+            index,
+            milestone,
+            utxo_changes,
+            backfilled,
+        })
+    }
+}
+
+impl Client {
+    /// Returns a gap-free, ordered stream of milestones and their ledger changes. Milestones are received from the
+    /// `milestones` MQTT topic and the ones that were missed, e.g. while the MQTT connection was down or because the
+    /// stream wasn't polled fast enough, are requested with [`Client::get_milestone_by_index()`]. If a `start_index` is
+    /// provided, the milestones from that index on are requested before the first received one.
+    /// Failed requests are returned as errors and retried on the next poll.
+    pub async fn milestone_feed(
+        &self,
+        start_index: Option<u32>,
+    ) -> Result<impl Stream<Item = Result<MilestoneFeedEvent>> + '_> {
+        let events = self.subscribe_stream(vec![Topic::Milestones]).await?;
+
+        Ok(self.milestone_feed_from_events(events, start_index))
+    }
+
+    /// Returns the gap-free, ordered stream of [`Client::milestone_feed()`] for the milestones of `events`, e.g. of a
+    /// [`TopicEventStream`](super::TopicEventStream) that's also subscribed to other topics, whose events are skipped.
+    pub fn milestone_feed_from_events<'a, S>(
+        &'a self,
+        events: S,
+        start_index: Option<u32>,
+    ) -> impl Stream<Item = Result<MilestoneFeedEvent>> + 'a
+    where
+        S: Stream<Item = std::result::Result<TopicEvent, MqttError>> + Unpin + 'a,
+    {
+        let state = MilestoneFeedState {
+            client: self,
+            events,
+            gaps: MilestoneGapTracker {
+                next_index: start_index,
+            },
+            queue: VecDeque::new(),
+        };
+
+        futures::stream::unfold(state, |mut state| async move {
+            state.next().await.map(|event| (event, state))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn milestone_gaps() {
+        let mut gaps = MilestoneGapTracker::default();
+
+        assert_eq!(gaps.track(10), Some(10..10));
+        assert_eq!(gaps.track(11), Some(11..11));
+        // Duplicates and older milestones are skipped.
+        assert_eq!(gaps.track(11), None);
+        assert_eq!(gaps.track(5), None);
+        // Missed milestones are returned.
+        assert_eq!(gaps.track(15), Some(12..15));
+        assert_eq!(gaps.track(16), Some(16..16));
+    }
+
+    #[test]
+    fn milestone_gaps_from_start_index() {
+        let mut gaps = MilestoneGapTracker { next_index: Some(3) };
+
+        assert_eq!(gaps.track(6), Some(3..6));
+        assert_eq!(gaps.track(7), Some(7..7));
+    }
+}
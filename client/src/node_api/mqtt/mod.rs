@@ -4,28 +4,35 @@
 //! IOTA node MQTT API
 
 mod error;
+mod milestone_feed;
+mod stream;
 pub mod types;
 
-use std::{
-    sync::{Arc, RwLock as StdRwLock},
-    time::Instant,
-};
+use std::{sync::Arc, time::Instant};
 
 use crypto::utils;
 use iota_types::block::{
     payload::{milestone::ReceiptMilestoneOption, MilestonePayload},
+    protocol::ProtocolParameters,
     Block,
 };
 use log::warn;
 use packable::PackableExt;
 use rumqttc::{AsyncClient, Event, EventLoop, Incoming, MqttOptions, NetworkOptions, QoS, SubscribeFilter, Transport};
-use tokio::sync::{
-    watch::{Receiver as WatchReceiver, Sender},
-    RwLock,
+use serde::de::DeserializeOwned;
+use tokio::sync::{watch::Receiver as WatchReceiver, RwLock};
+
+pub use self::{
+    error::Error,
+    milestone_feed::MilestoneFeedEvent,
+    stream::{OverflowPolicy, TopicEventStream},
+    types::*,
+};
+use crate::{
+    constants::DEFAULT_MQTT_STREAM_CAPACITY,
+    node_manager::{node::Node, NodeManager},
+    Client,
 };
-
-pub use self::{error::Error, types::*};
-use crate::{Client, NetworkInfo};
 
 impl Client {
     /// Returns a handle to the MQTT topics manager.
@@ -42,6 +49,15 @@ impl Client {
         MqttManager::new(self).with_topics(topics).subscribe(callback).await
     }
 
+    /// Subscribe to MQTT events with a stream, which buffers up to 1000 events and drops the oldest ones if it's not
+    /// polled fast enough. Dropping the stream unsubscribes the topics.
+    pub async fn subscribe_stream(&self, topics: Vec<Topic>) -> Result<TopicEventStream, Error> {
+        MqttManager::new(self)
+            .with_topics(topics)
+            .subscribe_stream(DEFAULT_MQTT_STREAM_CAPACITY, OverflowPolicy::DropOldest)
+            .await
+    }
+
     /// Unsubscribe from MQTT events.
     pub async fn unsubscribe(&self, topics: Vec<Topic>) -> Result<(), Error> {
         MqttManager::new(self).with_topics(topics).unsubscribe().await
@@ -61,85 +77,120 @@ async fn set_mqtt_client(client: &Client) -> Result<(), Error> {
     let exists = client.mqtt_client.read().await.is_some();
 
     if !exists {
-        let nodes = if !client.node_manager.ignore_node_health {
-            #[cfg(not(target_family = "wasm"))]
-            {
-                client
-                    .node_manager
-                    .healthy_nodes
-                    .read()
-                    .map_or(client.node_manager.nodes.clone(), |healthy_nodes| {
-                        healthy_nodes.iter().map(|(node, _)| node.clone()).collect()
-                    })
-            }
-            #[cfg(target_family = "wasm")]
-            {
-                client.node_manager.nodes.clone()
-            }
-        } else {
-            client.node_manager.nodes.clone()
-        };
-        for node in &nodes {
-            let host = node.url.host_str().expect("can't get host from URL");
-            let mut entropy = [0u8; 8];
-            utils::rand::fill(&mut entropy)?;
-            let id = format!("iotars{}", prefix_hex::encode(entropy));
-            let port = client.broker_options.port;
-            let mut uri = format!(
-                "{}://{}:{}/api/mqtt/v1",
-                if node.url.scheme() == "https" { "wss" } else { "ws" },
-                host,
-                node.url.port_or_known_default().unwrap_or(port)
-            );
-
-            if !client.broker_options.use_ws {
-                uri = host.to_string();
-            };
-            let mut mqtt_options = MqttOptions::new(id, uri, port);
-            if client.broker_options.use_ws {
-                mqtt_options.set_transport(Transport::ws());
-            }
-            let (_, mut connection) = AsyncClient::new(mqtt_options.clone(), 10);
-            connection.set_network_options(
-                *NetworkOptions::new().set_connection_timeout(client.broker_options.timeout.as_secs()),
-            );
-            // poll the event loop until we find a ConnAck event,
-            // which means that the mqtt client is ready to be used on this host
-            // if the event loop returns an error, we check the next node
-            let mut got_ack = false;
-            while let Ok(event) = connection.poll().await {
-                if let Event::Incoming(Incoming::ConnAck(_)) = event {
-                    got_ack = true;
-                    break;
-                }
-            }
+        let nodes = broker_nodes(&client.node_manager, None);
 
-            // if we found a valid mqtt connection, loop it on a separate thread
-            if got_ack {
-                let (mqtt_client, connection) = AsyncClient::new(mqtt_options, 10);
-                client.mqtt_client.write().await.replace(mqtt_client.clone());
-                poll_mqtt(
-                    mqtt_client,
-                    client.mqtt_topic_handlers.clone(),
-                    client.broker_options.clone(),
-                    client.mqtt_event_channel.0.clone(),
-                    connection,
-                    client.network_info.clone(),
-                );
-            }
+        // if we found a valid mqtt connection, loop it on a separate thread
+        if let Some((node, mqtt_options)) = connect(&nodes, &client.broker_options).await? {
+            let (mqtt_client, connection) = new_mqtt_client(mqtt_options, &client.broker_options);
+            client.mqtt_client.write().await.replace(mqtt_client.clone());
+            poll_mqtt(client, mqtt_client, connection, node);
         }
     }
     Ok(())
 }
 
-fn poll_mqtt(
-    mqtt_client: AsyncClient,
-    mqtt_topic_handlers_guard: Arc<RwLock<TopicHandlerMap>>,
-    options: BrokerOptions,
-    event_sender: Arc<Sender<MqttEvent>>,
-    mut event_loop: EventLoop,
-    network_info: Arc<StdRwLock<NetworkInfo>>,
-) {
+// Returns the nodes that can be used as MQTT brokers, the healthy ones if the node health isn't ignored. The `failed`
+// node is moved to the end, so other nodes are tried first.
+fn broker_nodes(node_manager: &NodeManager, failed: Option<&Node>) -> Vec<Node> {
+    let nodes = if !node_manager.ignore_node_health {
+        #[cfg(not(target_family = "wasm"))]
+        {
+            node_manager
+                .healthy_nodes
+                .read()
+                .map_or(node_manager.nodes.clone(), |healthy_nodes| {
+                    healthy_nodes.iter().map(|(node, _)| node.clone()).collect()
+                })
+        }
+        #[cfg(target_family = "wasm")]
+        {
+            node_manager.nodes.clone()
+        }
+    } else {
+        node_manager.nodes.clone()
+    };
+
+    let (mut nodes, failed_nodes): (Vec<_>, Vec<_>) = nodes
+        .into_iter()
+        .partition(|node| failed.map_or(true, |failed| failed.url != node.url));
+    nodes.extend(failed_nodes);
+    nodes
+}
+
+// Builds the MQTT options to connect to the broker of a node, with the credentials of the broker options or the node
+// and the TLS configuration of the broker options.
+fn mqtt_options(node: &Node, options: &BrokerOptions) -> Result<MqttOptions, Error> {
+    let host = node.url.host_str().expect("can't get host from URL");
+    let mut entropy = [0u8; 8];
+    utils::rand::fill(&mut entropy)?;
+    let id = format!("iotars{}", prefix_hex::encode(entropy));
+    let port = options.port;
+    let secure = options.tls.is_some() || node.url.scheme() == "https";
+    let uri = if options.use_ws {
+        format!(
+            "{}://{}:{}/api/mqtt/v1",
+            if secure { "wss" } else { "ws" },
+            host,
+            node.url.port_or_known_default().unwrap_or(port)
+        )
+    } else {
+        host.to_string()
+    };
+
+    let mut mqtt_options = MqttOptions::new(id, uri, port);
+    mqtt_options.set_transport(match (&options.tls, options.use_ws) {
+        (Some(tls), true) => Transport::wss_with_config(tls.to_tls_configuration()),
+        (Some(tls), false) => Transport::tls_with_config(tls.to_tls_configuration()),
+        (None, true) => Transport::ws(),
+        (None, false) => Transport::tcp(),
+    });
+
+    if let Some(auth) = options.auth.as_ref().or(node.auth.as_ref()) {
+        if let Some((name, password)) = &auth.basic_auth_name_pwd {
+            mqtt_options.set_credentials(name, password);
+        } else if let Some(jwt) = &auth.jwt {
+            mqtt_options.set_credentials("", jwt);
+        }
+    }
+
+    Ok(mqtt_options)
+}
+
+fn new_mqtt_client(mqtt_options: MqttOptions, options: &BrokerOptions) -> (AsyncClient, EventLoop) {
+    let (mqtt_client, mut connection) = AsyncClient::new(mqtt_options, 10);
+    connection.set_network_options(*NetworkOptions::new().set_connection_timeout(options.timeout.as_secs()));
+    (mqtt_client, connection)
+}
+
+// Returns the first node whose broker accepts a connection, with its MQTT options.
+async fn connect(nodes: &[Node], options: &BrokerOptions) -> Result<Option<(Node, MqttOptions)>, Error> {
+    for node in nodes {
+        let mqtt_options = mqtt_options(node, options)?;
+        let (_, mut connection) = new_mqtt_client(mqtt_options.clone(), options);
+        // poll the event loop until we find a ConnAck event,
+        // which means that the mqtt client is ready to be used on this host
+        // if the event loop returns an error, we check the next node
+        while let Ok(event) = connection.poll().await {
+            if let Event::Incoming(Incoming::ConnAck(_)) = event {
+                return Ok(Some((node.clone(), mqtt_options)));
+            }
+        }
+        warn!("Connecting to the MQTT broker of {} failed", node.url);
+    }
+
+    Ok(None)
+}
+
+fn poll_mqtt(client: &Client, mut mqtt_client: AsyncClient, mut event_loop: EventLoop, mut node: Node) {
+    // A weak reference, so the MQTT client is still disconnected when the last client clone gets dropped.
+    let mqtt_client_guard = Arc::downgrade(&client.mqtt_client);
+    let mqtt_topic_handlers_guard = client.mqtt_topic_handlers.clone();
+    let options = client.broker_options.clone();
+    let event_sender = client.mqtt_event_channel.0.clone();
+    let network_info = client.network_info.clone();
+    let milestone_verifier = client.milestone_verifier.clone();
+    let node_manager = client.node_manager.clone();
+
     std::thread::spawn(move || {
         let runtime = tokio::runtime::Builder::new_current_thread()
             .enable_all()
@@ -169,7 +220,7 @@ fn poll_mqtt(
                                 .read()
                                 .await
                                 .keys()
-                                .map(|t| SubscribeFilter::new(t.topic().to_string(), QoS::AtLeastOnce))
+                                .map(|t| SubscribeFilter::new(t.to_string(), QoS::AtLeastOnce))
                                 .collect::<Vec<SubscribeFilter>>();
                             if !topics.is_empty() {
                                 let _ = mqtt_client.subscribe_many(topics).await;
@@ -177,70 +228,34 @@ fn poll_mqtt(
                         }
                     }
                     Ok(Event::Incoming(Incoming::Publish(p))) => {
-                        let topic = p.topic.clone();
                         let network_info = network_info.clone();
+                        let milestone_verifier = milestone_verifier.clone();
 
                         crate::async_runtime::spawn(async move {
+                            let topic = match Topic::try_new(p.topic.clone()) {
+                                Ok(topic) => topic,
+                                Err(e) => {
+                                    warn!("Received an event for an unknown topic: {:?}", e);
+                                    return;
+                                }
+                            };
                             let mqtt_topic_handlers = mqtt_topic_handlers_guard.read().await;
 
-                            if let Some(handlers) = mqtt_topic_handlers.get(&Topic::new_unchecked(topic.clone())) {
-                                let event = {
-                                    if topic.contains("blocks") || topic.contains("included-block") {
-                                        let payload = &*p.payload;
-                                        let protocol_parameters = &network_info.read().unwrap().protocol_parameters;
-
-                                        match Block::unpack_verified(payload, protocol_parameters) {
-                                            Ok(block) => Ok(TopicEvent {
-                                                topic,
-                                                payload: MqttPayload::Block(block),
-                                            }),
-                                            Err(e) => {
-                                                warn!("Block unpacking failed: {:?}", e);
-                                                Err(())
-                                            }
-                                        }
-                                    } else if topic.contains("milestones") {
-                                        let payload = &*p.payload;
-                                        let protocol_parameters = &network_info.read().unwrap().protocol_parameters;
-
-                                        match MilestonePayload::unpack_verified(payload, protocol_parameters) {
-                                            Ok(milestone_payload) => Ok(TopicEvent {
-                                                topic,
-                                                payload: MqttPayload::MilestonePayload(milestone_payload),
-                                            }),
-                                            Err(e) => {
-                                                warn!("MilestonePayload unpacking failed: {:?}", e);
-                                                Err(())
-                                            }
-                                        }
-                                    } else if topic.contains("receipts") {
-                                        let payload = &*p.payload;
-                                        let protocol_parameters = &network_info.read().unwrap().protocol_parameters;
-
-                                        match ReceiptMilestoneOption::unpack_verified(payload, protocol_parameters) {
-                                            Ok(receipt) => Ok(TopicEvent {
-                                                topic,
-                                                payload: MqttPayload::Receipt(receipt),
-                                            }),
-                                            Err(e) => {
-                                                warn!("Receipt unpacking failed: {:?}", e);
-                                                Err(())
-                                            }
-                                        }
-                                    } else {
-                                        match serde_json::from_slice(&p.payload) {
-                                            Ok(value) => Ok(TopicEvent {
-                                                topic,
-                                                payload: MqttPayload::Json(value),
-                                            }),
-                                            Err(e) => {
-                                                warn!("Cannot parse JSON: {:?}", e);
-                                                Err(())
-                                            }
-                                        }
-                                    }
+                            if let Some(handlers) = mqtt_topic_handlers.get(&topic) {
+                                let payload = {
+                                    let protocol_parameters = &network_info.read().unwrap().protocol_parameters;
+                                    decode_payload(&topic, &p.payload, protocol_parameters)
                                 };
-                                if let Ok(event) = event {
+                                if let (Some(milestone_verifier), Some(MqttPayload::MilestonePayload(milestone))) =
+                                    (&milestone_verifier, &payload)
+                                {
+                                    if let Err(e) = milestone_verifier.verify(milestone) {
+                                        warn!("Received an invalid milestone: {e}");
+                                        return;
+                                    }
+                                }
+                                if let Some(payload) = payload {
+                                    let event = TopicEvent { topic, payload };
                                     for handler in handlers {
                                         handler(&event);
                                     }
@@ -255,6 +270,25 @@ fn poll_mqtt(
                             connection_failure_count = 1;
                         }
                         if connection_failure_count == options.max_reconnection_attempts {
+                            // fail over to the broker of another node, the topics are resubscribed on its ConnAck,
+                            // unless the client was disconnected on purpose or dropped
+                            if let Some(mqtt_client_guard) = mqtt_client_guard.upgrade() {
+                                if mqtt_client_guard.read().await.is_some() {
+                                    let nodes = broker_nodes(&node_manager, Some(&node));
+                                    if let Ok(Some((failover_node, mqtt_options))) = connect(&nodes, &options).await {
+                                        warn!(
+                                            "Failing over from the MQTT broker of {} to {}",
+                                            node.url, failover_node.url
+                                        );
+                                        (mqtt_client, event_loop) = new_mqtt_client(mqtt_options, &options);
+                                        mqtt_client_guard.write().await.replace(mqtt_client.clone());
+                                        node = failover_node;
+                                        connection_failure_count = 0;
+                                        is_subscribed = false;
+                                        continue;
+                                    }
+                                }
+                            }
                             let _ = event_sender.send(MqttEvent::Disconnected);
                             break;
                         }
@@ -268,6 +302,58 @@ fn poll_mqtt(
     });
 }
 
+// Decodes the payload of an event into the type published on its topic.
+fn decode_payload(topic: &Topic, payload: &[u8], protocol_parameters: &ProtocolParameters) -> Option<MqttPayload> {
+    match topic {
+        Topic::Blocks
+        | Topic::TransactionBlocks
+        | Topic::TransactionTaggedDataBlocks { .. }
+        | Topic::TaggedDataBlocks { .. }
+        | Topic::TransactionIncludedBlock(_) => match Block::unpack_verified(payload, protocol_parameters) {
+            Ok(block) => Some(MqttPayload::Block(block)),
+            Err(e) => {
+                warn!("Block unpacking failed: {:?}", e);
+                None
+            }
+        },
+        Topic::Milestones => match MilestonePayload::unpack_verified(payload, protocol_parameters) {
+            Ok(milestone_payload) => Some(MqttPayload::MilestonePayload(milestone_payload)),
+            Err(e) => {
+                warn!("MilestonePayload unpacking failed: {:?}", e);
+                None
+            }
+        },
+        Topic::Receipts => match ReceiptMilestoneOption::unpack_verified(payload, protocol_parameters) {
+            Ok(receipt) => Some(MqttPayload::Receipt(receipt)),
+            Err(e) => {
+                warn!("Receipt unpacking failed: {:?}", e);
+                None
+            }
+        },
+        Topic::LatestMilestoneInfo | Topic::ConfirmedMilestoneInfo => {
+            decode_json(payload).map(MqttPayload::MilestoneInfo)
+        }
+        Topic::BlockMetadata(_) | Topic::ReferencedBlockMetadata => {
+            decode_json(payload).map(MqttPayload::BlockMetadata)
+        }
+        Topic::Output(_)
+        | Topic::AliasOutput(_)
+        | Topic::NftOutput(_)
+        | Topic::FoundryOutput(_)
+        | Topic::OutputsByUnlock { .. } => decode_json(payload).map(MqttPayload::OutputWithMetadata),
+    }
+}
+
+fn decode_json<T: DeserializeOwned>(payload: &[u8]) -> Option<T> {
+    match serde_json::from_slice(payload) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            warn!("Cannot parse JSON: {:?}", e);
+            None
+        }
+    }
+}
+
 /// MQTT subscriber.
 pub struct MqttManager<'a> {
     client: &'a Client,
@@ -340,8 +426,25 @@ impl<'a> MqttTopicManager<'a> {
         self,
         callback: C,
     ) -> Result<(), Error> {
-        let cb =
-            Arc::new(Box::new(callback) as Box<dyn Fn(&crate::node_api::mqtt::TopicEvent) + Send + Sync + 'static>);
+        self.subscribe_handler(Arc::new(Box::new(callback) as TopicHandler))
+            .await
+    }
+
+    /// Subscribe to the given topics with a stream of their events. Up to `capacity` events are buffered until the
+    /// stream is polled, further events are handled according to the `overflow_policy`. Dropping the stream
+    /// unsubscribes the topics, unless they're still used by other subscriptions.
+    pub async fn subscribe_stream(
+        self,
+        capacity: usize,
+        overflow_policy: OverflowPolicy,
+    ) -> Result<TopicEventStream, Error> {
+        let (stream, handler) = TopicEventStream::new(self.client, self.topics.clone(), capacity, overflow_policy);
+        self.subscribe_handler(handler).await?;
+
+        Ok(stream)
+    }
+
+    async fn subscribe_handler(self, handler: Arc<TopicHandler>) -> Result<(), Error> {
         set_mqtt_client(self.client).await?;
         self.client
             .mqtt_client
@@ -352,7 +455,7 @@ impl<'a> MqttTopicManager<'a> {
             .subscribe_many(
                 self.topics
                     .iter()
-                    .map(|t| SubscribeFilter::new(t.topic().to_string(), QoS::AtLeastOnce))
+                    .map(|t| SubscribeFilter::new(t.to_string(), QoS::AtLeastOnce))
                     .collect::<Vec<SubscribeFilter>>(),
             )
             .await?;
@@ -362,9 +465,9 @@ impl<'a> MqttTopicManager<'a> {
             for topic in self.topics {
                 #[allow(clippy::option_if_let_else)]
                 match mqtt_topic_handlers.get_mut(&topic) {
-                    Some(handlers) => handlers.push(cb.clone()),
+                    Some(handlers) => handlers.push(handler.clone()),
                     None => {
-                        mqtt_topic_handlers.insert(topic, vec![cb.clone()]);
+                        mqtt_topic_handlers.insert(topic, vec![handler.clone()]);
                     }
                 }
             }
@@ -387,7 +490,7 @@ impl<'a> MqttTopicManager<'a> {
 
         if let Some(client) = &*self.client.mqtt_client.write().await {
             for topic in &topics {
-                client.unsubscribe(topic.topic()).await?;
+                client.unsubscribe(topic.to_string()).await?;
             }
         }
 
@@ -407,3 +510,97 @@ impl<'a> MqttTopicManager<'a> {
         Ok(())
     }
 }
+
+// Removes a single handler from the given topics and unsubscribes the topics that aren't used by other handlers
+// anymore.
+async fn remove_topic_handler(
+    mqtt_client: &RwLock<Option<AsyncClient>>,
+    mqtt_topic_handlers: &RwLock<TopicHandlerMap>,
+    automatic_disconnect: bool,
+    topics: Vec<Topic>,
+    handler: &Arc<TopicHandler>,
+) -> Result<(), Error> {
+    let (unused_topics, empty_topic_handlers) = {
+        let mut mqtt_topic_handlers = mqtt_topic_handlers.write().await;
+        let mut unused_topics = Vec::new();
+        for topic in topics {
+            if let Some(handlers) = mqtt_topic_handlers.get_mut(&topic) {
+                handlers.retain(|h| !Arc::ptr_eq(h, handler));
+                if handlers.is_empty() {
+                    mqtt_topic_handlers.remove(&topic);
+                    unused_topics.push(topic);
+                }
+            }
+        }
+        (unused_topics, mqtt_topic_handlers.is_empty())
+    };
+
+    let mut mqtt_client = mqtt_client.write().await;
+    if let Some(mqtt_client) = &*mqtt_client {
+        for topic in &unused_topics {
+            mqtt_client.unsubscribe(topic.to_string()).await?;
+        }
+    }
+
+    if automatic_disconnect && empty_topic_handlers && !unused_topics.is_empty() {
+        if let Some(mqtt_client) = mqtt_client.take() {
+            mqtt_client.disconnect().await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node_manager::node::NodeAuth;
+
+    fn node(url: &str, auth: Option<NodeAuth>) -> Node {
+        Node {
+            url: url.parse().unwrap(),
+            auth,
+            disabled: false,
+        }
+    }
+
+    fn jwt(jwt: &str) -> NodeAuth {
+        NodeAuth {
+            jwt: Some(jwt.to_string()),
+            basic_auth_name_pwd: None,
+        }
+    }
+
+    #[test]
+    fn mqtt_options_credentials() {
+        let options = BrokerOptions::new();
+
+        let broker = mqtt_options(&node("https://node:443", None), &options).unwrap();
+        assert_eq!(broker.credentials(), None);
+        assert_eq!(
+            broker.broker_address(),
+            ("wss://node:443/api/mqtt/v1".to_string(), 1883)
+        );
+
+        // The credentials of the node are used, unless the broker options have their own.
+        let jwt_node = node("http://node:14265", Some(jwt("node")));
+        let broker = mqtt_options(&jwt_node, &options).unwrap();
+        assert_eq!(broker.credentials(), Some((String::new(), "node".to_string())));
+
+        let options = options.auth(NodeAuth {
+            jwt: None,
+            basic_auth_name_pwd: Some(("user".to_string(), "password".to_string())),
+        });
+        let broker = mqtt_options(&jwt_node, &options).unwrap();
+        assert_eq!(broker.credentials(), Some(("user".to_string(), "password".to_string())));
+    }
+
+    #[test]
+    fn mqtt_options_tls() {
+        let options = BrokerOptions::new().use_ws(false).tls(BrokerTlsOptions::new("ca"));
+        let broker = mqtt_options(&node("http://node:14265", None), &options).unwrap();
+
+        assert_eq!(broker.broker_address(), ("node".to_string(), 1883));
+        assert!(matches!(broker.transport(), Transport::Tls(_)));
+    }
+}
@@ -0,0 +1,375 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Stream based MQTT subscriptions
+
+use std::{
+    collections::VecDeque,
+    pin::Pin,
+    sync::{Arc, Mutex, Weak},
+    task::{Context, Poll, Waker},
+};
+
+use futures::Stream;
+use log::warn;
+use tokio::sync::RwLock;
+
+use super::{
+    types::{MqttClientState, TopicHandler, TopicHandlerMap},
+    Error, Topic, TopicEvent,
+};
+use crate::Client;
+
+/// Defines what happens to a new event if the buffer of a [`TopicEventStream`] is full.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Drops the oldest buffered event to make room for the new one.
+    DropOldest,
+    /// Drops the new event.
+    DropNewest,
+    /// Stops buffering events and returns [`Error::StreamOverflow`] after the buffered events, ending the stream.
+    Error,
+}
+
+impl Default for OverflowPolicy {
+    fn default() -> Self {
+        Self::DropOldest
+    }
+}
+
+#[derive(Debug)]
+struct EventBuffer {
+    events: VecDeque<TopicEvent>,
+    capacity: usize,
+    overflow_policy: OverflowPolicy,
+    overflowed: bool,
+    overflow_reported: bool,
+    // Set once the topic handler got dropped, e.g. because the topics were unsubscribed.
+    closed: bool,
+    waker: Option<Waker>,
+}
+
+impl EventBuffer {
+    fn new(capacity: usize, overflow_policy: OverflowPolicy) -> Self {
+        Self {
+            events: VecDeque::new(),
+            capacity: capacity.max(1),
+            overflow_policy,
+            overflowed: false,
+            overflow_reported: false,
+            closed: false,
+            waker: None,
+        }
+    }
+
+    fn push(&mut self, event: TopicEvent) {
+        if self.overflowed {
+            return;
+        }
+
+        if self.events.len() >= self.capacity {
+            match self.overflow_policy {
+                OverflowPolicy::DropOldest => {
+                    self.events.pop_front();
+                }
+                OverflowPolicy::DropNewest => return,
+                OverflowPolicy::Error => {
+                    self.overflowed = true;
+                    self.wake();
+                    return;
+                }
+            }
+        }
+
+        self.events.push_back(event);
+        self.wake();
+    }
+
+    fn poll_next(&mut self, cx: &Context<'_>) -> Poll<Option<Result<TopicEvent, Error>>> {
+        if let Some(event) = self.events.pop_front() {
+            return Poll::Ready(Some(Ok(event)));
+        }
+
+        if self.overflowed && !self.overflow_reported {
+            self.overflow_reported = true;
+            return Poll::Ready(Some(Err(Error::StreamOverflow(self.capacity))));
+        }
+
+        if self.overflowed || self.closed {
+            return Poll::Ready(None);
+        }
+
+        self.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+
+    fn wake(&mut self) {
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+// Moved into the topic handler, closes the stream when the handler gets dropped.
+struct EventSender(Arc<Mutex<EventBuffer>>);
+
+impl EventSender {
+    fn send(&self, event: &TopicEvent) {
+        self.0
+            .lock()
+            .expect("failed to lock the event buffer")
+            .push(event.clone());
+    }
+}
+
+impl Drop for EventSender {
+    fn drop(&mut self) {
+        if let Ok(mut buffer) = self.0.lock() {
+            buffer.closed = true;
+            buffer.wake();
+        }
+    }
+}
+
+/// A stream of the events of subscribed MQTT topics, created with
+/// [`MqttTopicManager::subscribe_stream()`](super::MqttTopicManager::subscribe_stream).
+///
+/// Events are buffered until the stream is polled, up to a capacity after which the [`OverflowPolicy`] applies.
+/// Dropping the stream unsubscribes its topics, unless they're still used by other subscriptions. The stream ends
+/// if the topics are unsubscribed or the MQTT broker is disconnected in another way.
+#[must_use = "streams do nothing unless polled and dropping the stream unsubscribes the topics"]
+pub struct TopicEventStream {
+    // Only the MQTT state is kept instead of a client clone, and the MQTT client weakly, so the stream doesn't keep
+    // the connection up once all client clones are dropped.
+    mqtt_client: Weak<MqttClientState>,
+    mqtt_topic_handlers: Arc<RwLock<TopicHandlerMap>>,
+    automatic_disconnect: bool,
+    topics: Vec<Topic>,
+    handler: Weak<TopicHandler>,
+    buffer: Arc<Mutex<EventBuffer>>,
+}
+
+impl TopicEventStream {
+    /// Creates the stream and the handler which has to be registered for its topics.
+    pub(crate) fn new(
+        client: &Client,
+        topics: Vec<Topic>,
+        capacity: usize,
+        overflow_policy: OverflowPolicy,
+    ) -> (Self, Arc<TopicHandler>) {
+        let buffer = Arc::new(Mutex::new(EventBuffer::new(capacity, overflow_policy)));
+        let sender = EventSender(buffer.clone());
+        let handler = Arc::new(Box::new(move |event: &TopicEvent| sender.send(event)) as TopicHandler);
+
+        (
+            Self {
+                mqtt_client: Arc::downgrade(&client.mqtt_client),
+                mqtt_topic_handlers: client.mqtt_topic_handlers.clone(),
+                automatic_disconnect: client.broker_options.automatic_disconnect,
+                topics,
+                handler: Arc::downgrade(&handler),
+                buffer,
+            },
+            handler,
+        )
+    }
+
+    /// Returns the subscribed topics.
+    pub fn topics(&self) -> &[Topic] {
+        &self.topics
+    }
+}
+
+impl Stream for TopicEventStream {
+    type Item = Result<TopicEvent, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.buffer
+            .lock()
+            .expect("failed to lock the event buffer")
+            .poll_next(cx)
+    }
+}
+
+impl Drop for TopicEventStream {
+    fn drop(&mut self) {
+        // The handler was already removed, e.g. by unsubscribing from its topics, or the client was dropped.
+        let (Some(handler), Some(mqtt_client)) = (self.handler.upgrade(), self.mqtt_client.upgrade()) else {
+            return;
+        };
+        let mqtt_topic_handlers = self.mqtt_topic_handlers.clone();
+        let automatic_disconnect = self.automatic_disconnect;
+        let topics = std::mem::take(&mut self.topics);
+
+        crate::async_runtime::spawn(async move {
+            if let Err(e) = super::remove_topic_handler(
+                &mqtt_client,
+                &mqtt_topic_handlers,
+                automatic_disconnect,
+                topics,
+                &handler,
+            )
+            .await
+            {
+                warn!("Unsubscribing the topics of a dropped stream failed: {e}");
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures::task::noop_waker;
+    use iota_types::block::payload::milestone::MilestoneId;
+    use rumqttc::{AsyncClient, MqttOptions};
+
+    use super::*;
+    use crate::mqtt::{MilestoneInfo, MqttPayload};
+
+    fn event(index: u32) -> TopicEvent {
+        TopicEvent {
+            topic: Topic::LatestMilestoneInfo,
+            payload: MqttPayload::MilestoneInfo(MilestoneInfo {
+                index,
+                timestamp: 0,
+                milestone_id: MilestoneId::null(),
+            }),
+        }
+    }
+
+    fn drain(buffer: &mut EventBuffer) -> Vec<Poll<Option<Result<TopicEvent, Error>>>> {
+        let waker = noop_waker();
+        let cx = Context::from_waker(&waker);
+        let mut polled = Vec::new();
+
+        loop {
+            let poll = buffer.poll_next(&cx);
+            let done = matches!(poll, Poll::Pending | Poll::Ready(None));
+            polled.push(poll);
+            if done {
+                return polled;
+            }
+        }
+    }
+
+    fn events(polled: &[Poll<Option<Result<TopicEvent, Error>>>]) -> Vec<TopicEvent> {
+        polled
+            .iter()
+            .filter_map(|poll| match poll {
+                Poll::Ready(Some(Ok(event))) => Some(event.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn drop_oldest() {
+        let mut buffer = EventBuffer::new(2, OverflowPolicy::DropOldest);
+        (0..3).for_each(|index| buffer.push(event(index)));
+
+        let polled = drain(&mut buffer);
+        assert_eq!(events(&polled), vec![event(1), event(2)]);
+        assert!(matches!(polled.last(), Some(Poll::Pending)));
+    }
+
+    #[test]
+    fn drop_newest() {
+        let mut buffer = EventBuffer::new(2, OverflowPolicy::DropNewest);
+        (0..3).for_each(|index| buffer.push(event(index)));
+
+        let polled = drain(&mut buffer);
+        assert_eq!(events(&polled), vec![event(0), event(1)]);
+        assert!(matches!(polled.last(), Some(Poll::Pending)));
+    }
+
+    #[test]
+    fn overflow_error() {
+        let mut buffer = EventBuffer::new(2, OverflowPolicy::Error);
+        (0..4).for_each(|index| buffer.push(event(index)));
+
+        // The buffered events are returned before the error, which ends the stream.
+        let polled = drain(&mut buffer);
+        assert_eq!(events(&polled), vec![event(0), event(1)]);
+        assert!(matches!(polled[2], Poll::Ready(Some(Err(Error::StreamOverflow(2))))));
+        assert!(matches!(polled[3], Poll::Ready(None)));
+    }
+
+    #[test]
+    fn closed_by_dropped_sender() {
+        let buffer = Arc::new(Mutex::new(EventBuffer::new(2, OverflowPolicy::default())));
+        let sender = EventSender(buffer.clone());
+        sender.send(&event(0));
+        drop(sender);
+
+        let polled = drain(&mut buffer.lock().unwrap());
+        assert_eq!(events(&polled), vec![event(0)]);
+        assert!(matches!(polled.last(), Some(Poll::Ready(None))));
+    }
+
+    // Registers a stream for the topics like `MqttTopicManager::subscribe_stream()`, without a client.
+    async fn subscribe(
+        mqtt_client: &Arc<MqttClientState>,
+        mqtt_topic_handlers: &Arc<RwLock<TopicHandlerMap>>,
+        topics: &[&str],
+    ) -> (TopicEventStream, Arc<TopicHandler>) {
+        let topics = topics
+            .iter()
+            .map(|topic| Topic::try_new(*topic).unwrap())
+            .collect::<Vec<_>>();
+        let buffer = Arc::new(Mutex::new(EventBuffer::new(2, OverflowPolicy::default())));
+        let sender = EventSender(buffer.clone());
+        let handler = Arc::new(Box::new(move |event: &TopicEvent| sender.send(event)) as TopicHandler);
+
+        let mut handlers = mqtt_topic_handlers.write().await;
+        for topic in &topics {
+            handlers.entry(topic.clone()).or_default().push(handler.clone());
+        }
+
+        (
+            TopicEventStream {
+                mqtt_client: Arc::downgrade(mqtt_client),
+                mqtt_topic_handlers: mqtt_topic_handlers.clone(),
+                automatic_disconnect: true,
+                topics,
+                handler: Arc::downgrade(&handler),
+                buffer,
+            },
+            handler,
+        )
+    }
+
+    #[tokio::test]
+    async fn drop_unsubscribes_own_topics() {
+        // The event loop is never polled, requests are only queued.
+        let (async_client, _event_loop) = AsyncClient::new(MqttOptions::new("stream-test", "localhost", 1883), 10);
+        let mqtt_client = Arc::new(MqttClientState::default());
+        mqtt_client.write().await.replace(async_client);
+        let mqtt_topic_handlers = Arc::new(RwLock::new(TopicHandlerMap::new()));
+
+        let (first, _) = subscribe(&mqtt_client, &mqtt_topic_handlers, &["milestone-info/latest", "blocks"]).await;
+        let (_second, second_handler) = subscribe(&mqtt_client, &mqtt_topic_handlers, &["blocks", "milestones"]).await;
+
+        // The topics are unsubscribed by a spawned task.
+        drop(first);
+        for _ in 0..100 {
+            if mqtt_topic_handlers.read().await.len() == 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let handlers = mqtt_topic_handlers.read().await;
+        let mut topics = handlers.keys().map(|topic| topic.topic()).collect::<Vec<_>>();
+        topics.sort_unstable();
+        assert_eq!(topics, vec!["blocks", "milestones"]);
+        // The shared topic is only left with the handler of the other stream.
+        for handlers in handlers.values() {
+            assert_eq!(handlers.len(), 1);
+            assert!(Arc::ptr_eq(&handlers[0], &second_handler));
+        }
+        // The connection is still up for the other stream.
+        assert!(mqtt_client.read().await.is_some());
+    }
+}
@@ -3,44 +3,91 @@
 
 //! MQTT types
 
-use std::{collections::HashMap, sync::Arc, time::Duration};
-
-use iota_types::block::{
-    payload::{milestone::ReceiptMilestoneOption, MilestonePayload},
-    Block,
+use std::{collections::HashMap, fmt, ops::Deref, str::FromStr, sync::Arc, time::Duration};
+
+use iota_types::{
+    api::core::response::{BlockMetadataResponse, OutputWithMetadataResponse},
+    block::{
+        output::{AliasId, FoundryId, NftId, OutputId},
+        payload::{
+            milestone::{MilestoneId, ReceiptMilestoneOption},
+            transaction::TransactionId,
+            MilestonePayload,
+        },
+        Block, BlockId,
+    },
 };
-use regex::RegexSet;
-use serde::{de::Error as _, Deserialize, Deserializer};
-use serde_json::Value;
+use regex::Regex;
+use rumqttc::{AsyncClient, Key, TlsConfiguration};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use tokio::sync::RwLock;
 
 use super::Error;
+use crate::node_manager::node::NodeAuth;
 
-type TopicHandler = Box<dyn Fn(&TopicEvent) + Send + Sync>;
+pub(crate) type TopicHandler = Box<dyn Fn(&TopicEvent) + Send + Sync>;
 
 pub(crate) type TopicHandlerMap = HashMap<Topic, Vec<Arc<TopicHandler>>>;
 
+/// The MQTT client shared by all clones of a `Client`, disconnected once the last of them is dropped.
+#[derive(Default)]
+pub(crate) struct MqttClientState(RwLock<Option<AsyncClient>>);
+
+impl Deref for MqttClientState {
+    type Target = RwLock<Option<AsyncClient>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Drop for MqttClientState {
+    fn drop(&mut self) {
+        if let Some(mqtt_client) = self.0.get_mut().take() {
+            // Fails if the event loop already stopped, in which case there's nothing left to disconnect.
+            let _ = mqtt_client.try_disconnect();
+        }
+    }
+}
+
 /// An event from a MQTT topic.
 
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct TopicEvent {
     /// the MQTT topic.
-    pub topic: String,
+    pub topic: Topic,
     /// The MQTT event payload.
     pub payload: MqttPayload,
 }
 
-/// The payload of an `TopicEvent`.
+/// The payload of an `TopicEvent`, decoded according to its topic.
 
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub enum MqttPayload {
-    /// In case it contains JSON.
-    Json(Value),
     /// In case it contains a `Block` object.
     Block(Block),
     /// In case it contains a `Milestone` object.
     MilestonePayload(MilestonePayload),
     /// In case it contains a `Receipt` object.
     Receipt(ReceiptMilestoneOption),
+    /// In case it contains the info of the latest or confirmed milestone.
+    MilestoneInfo(MilestoneInfo),
+    /// In case it contains the metadata of a block.
+    BlockMetadata(BlockMetadataResponse),
+    /// In case it contains an output and its metadata.
+    OutputWithMetadata(OutputWithMetadataResponse),
+}
+
+/// The info of a milestone, published on the `milestone-info` topics.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MilestoneInfo {
+    /// The index of the milestone.
+    pub index: u32,
+    /// The timestamp of the milestone.
+    pub timestamp: u32,
+    /// The ID of the milestone.
+    pub milestone_id: MilestoneId,
 }
 
 /// Mqtt events.
@@ -68,6 +115,10 @@ pub struct BrokerOptions {
     pub(crate) port: u16,
     #[serde(default = "default_max_reconnection_attempts", rename = "maxReconnectionAttempts")]
     pub(crate) max_reconnection_attempts: usize,
+    #[serde(default)]
+    pub(crate) auth: Option<NodeAuth>,
+    #[serde(default)]
+    pub(crate) tls: Option<BrokerTlsOptions>,
 }
 
 fn default_broker_automatic_disconnect() -> bool {
@@ -98,6 +149,8 @@ impl Default for BrokerOptions {
             use_ws: default_broker_use_ws(),
             port: default_broker_port(),
             max_reconnection_attempts: default_max_reconnection_attempts(),
+            auth: None,
+            tls: None,
         }
     }
 }
@@ -132,16 +185,190 @@ impl BrokerOptions {
         self
     }
 
-    /// Sets the maximum number of reconnection attempts. 0 is unlimited.
+    /// Sets the maximum number of reconnection attempts. 0 is unlimited. Once the attempts are used up, the client
+    /// fails over to the broker of another healthy node and resubscribes all topics.
     pub fn max_reconnection_attempts(mut self, max_reconnection_attempts: usize) -> Self {
         self.max_reconnection_attempts = max_reconnection_attempts;
         self
     }
+
+    /// Sets the credentials used for all brokers, instead of the [`NodeAuth`] of their nodes. Basic auth credentials
+    /// are sent as MQTT user name and password, a JWT as password with an empty user name.
+    pub fn auth(mut self, auth: NodeAuth) -> Self {
+        self.auth.replace(auth);
+        self
+    }
+
+    /// Sets the TLS options used to connect to the brokers.
+    pub fn tls(mut self, tls: BrokerTlsOptions) -> Self {
+        self.tls.replace(tls);
+        self
+    }
+}
+
+/// The TLS options of the connection to MQTT brokers.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct BrokerTlsOptions {
+    /// PEM encoded root certificates the certificates of the brokers are verified with.
+    pub ca_certificates: String,
+    /// Client certificate for brokers that require client authentication.
+    #[serde(default)]
+    pub client_auth: Option<BrokerClientAuth>,
+}
+
+/// A client certificate to authenticate at MQTT brokers.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct BrokerClientAuth {
+    /// PEM encoded certificate chain.
+    pub certificate_chain: String,
+    /// PEM encoded private key of the certificate.
+    pub private_key: BrokerPrivateKey,
+}
+
+/// A PEM encoded private key of a client certificate.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum BrokerPrivateKey {
+    /// RSA private key.
+    Rsa(String),
+    /// ECC private key.
+    Ecc(String),
+}
+
+impl BrokerTlsOptions {
+    /// Creates TLS options with PEM encoded root certificates.
+    pub fn new(ca_certificates: impl Into<String>) -> Self {
+        Self {
+            ca_certificates: ca_certificates.into(),
+            client_auth: None,
+        }
+    }
+
+    /// Sets a PEM encoded client certificate chain and its private key.
+    #[must_use]
+    pub fn with_client_auth(mut self, certificate_chain: impl Into<String>, private_key: BrokerPrivateKey) -> Self {
+        self.client_auth.replace(BrokerClientAuth {
+            certificate_chain: certificate_chain.into(),
+            private_key,
+        });
+        self
+    }
+
+    pub(crate) fn to_tls_configuration(&self) -> TlsConfiguration {
+        TlsConfiguration::Simple {
+            ca: self.ca_certificates.as_bytes().to_vec(),
+            alpn: None,
+            client_auth: self.client_auth.as_ref().map(|client_auth| {
+                (
+                    client_auth.certificate_chain.as_bytes().to_vec(),
+                    match &client_auth.private_key {
+                        BrokerPrivateKey::Rsa(key) => Key::RSA(key.as_bytes().to_vec()),
+                        BrokerPrivateKey::Ecc(key) => Key::ECC(key.as_bytes().to_vec()),
+                    },
+                )
+            }),
+        }
+    }
+}
+
+/// The unlock condition an address has to be in for the [`Topic::OutputsByUnlock`] topic.
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+pub enum UnlockKind {
+    /// Any of the unlock conditions.
+    Any,
+    /// The address unlock condition.
+    Address,
+    /// The storage deposit return unlock condition.
+    StorageReturn,
+    /// The expiration unlock condition.
+    Expiration,
+    /// The state controller address unlock condition.
+    StateController,
+    /// The governor address unlock condition.
+    Governor,
+    /// The immutable alias address unlock condition.
+    ImmutableAlias,
+}
+
+impl UnlockKind {
+    /// Returns the topic segment of the unlock kind.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Any => "+",
+            Self::Address => "address",
+            Self::StorageReturn => "storage-return",
+            Self::Expiration => "expiration",
+            Self::StateController => "state-controller",
+            Self::Governor => "governor",
+            Self::ImmutableAlias => "immutable-alias",
+        }
+    }
+
+    fn from_topic_segment(segment: &str) -> Option<Self> {
+        Some(match segment {
+            "+" => Self::Any,
+            "address" => Self::Address,
+            "storage-return" => Self::StorageReturn,
+            "expiration" => Self::Expiration,
+            "state-controller" => Self::StateController,
+            "governor" => Self::Governor,
+            "immutable-alias" => Self::ImmutableAlias,
+            _ => return None,
+        })
+    }
 }
 
 /// A MQTT topic.
-#[derive(Debug, Clone, Hash, Eq, PartialEq, Serialize)]
-pub struct Topic(String);
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub enum Topic {
+    /// `milestone-info/latest`, publishes [`MqttPayload::MilestoneInfo`].
+    LatestMilestoneInfo,
+    /// `milestone-info/confirmed`, publishes [`MqttPayload::MilestoneInfo`].
+    ConfirmedMilestoneInfo,
+    /// `milestones`, publishes [`MqttPayload::MilestonePayload`].
+    Milestones,
+    /// `blocks`, publishes [`MqttPayload::Block`].
+    Blocks,
+    /// `blocks/transaction`, publishes [`MqttPayload::Block`].
+    TransactionBlocks,
+    /// `blocks/transaction/tagged-data[/{tag}]`, publishes [`MqttPayload::Block`].
+    TransactionTaggedDataBlocks {
+        /// Optional tag of the tagged data, between 1 and 64 bytes.
+        tag: Option<Vec<u8>>,
+    },
+    /// `blocks/tagged-data[/{tag}]`, publishes [`MqttPayload::Block`].
+    TaggedDataBlocks {
+        /// Optional tag of the tagged data, between 1 and 64 bytes.
+        tag: Option<Vec<u8>>,
+    },
+    /// `block-metadata/{blockId}`, publishes [`MqttPayload::BlockMetadata`].
+    BlockMetadata(BlockId),
+    /// `block-metadata/referenced`, publishes [`MqttPayload::BlockMetadata`].
+    ReferencedBlockMetadata,
+    /// `transactions/{transactionId}/included-block`, publishes [`MqttPayload::Block`].
+    TransactionIncludedBlock(TransactionId),
+    /// `outputs/{outputId}`, publishes [`MqttPayload::OutputWithMetadata`].
+    Output(OutputId),
+    /// `outputs/alias/{aliasId}`, publishes [`MqttPayload::OutputWithMetadata`].
+    AliasOutput(AliasId),
+    /// `outputs/nft/{nftId}`, publishes [`MqttPayload::OutputWithMetadata`].
+    NftOutput(NftId),
+    /// `outputs/foundry/{foundryId}`, publishes [`MqttPayload::OutputWithMetadata`].
+    FoundryOutput(FoundryId),
+    /// `outputs/unlock/{kind}/{bech32Address}[/spent]`, publishes [`MqttPayload::OutputWithMetadata`].
+    OutputsByUnlock {
+        /// The unlock condition the address has to be in.
+        kind: UnlockKind,
+        /// The Bech32 encoded address.
+        address: String,
+        /// Whether the spent outputs are published instead of the created ones.
+        spent: bool,
+    },
+    /// `receipts`, publishes [`MqttPayload::Receipt`].
+    Receipts,
+}
 
 impl TryFrom<String> for Topic {
     type Error = Error;
@@ -151,6 +378,117 @@ impl TryFrom<String> for Topic {
     }
 }
 
+impl FromStr for Topic {
+    type Err = Error;
+
+    fn from_str(topic: &str) -> std::result::Result<Self, Self::Err> {
+        let invalid = || Error::InvalidTopic(topic.to_string());
+        let segments = topic.split('/').collect::<Vec<_>>();
+
+        Ok(match segments.as_slice() {
+            ["milestone-info", "latest"] => Self::LatestMilestoneInfo,
+            ["milestone-info", "confirmed"] => Self::ConfirmedMilestoneInfo,
+            ["milestones"] => Self::Milestones,
+            ["blocks"] => Self::Blocks,
+            ["blocks", "transaction"] => Self::TransactionBlocks,
+            ["blocks", "transaction", "tagged-data"] => Self::TransactionTaggedDataBlocks { tag: None },
+            ["blocks", "transaction", "tagged-data", tag] => Self::TransactionTaggedDataBlocks {
+                tag: Some(parse_tag(tag).ok_or_else(invalid)?),
+            },
+            ["blocks", "tagged-data"] => Self::TaggedDataBlocks { tag: None },
+            ["blocks", "tagged-data", tag] => Self::TaggedDataBlocks {
+                tag: Some(parse_tag(tag).ok_or_else(invalid)?),
+            },
+            ["block-metadata", "referenced"] => Self::ReferencedBlockMetadata,
+            ["block-metadata", block_id] => Self::BlockMetadata(parse_id(block_id).ok_or_else(invalid)?),
+            ["transactions", transaction_id, "included-block"] => {
+                Self::TransactionIncludedBlock(parse_id(transaction_id).ok_or_else(invalid)?)
+            }
+            ["outputs", "alias", alias_id] => Self::AliasOutput(parse_id(alias_id).ok_or_else(invalid)?),
+            ["outputs", "nft", nft_id] => Self::NftOutput(parse_id(nft_id).ok_or_else(invalid)?),
+            ["outputs", "foundry", foundry_id] => Self::FoundryOutput(parse_id(foundry_id).ok_or_else(invalid)?),
+            ["outputs", "unlock", kind, address, spent @ ..] if spent.is_empty() || spent == ["spent"] => {
+                let bech32_address = lazy_static!(
+                    Regex::new(r"^[\x21-\x7E]{1,30}1[A-Za-z0-9]+$").expect("cannot build regex") => Regex);
+                if !bech32_address.is_match(address) {
+                    return Err(invalid());
+                }
+
+                Self::OutputsByUnlock {
+                    kind: UnlockKind::from_topic_segment(kind).ok_or_else(invalid)?,
+                    address: address.to_string(),
+                    spent: !spent.is_empty(),
+                }
+            }
+            ["outputs", output_id] => Self::Output(parse_id(output_id).ok_or_else(invalid)?),
+            ["receipts"] => Self::Receipts,
+            _ => return Err(invalid()),
+        })
+    }
+}
+
+// Only accepts the lowercase hex encoding that nodes use in topics.
+fn is_lowercase_hex(value: &str) -> bool {
+    value
+        .strip_prefix("0x")
+        .map_or(false, |hex| hex.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')))
+}
+
+fn parse_id<T: FromStr>(value: &str) -> Option<T> {
+    is_lowercase_hex(value).then(|| value.parse().ok()).flatten()
+}
+
+fn parse_tag(value: &str) -> Option<Vec<u8>> {
+    is_lowercase_hex(value)
+        .then(|| prefix_hex::decode::<Vec<u8>, _>(value).ok())
+        .flatten()
+        .filter(|tag| (1..=64).contains(&tag.len()))
+}
+
+impl fmt::Display for Topic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LatestMilestoneInfo => write!(f, "milestone-info/latest"),
+            Self::ConfirmedMilestoneInfo => write!(f, "milestone-info/confirmed"),
+            Self::Milestones => write!(f, "milestones"),
+            Self::Blocks => write!(f, "blocks"),
+            Self::TransactionBlocks => write!(f, "blocks/transaction"),
+            Self::TransactionTaggedDataBlocks { tag: None } => write!(f, "blocks/transaction/tagged-data"),
+            Self::TransactionTaggedDataBlocks { tag: Some(tag) } => {
+                write!(f, "blocks/transaction/tagged-data/{}", prefix_hex::encode(tag.clone()))
+            }
+            Self::TaggedDataBlocks { tag: None } => write!(f, "blocks/tagged-data"),
+            Self::TaggedDataBlocks { tag: Some(tag) } => {
+                write!(f, "blocks/tagged-data/{}", prefix_hex::encode(tag.clone()))
+            }
+            Self::BlockMetadata(block_id) => write!(f, "block-metadata/{block_id}"),
+            Self::ReferencedBlockMetadata => write!(f, "block-metadata/referenced"),
+            Self::TransactionIncludedBlock(transaction_id) => write!(f, "transactions/{transaction_id}/included-block"),
+            Self::Output(output_id) => write!(f, "outputs/{output_id}"),
+            Self::AliasOutput(alias_id) => write!(f, "outputs/alias/{alias_id}"),
+            Self::NftOutput(nft_id) => write!(f, "outputs/nft/{nft_id}"),
+            Self::FoundryOutput(foundry_id) => write!(f, "outputs/foundry/{foundry_id}"),
+            Self::OutputsByUnlock { kind, address, spent } => {
+                write!(f, "outputs/unlock/{}/{address}", kind.as_str())?;
+                if *spent {
+                    write!(f, "/spent")?;
+                }
+                Ok(())
+            }
+            Self::Receipts => write!(f, "receipts"),
+        }
+    }
+}
+
+impl Serialize for Topic {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
 impl<'de> Deserialize<'de> for Topic {
     fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
     where
@@ -162,50 +500,13 @@ impl<'de> Deserialize<'de> for Topic {
 }
 
 impl Topic {
-    /// Creates a new topic and checks if it's valid.
+    /// Parses a topic and checks if it's valid.
     pub fn try_new(topic: impl Into<String>) -> Result<Self, Error> {
-        let valid_topics = lazy_static!(
-        RegexSet::new([
-            // Milestone topics.
-            r"^milestone-info/latest$",
-            r"^milestone-info/confirmed$",
-            r"^milestones$",
-            // Block topics.
-            r"^blocks$",
-            r"^blocks/transaction$",
-            r"^blocks/transaction/tagged-data$",
-            r"^blocks/transaction/tagged-data/0x((?:[a-f0-9]{2}){1,64})$",
-            r"^blocks/tagged-data$",
-            r"^blocks/tagged-data/0x((?:[a-f0-9]{2}){1,64})$",
-            r"^block-metadata/0x([a-f0-9]{64})$",
-            r"^block-metadata/referenced$",
-            // Transaction topics.
-            r"^transactions/0x([a-f0-9]{64})/included-block$",
-            // Output topics.
-            r"^outputs/0x([a-f0-9]{64})(\d{4})$",
-            r"^outputs/alias/0x([a-f0-9]{64})$",
-            r"^outputs/nft/0x([a-f0-9]{64})$",
-            r"^outputs/foundry/0x([a-f0-9]{76})$",
-            r"^outputs/unlock/(\+|address|storage-return|expiration|state-controller|governor|immutable-alias)/[\x21-\x7E]{1,30}1[A-Za-z0-9]+(?:/spent)?$",
-            // Receipt topics.
-            r"^receipts$",
-        ]).expect("cannot build regex set") => RegexSet);
-        let topic = topic.into();
-
-        if valid_topics.is_match(&topic) {
-            Ok(Self(topic))
-        } else {
-            Err(Error::InvalidTopic(topic))
-        }
-    }
-
-    /// Creates a new topic without checking if the given string represents a valid topic.
-    pub fn new_unchecked(value: String) -> Self {
-        Self(value)
+        topic.into().parse()
     }
 
     /// Returns the topic.
-    pub fn topic(&self) -> &str {
-        &self.0
+    pub fn topic(&self) -> String {
+        self.to_string()
     }
 }
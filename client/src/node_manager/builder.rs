@@ -17,8 +17,11 @@ use crate::{
     constants::{DEFAULT_MIN_QUORUM_SIZE, DEFAULT_QUORUM_THRESHOLD, DEFAULT_USER_AGENT, NODE_SYNC_INTERVAL},
     error::{Error, Result},
     node_manager::{
-        http_client::HttpClient,
+        cache::ResponseCache,
+        http_client::{HttpClient, HttpTransport, SharedHttpTransport},
         node::{Node, NodeAuth, NodeDto},
+        retry::RetryPolicy,
+        scoring::NodeScores,
         NodeManager,
     },
 };
@@ -54,9 +57,18 @@ pub struct NodeManagerBuilder {
     /// % of nodes that have to return the same response so it gets accepted
     #[serde(rename = "quorumThreshold", default = "default_quorum_threshold")]
     pub quorum_threshold: usize,
+    /// Defines if and how failed requests are retried
+    #[serde(rename = "retryPolicy", default)]
+    pub retry_policy: RetryPolicy,
     /// The User-Agent header for requests
     #[serde(rename = "userAgent", default = "default_user_agent")]
     pub user_agent: String,
+    /// Cache for node API responses, nothing is cached if none is set
+    #[serde(skip)]
+    pub(crate) response_cache: Option<ResponseCache>,
+    /// Custom transport for HTTP requests, reqwest is used if none is set
+    #[serde(skip)]
+    pub(crate) http_transport: Option<SharedHttpTransport>,
 }
 
 fn default_user_agent() -> String {
@@ -208,11 +220,26 @@ impl NodeManagerBuilder {
         self
     }
 
+    pub(crate) fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    pub(crate) fn with_response_cache(mut self, response_cache: ResponseCache) -> Self {
+        self.response_cache.replace(response_cache);
+        self
+    }
+
     pub(crate) fn with_user_agent(mut self, user_agent: String) -> Self {
         self.user_agent = user_agent;
         self
     }
 
+    pub(crate) fn with_http_transport(mut self, http_transport: Arc<dyn HttpTransport>) -> Self {
+        self.http_transport.replace(SharedHttpTransport(http_transport));
+        self
+    }
+
     pub(crate) fn build(self, healthy_nodes: Arc<RwLock<HashMap<Node, InfoResponse>>>) -> NodeManager {
         NodeManager {
             primary_node: self.primary_node.map(|node| node.into()),
@@ -227,7 +254,13 @@ impl NodeManagerBuilder {
             quorum: self.quorum,
             min_quorum_size: self.min_quorum_size,
             quorum_threshold: self.quorum_threshold,
-            http_client: HttpClient::new(self.user_agent),
+            retry_policy: self.retry_policy,
+            response_cache: self.response_cache,
+            http_client: match self.http_transport {
+                Some(http_transport) => HttpClient::with_transport(http_transport.0, self.user_agent),
+                None => HttpClient::new(self.user_agent),
+            },
+            node_scores: NodeScores::default(),
         }
     }
 }
@@ -244,7 +277,10 @@ impl Default for NodeManagerBuilder {
             quorum: false,
             min_quorum_size: DEFAULT_MIN_QUORUM_SIZE,
             quorum_threshold: DEFAULT_QUORUM_THRESHOLD,
+            retry_policy: RetryPolicy::default(),
             user_agent: DEFAULT_USER_AGENT.to_string(),
+            response_cache: None,
+            http_transport: None,
         }
     }
 }
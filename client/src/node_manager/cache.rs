@@ -0,0 +1,656 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Optional cache for node API responses.
+//!
+//! Blocks, milestones and UTXO changes never change once they exist, so they are cached until they get evicted, mutable
+//! responses like output metadata are only cached for a short time.
+
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+#[cfg(not(target_family = "wasm"))]
+use std::{collections::VecDeque, path::PathBuf};
+
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    constants::{DEFAULT_RESPONSE_CACHE_MAX_SIZE, DEFAULT_RESPONSE_CACHE_MUTABLE_TTL},
+    error::{Error, Result},
+    unix_timestamp_millis, Client,
+};
+
+/// A cached response.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CacheEntry {
+    /// The serialized response.
+    pub value: Vec<u8>,
+    /// Unix timestamp in milliseconds after which the entry is outdated, `None` if it never expires.
+    pub expires_at: Option<u64>,
+}
+
+impl CacheEntry {
+    fn is_expired(&self, now: u64) -> bool {
+        self.expires_at.map_or(false, |expires_at| expires_at <= now)
+    }
+}
+
+/// The storage of a [`ResponseCache`].
+///
+/// Backends are responsible for bounding their size, expired entries are removed by the [`ResponseCache`] when they
+/// are read. Failing backends don't fail requests, their errors are only logged.
+#[cfg_attr(target_family = "wasm", async_trait(?Send))]
+#[cfg_attr(not(target_family = "wasm"), async_trait)]
+pub trait CacheBackend: Send + Sync {
+    /// Gets the entry stored under `key`.
+    async fn get(&self, key: &str) -> Result<Option<CacheEntry>>;
+
+    /// Stores an entry under `key`, replacing a previous one.
+    async fn insert(&self, key: &str, entry: CacheEntry) -> Result<()>;
+
+    /// Removes the entry stored under `key`.
+    async fn remove(&self, key: &str) -> Result<()>;
+
+    /// Removes all entries.
+    async fn clear(&self) -> Result<()>;
+}
+
+#[derive(Debug, Default)]
+struct MemoryCacheState {
+    // Entries with the tick of their last use.
+    entries: HashMap<String, (CacheEntry, u64)>,
+    size: usize,
+    tick: u64,
+}
+
+impl MemoryCacheState {
+    fn remove(&mut self, key: &str) {
+        if let Some((entry, _)) = self.entries.remove(key) {
+            self.size -= entry.value.len();
+        }
+    }
+}
+
+/// A [`CacheBackend`] that keeps entries in memory and evicts the least recently used ones once the size of all
+/// values exceeds `max_size` bytes.
+#[derive(Debug)]
+pub struct MemoryCache {
+    max_size: usize,
+    state: Mutex<MemoryCacheState>,
+}
+
+impl MemoryCache {
+    /// Creates an in-memory cache which holds at most `max_size` bytes.
+    pub fn new(max_size: usize) -> Self {
+        Self {
+            max_size,
+            state: Mutex::new(MemoryCacheState::default()),
+        }
+    }
+}
+
+impl Default for MemoryCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_RESPONSE_CACHE_MAX_SIZE)
+    }
+}
+
+#[cfg_attr(target_family = "wasm", async_trait(?Send))]
+#[cfg_attr(not(target_family = "wasm"), async_trait)]
+impl CacheBackend for MemoryCache {
+    async fn get(&self, key: &str) -> Result<Option<CacheEntry>> {
+        let mut state = self.state.lock().map_err(|_| Error::PoisonError)?;
+        state.tick += 1;
+        let tick = state.tick;
+
+        Ok(state.entries.get_mut(key).map(|(entry, last_use)| {
+            *last_use = tick;
+            entry.clone()
+        }))
+    }
+
+    async fn insert(&self, key: &str, entry: CacheEntry) -> Result<()> {
+        let mut state = self.state.lock().map_err(|_| Error::PoisonError)?;
+        state.remove(key);
+
+        if entry.value.len() > self.max_size {
+            return Ok(());
+        }
+
+        while state.size + entry.value.len() > self.max_size {
+            let least_recently_used = state
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, last_use))| *last_use)
+                .map(|(key, _)| key.clone());
+            match least_recently_used {
+                Some(key) => state.remove(&key),
+                None => break,
+            }
+        }
+
+        state.tick += 1;
+        let tick = state.tick;
+        state.size += entry.value.len();
+        state.entries.insert(key.to_string(), (entry, tick));
+
+        Ok(())
+    }
+
+    async fn remove(&self, key: &str) -> Result<()> {
+        self.state.lock().map_err(|_| Error::PoisonError)?.remove(key);
+        Ok(())
+    }
+
+    async fn clear(&self) -> Result<()> {
+        *self.state.lock().map_err(|_| Error::PoisonError)? = MemoryCacheState::default();
+        Ok(())
+    }
+}
+
+/// A [`CacheBackend`] that stores every entry in its own file in a directory, so cached responses survive restarts.
+/// The oldest entries are evicted once the size of all files exceeds `max_size` bytes.
+#[cfg(not(target_family = "wasm"))]
+#[cfg_attr(docsrs, doc(cfg(not(target_family = "wasm"))))]
+#[derive(Debug)]
+pub struct FileCache {
+    directory: PathBuf,
+    max_size: u64,
+    // File names with their size, the oldest first.
+    index: Mutex<VecDeque<(String, u64)>>,
+}
+
+#[cfg(not(target_family = "wasm"))]
+impl FileCache {
+    const FILE_EXTENSION: &'static str = "cache";
+
+    /// Opens the cache in `directory`, creating it if needed, entries of a previous run are kept.
+    pub fn open(directory: impl Into<PathBuf>, max_size: u64) -> Result<Self> {
+        let directory = directory.into();
+        std::fs::create_dir_all(&directory)?;
+
+        let mut files = Vec::new();
+        for dir_entry in std::fs::read_dir(&directory)? {
+            let dir_entry = dir_entry?;
+            let path = dir_entry.path();
+            if path
+                .extension()
+                .map_or(true, |extension| extension != Self::FILE_EXTENSION)
+            {
+                continue;
+            }
+            let metadata = dir_entry.metadata()?;
+            if let Some(file_name) = path.file_name().and_then(|file_name| file_name.to_str()) {
+                files.push((metadata.modified()?, file_name.to_string(), metadata.len()));
+            }
+        }
+        files.sort();
+
+        Ok(Self {
+            directory,
+            max_size,
+            index: Mutex::new(
+                files
+                    .into_iter()
+                    .map(|(_, file_name, size)| (file_name, size))
+                    .collect(),
+            ),
+        })
+    }
+
+    fn file_name(key: &str) -> String {
+        use crypto::hashes::{blake2b::Blake2b256, Digest};
+
+        let hash = Blake2b256::digest(key.as_bytes());
+        let mut file_name = hash.iter().map(|byte| format!("{byte:02x}")).collect::<String>();
+        file_name.push('.');
+        file_name.push_str(Self::FILE_EXTENSION);
+        file_name
+    }
+
+    fn encode(entry: &CacheEntry) -> Vec<u8> {
+        // A zero timestamp marks entries that never expire.
+        let mut bytes = entry.expires_at.unwrap_or(0).to_le_bytes().to_vec();
+        bytes.extend_from_slice(&entry.value);
+        bytes
+    }
+
+    fn decode(mut bytes: Vec<u8>) -> Option<CacheEntry> {
+        if bytes.len() < 8 {
+            return None;
+        }
+        let value = bytes.split_off(8);
+        let expires_at = u64::from_le_bytes(bytes.try_into().ok()?);
+
+        Some(CacheEntry {
+            value,
+            expires_at: (expires_at != 0).then_some(expires_at),
+        })
+    }
+
+    async fn remove_file(&self, file_name: &str) -> Result<()> {
+        match tokio::fs::remove_file(self.directory.join(file_name)).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[async_trait]
+impl CacheBackend for FileCache {
+    async fn get(&self, key: &str) -> Result<Option<CacheEntry>> {
+        match tokio::fs::read(self.directory.join(Self::file_name(key))).await {
+            Ok(bytes) => Ok(Self::decode(bytes)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn insert(&self, key: &str, entry: CacheEntry) -> Result<()> {
+        let file_name = Self::file_name(key);
+        let bytes = Self::encode(&entry);
+        let size = bytes.len() as u64;
+
+        if size > self.max_size {
+            return self.remove(key).await;
+        }
+
+        // Write to a temporary file first, so concurrent reads never see a partially written entry.
+        let path = self.directory.join(&file_name);
+        let tmp_path = path.with_extension("tmp");
+        tokio::fs::write(&tmp_path, bytes).await?;
+        tokio::fs::rename(&tmp_path, &path).await?;
+
+        let evicted = {
+            let mut index = self.index.lock().map_err(|_| Error::PoisonError)?;
+            index.retain(|(name, _)| name != &file_name);
+            index.push_back((file_name, size));
+
+            let mut total_size = index.iter().map(|(_, size)| size).sum::<u64>();
+            let mut evicted = Vec::new();
+            while total_size > self.max_size {
+                match index.pop_front() {
+                    Some((name, size)) => {
+                        total_size -= size;
+                        evicted.push(name);
+                    }
+                    None => break,
+                }
+            }
+            evicted
+        };
+
+        for file_name in evicted {
+            self.remove_file(&file_name).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn remove(&self, key: &str) -> Result<()> {
+        let file_name = Self::file_name(key);
+        self.index
+            .lock()
+            .map_err(|_| Error::PoisonError)?
+            .retain(|(name, _)| name != &file_name);
+        self.remove_file(&file_name).await
+    }
+
+    async fn clear(&self) -> Result<()> {
+        let file_names = std::mem::take(&mut *self.index.lock().map_err(|_| Error::PoisonError)?);
+        for (file_name, _) in file_names {
+            self.remove_file(&file_name).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether a response can change after it has been returned by a node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum CachePolicy {
+    /// Responses that never change, like blocks or milestones.
+    Immutable,
+    /// Responses that can change, like output metadata once the output gets spent.
+    Mutable,
+}
+
+/// Hit and miss counters of a [`ResponseCache`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheMetrics {
+    /// Amount of responses that were returned from the cache.
+    pub hits: u64,
+    /// Amount of responses that had to be requested from a node.
+    pub misses: u64,
+}
+
+impl CacheMetrics {
+    /// Returns the share of responses that were returned from the cache, between 0 and 1.
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct CacheCounters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+/// Cache for node API responses, provided with [`ClientBuilder::with_response_cache()`](crate::ClientBuilder).
+///
+/// Used by [`Client::get_block()`], [`Client::get_milestone_by_id()`], [`Client::get_milestone_by_index()`],
+/// [`Client::get_utxo_changes_by_id()`], [`Client::get_utxo_changes_by_index()`] and [`Client::get_included_block()`],
+/// whose responses are cached until the backend evicts them, and by [`Client::get_output()`] and
+/// [`Client::get_output_metadata()`], whose responses are only cached for the mutable TTL. Responses are keyed by
+/// network name, so clients of different networks can share a cache.
+#[derive(Clone)]
+pub struct ResponseCache {
+    backend: Arc<dyn CacheBackend>,
+    mutable_ttl: Duration,
+    counters: Arc<CacheCounters>,
+}
+
+impl ResponseCache {
+    /// Creates a response cache with the given backend.
+    pub fn new(backend: impl CacheBackend + 'static) -> Self {
+        Self {
+            backend: Arc::new(backend),
+            mutable_ttl: DEFAULT_RESPONSE_CACHE_MUTABLE_TTL,
+            counters: Arc::default(),
+        }
+    }
+
+    /// Creates a response cache which keeps at most `max_size` bytes in memory.
+    pub fn in_memory(max_size: usize) -> Self {
+        Self::new(MemoryCache::new(max_size))
+    }
+
+    /// Sets for how long mutable responses, like output metadata, are cached. A zero duration disables caching them.
+    pub fn with_mutable_ttl(mut self, mutable_ttl: Duration) -> Self {
+        self.mutable_ttl = mutable_ttl;
+        self
+    }
+
+    /// Returns the hit and miss counters.
+    pub fn metrics(&self) -> CacheMetrics {
+        CacheMetrics {
+            hits: self.counters.hits.load(Ordering::Relaxed),
+            misses: self.counters.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Removes all cached responses.
+    pub async fn clear(&self) -> Result<()> {
+        self.backend.clear().await
+    }
+
+    // Returns the cached response, outdated and undecodable entries are removed.
+    pub(crate) async fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let entry = self.backend.get(key).await.unwrap_or_else(|e| {
+            log::warn!("failed to read {key} from the response cache: {e}");
+            None
+        });
+
+        let value = match entry {
+            Some(entry) => {
+                let value = if entry.is_expired(unix_timestamp_millis()) {
+                    None
+                } else {
+                    serde_json::from_slice(&entry.value).ok()
+                };
+                if value.is_none() {
+                    self.remove(key).await;
+                }
+                value
+            }
+            None => None,
+        };
+
+        if value.is_some() {
+            self.counters.hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.counters.misses.fetch_add(1, Ordering::Relaxed);
+        }
+
+        value
+    }
+
+    pub(crate) async fn remove(&self, key: &str) {
+        if let Err(e) = self.backend.remove(key).await {
+            log::warn!("failed to remove {key} from the response cache: {e}");
+        }
+    }
+
+    pub(crate) async fn insert<T: Serialize + Sync>(&self, key: &str, value: &T, policy: CachePolicy) {
+        let expires_at = match policy {
+            CachePolicy::Immutable => None,
+            CachePolicy::Mutable if self.mutable_ttl.is_zero() => return,
+            CachePolicy::Mutable => Some(
+                unix_timestamp_millis().saturating_add(self.mutable_ttl.as_millis().try_into().unwrap_or(u64::MAX)),
+            ),
+        };
+        let entry = match serde_json::to_vec(value) {
+            Ok(value) => CacheEntry { value, expires_at },
+            Err(e) => {
+                log::warn!("failed to serialize {key} for the response cache: {e}");
+                return;
+            }
+        };
+
+        if let Err(e) = self.backend.insert(key, entry).await {
+            log::warn!("failed to write {key} to the response cache: {e}");
+        }
+    }
+}
+
+impl Default for ResponseCache {
+    fn default() -> Self {
+        Self::new(MemoryCache::default())
+    }
+}
+
+impl Debug for ResponseCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResponseCache")
+            .field("mutable_ttl", &self.mutable_ttl)
+            .field("metrics", &self.metrics())
+            .finish()
+    }
+}
+
+impl PartialEq for ResponseCache {
+    fn eq(&self, other: &Self) -> bool {
+        // Only compare the data pointers, vtable pointers aren't guaranteed to be unique.
+        Arc::as_ptr(&self.backend).cast::<()>() == Arc::as_ptr(&other.backend).cast::<()>()
+            && self.mutable_ttl == other.mutable_ttl
+    }
+}
+
+impl Eq for ResponseCache {}
+
+impl Client {
+    /// Sends a GET request like `get_request()`, but returns the response from the response cache if it
+    /// has been requested before on the same network.
+    pub(crate) async fn get_cached_request<T: DeserializeOwned + Debug + Serialize + Send + Sync>(
+        &self,
+        path: &str,
+        need_quorum: bool,
+        prefer_permanode: bool,
+        policy: CachePolicy,
+    ) -> Result<T> {
+        let response_cache = self.node_manager.response_cache.as_ref();
+        let key = self.response_cache_key(path)?;
+
+        if let Some(response_cache) = response_cache {
+            if let Some(response) = response_cache.get(&key).await {
+                return Ok(response);
+            }
+        }
+
+        let response = self
+            .node_manager
+            .get_request(path, None, self.get_timeout(), need_quorum, prefer_permanode)
+            .await?;
+
+        if let Some(response_cache) = response_cache {
+            response_cache.insert(&key, &response, policy).await;
+        }
+
+        Ok(response)
+    }
+
+    /// Sends a GET request like `get_cached_request()`, but only returns and caches responses that pass `verify`,
+    /// which also converts them. Cached responses that don't pass it are evicted and requested again.
+    pub(crate) async fn get_verified_cached_request<T: DeserializeOwned + Debug + Serialize + Send + Sync, U>(
+        &self,
+        path: &str,
+        need_quorum: bool,
+        prefer_permanode: bool,
+        policy: CachePolicy,
+        verify: impl Fn(&T) -> Result<U> + Send + Sync,
+    ) -> Result<U> {
+        let response_cache = self.node_manager.response_cache.as_ref();
+        let key = self.response_cache_key(path)?;
+
+        if let Some(response_cache) = response_cache {
+            if let Some(response) = response_cache.get(&key).await {
+                match verify(&response) {
+                    Ok(verified) => return Ok(verified),
+                    Err(e) => {
+                        log::warn!("evicting {key} from the response cache, it failed verification: {e}");
+                        response_cache.remove(&key).await;
+                    }
+                }
+            }
+        }
+
+        let response = self
+            .node_manager
+            .get_request(path, None, self.get_timeout(), need_quorum, prefer_permanode)
+            .await?;
+        let verified = verify(&response)?;
+
+        if let Some(response_cache) = response_cache {
+            response_cache.insert(&key, &response, policy).await;
+        }
+
+        Ok(verified)
+    }
+
+    // Cached responses are keyed by network name and path, so a cache shared between networks never mixes them up.
+    fn response_cache_key(&self, path: &str) -> Result<String> {
+        let network_info = self.network_info.read().map_err(|_| Error::PoisonError)?;
+        Ok(format!("{}/{path}", network_info.protocol_parameters.network_name()))
+    }
+
+    /// Returns the hit and miss counters of the response cache, if one is set.
+    pub fn response_cache_metrics(&self) -> Option<CacheMetrics> {
+        self.node_manager.response_cache.as_ref().map(ResponseCache::metrics)
+    }
+
+    /// Removes all cached responses, if a response cache is set.
+    pub async fn clear_response_cache(&self) -> Result<()> {
+        if let Some(response_cache) = &self.node_manager.response_cache {
+            response_cache.clear().await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(size: usize) -> CacheEntry {
+        CacheEntry {
+            value: vec![0; size],
+            expires_at: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn memory_cache_evicts_least_recently_used() {
+        let cache = MemoryCache::new(10);
+        cache.insert("a", entry(4)).await.unwrap();
+        cache.insert("b", entry(4)).await.unwrap();
+        // Reading "a" makes "b" the least recently used entry.
+        assert!(cache.get("a").await.unwrap().is_some());
+        cache.insert("c", entry(4)).await.unwrap();
+
+        assert!(cache.get("a").await.unwrap().is_some());
+        assert!(cache.get("b").await.unwrap().is_none());
+        assert!(cache.get("c").await.unwrap().is_some());
+
+        // Entries bigger than the whole cache are not stored.
+        cache.insert("d", entry(11)).await.unwrap();
+        assert!(cache.get("d").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn response_cache_ttl() {
+        let response_cache = ResponseCache::in_memory(1024).with_mutable_ttl(Duration::ZERO);
+
+        response_cache.insert("immutable", &1u32, CachePolicy::Immutable).await;
+        response_cache.insert("mutable", &2u32, CachePolicy::Mutable).await;
+        assert_eq!(response_cache.get::<u32>("immutable").await, Some(1));
+        assert_eq!(response_cache.get::<u32>("mutable").await, None);
+
+        // Outdated entries are misses and get removed.
+        response_cache
+            .backend
+            .insert(
+                "expired",
+                CacheEntry {
+                    value: b"3".to_vec(),
+                    expires_at: Some(unix_timestamp_millis() - 1),
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(response_cache.get::<u32>("expired").await, None);
+        assert!(response_cache.backend.get("expired").await.unwrap().is_none());
+
+        assert_eq!(response_cache.metrics(), CacheMetrics { hits: 1, misses: 2 });
+    }
+
+    #[tokio::test]
+    async fn file_cache() {
+        let directory = std::env::temp_dir().join(format!("iota-client-file-cache-{}", unix_timestamp_millis()));
+        // Every file holds an 8 byte expiration timestamp and the value.
+        let cache = FileCache::open(&directory, 40).unwrap();
+        let expiring_entry = CacheEntry {
+            value: vec![1; 10],
+            expires_at: Some(5),
+        };
+        cache.insert("a", expiring_entry.clone()).await.unwrap();
+        cache.insert("b", entry(10)).await.unwrap();
+        assert_eq!(cache.get("a").await.unwrap(), Some(expiring_entry));
+
+        // The oldest entry is evicted first.
+        cache.insert("c", entry(10)).await.unwrap();
+        assert!(cache.get("a").await.unwrap().is_none());
+
+        // Entries are kept when the cache is opened again.
+        let cache = FileCache::open(&directory, 40).unwrap();
+        assert_eq!(cache.get("b").await.unwrap(), Some(entry(10)));
+        assert_eq!(cache.get("c").await.unwrap(), Some(entry(10)));
+
+        cache.clear().await.unwrap();
+        assert!(cache.get("c").await.unwrap().is_none());
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -1,113 +1,258 @@
 // Copyright 2021 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-//! The node manager that takes care of sending requests with healthy nodes and quorum if enabled
+//! The HTTP transport used by the node manager to send requests to nodes
 
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
 
-use reqwest::RequestBuilder;
+use async_trait::async_trait;
 use serde::de::DeserializeOwned;
 use serde_json::Value;
+use url::Url;
 
 use crate::{
     error::{Error, Result},
     node_manager::node::Node,
 };
-pub(crate) struct Response(reqwest::Response);
+
+/// HTTP method of a [`HttpRequest`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HttpMethod {
+    /// GET request.
+    Get,
+    /// POST request.
+    Post,
+    /// DELETE request.
+    Delete,
+}
+
+/// A request that the node manager wants to send to a node.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HttpRequest {
+    /// The HTTP method.
+    pub method: HttpMethod,
+    /// The full url, including path, query and basic auth credentials if set.
+    pub url: Url,
+    /// The headers of the request, e.g. `User-Agent`, `Authorization` or `Accept`.
+    pub headers: Vec<(String, String)>,
+    /// The body of the request.
+    pub body: Option<Vec<u8>>,
+    /// The request timeout. Transports that don't support timeouts can ignore it.
+    pub timeout: Duration,
+}
+
+impl HttpRequest {
+    /// Returns the value of the first header with the given name, compared case-insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// A response returned by a [`HttpTransport`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HttpResponse {
+    /// The HTTP status code.
+    pub status: u16,
+    /// The raw body of the response.
+    pub body: Vec<u8>,
+}
+
+/// The transport that is used to send HTTP requests to nodes.
+///
+/// Implement this trait to add a caching proxy, use custom TLS roots, record traffic or to answer requests from an
+/// in-process mock, and provide it with [`ClientBuilder::with_http_transport()`](crate::ClientBuilder).
+/// Responses with non-success status codes must be returned as [`HttpResponse`] and not as error, the node manager
+/// takes care of mapping them.
+#[cfg_attr(target_family = "wasm", async_trait(?Send))]
+#[cfg_attr(not(target_family = "wasm"), async_trait)]
+pub trait HttpTransport: Send + Sync {
+    /// Sends a request and returns the response of the node.
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse>;
+}
+
+/// The default [`HttpTransport`], backed by [`reqwest`].
+#[derive(Clone, Debug, Default)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+impl ReqwestTransport {
+    /// Creates a new transport with a default [`reqwest::Client`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new transport from an already configured [`reqwest::Client`], e.g. with custom TLS roots.
+    pub fn with_client(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+#[cfg_attr(target_family = "wasm", async_trait(?Send))]
+#[cfg_attr(not(target_family = "wasm"), async_trait)]
+impl HttpTransport for ReqwestTransport {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+        let mut request_builder = match request.method {
+            HttpMethod::Get => self.client.get(request.url),
+            HttpMethod::Post => self.client.post(request.url),
+            HttpMethod::Delete => self.client.delete(request.url),
+        };
+        for (name, value) in &request.headers {
+            request_builder = request_builder.header(name, value);
+        }
+        if let Some(body) = request.body {
+            request_builder = request_builder.body(body);
+        }
+        #[cfg(not(target_family = "wasm"))]
+        {
+            request_builder = request_builder.timeout(request.timeout);
+        }
+
+        let response = request_builder.send().await?;
+
+        Ok(HttpResponse {
+            status: response.status().as_u16(),
+            body: response.bytes().await?.to_vec(),
+        })
+    }
+}
+
+/// A custom [`HttpTransport`] that can be stored in the serializable builders.
+#[derive(Clone)]
+pub(crate) struct SharedHttpTransport(pub(crate) Arc<dyn HttpTransport>);
+
+impl std::fmt::Debug for SharedHttpTransport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("SharedHttpTransport").field(&"...").finish()
+    }
+}
+
+impl PartialEq for SharedHttpTransport {
+    fn eq(&self, other: &Self) -> bool {
+        // Only compare the data pointers, vtable pointers aren't guaranteed to be unique.
+        Arc::as_ptr(&self.0).cast::<()>() == Arc::as_ptr(&other.0).cast::<()>()
+    }
+}
+
+impl Eq for SharedHttpTransport {}
+
+pub(crate) struct Response(HttpResponse);
 
 impl Response {
     pub(crate) fn status(&self) -> u16 {
-        self.0.status().as_u16()
+        self.0.status
     }
 
-    pub(crate) async fn into_json<T: DeserializeOwned>(self) -> Result<T> {
-        self.0.json().await.map_err(Into::into)
+    pub(crate) fn into_json<T: DeserializeOwned>(self) -> Result<T> {
+        serde_json::from_slice(&self.0.body).map_err(Into::into)
     }
 
-    pub(crate) async fn into_text(self) -> Result<String> {
-        self.0.text().await.map_err(Into::into)
+    pub(crate) fn into_text(self) -> Result<String> {
+        String::from_utf8(self.0.body).map_err(|_| Error::Node("non UTF8 node response".into()))
     }
 
-    pub(crate) async fn into_bytes(self) -> Result<Vec<u8>> {
-        self.0.bytes().await.map(|b| b.to_vec()).map_err(Into::into)
+    pub(crate) fn into_bytes(self) -> Vec<u8> {
+        self.0.body
     }
 }
 
 #[derive(Clone)]
 pub(crate) struct HttpClient {
-    client: reqwest::Client,
+    transport: Arc<dyn HttpTransport>,
     user_agent: String,
 }
 
 impl HttpClient {
     pub(crate) fn new(user_agent: String) -> Self {
-        Self {
-            client: reqwest::Client::new(),
-            user_agent,
-        }
+        Self::with_transport(Arc::new(ReqwestTransport::new()), user_agent)
     }
 
-    async fn parse_response(response: reqwest::Response, url: &url::Url) -> Result<Response> {
-        let status = response.status();
-        if status.is_success() {
+    pub(crate) fn with_transport(transport: Arc<dyn HttpTransport>, user_agent: String) -> Self {
+        Self { transport, user_agent }
+    }
+
+    fn parse_response(response: HttpResponse, url: &Url) -> Result<Response> {
+        if (200..300).contains(&response.status) {
             Ok(Response(response))
         } else {
             Err(Error::ResponseError {
-                code: status.as_u16(),
-                text: response.text().await?,
+                code: response.status,
+                text: String::from_utf8_lossy(&response.body).into_owned(),
                 url: url.to_string(),
             })
         }
     }
 
-    fn build_request(&self, request_builder: RequestBuilder, node: &Node, _timeout: Duration) -> RequestBuilder {
-        let mut request_builder = request_builder.header(reqwest::header::USER_AGENT, &self.user_agent);
+    fn build_request(&self, method: HttpMethod, node: &Node, timeout: Duration) -> HttpRequest {
+        let mut headers = vec![("User-Agent".to_string(), self.user_agent.clone())];
 
         if let Some(node_auth) = &node.auth {
             if let Some(jwt) = &node_auth.jwt {
-                request_builder = request_builder.bearer_auth(jwt);
+                headers.push(("Authorization".to_string(), format!("Bearer {jwt}")));
             }
         }
-        #[cfg(not(target_family = "wasm"))]
-        {
-            request_builder = request_builder.timeout(_timeout);
+
+        HttpRequest {
+            method,
+            url: node.url.clone(),
+            headers,
+            body: None,
+            timeout,
         }
-        request_builder
+    }
+
+    async fn send(&self, request: HttpRequest) -> Result<Response> {
+        let url = request.url.clone();
+        let response = self.transport.send(request).await?;
+        Self::parse_response(response, &url)
     }
 
     pub(crate) async fn get(&self, node: Node, timeout: Duration) -> Result<Response> {
-        let mut request_builder = self.client.get(node.url.clone());
-        request_builder = self.build_request(request_builder, &node, timeout);
+        let request = self.build_request(HttpMethod::Get, &node, timeout);
         let start_time = instant::Instant::now();
-        let resp = request_builder.send().await?;
+        let resp = self.transport.send(request).await?;
         log::debug!(
             "GET: {:?} ms for {} {}",
             start_time.elapsed().as_millis(),
-            resp.status(),
+            resp.status,
             node.url
         );
-        Self::parse_response(resp, &node.url).await
+        Self::parse_response(resp, &node.url)
     }
 
     // Get with header: "accept", "application/vnd.iota.serializer-v1"
     pub(crate) async fn get_bytes(&self, node: Node, timeout: Duration) -> Result<Response> {
-        let mut request_builder = self.client.get(node.url.clone());
-        request_builder = self.build_request(request_builder, &node, timeout);
-        request_builder = request_builder.header("accept", "application/vnd.iota.serializer-v1");
-        let resp = request_builder.send().await?;
-        Self::parse_response(resp, &node.url).await
+        let mut request = self.build_request(HttpMethod::Get, &node, timeout);
+        request
+            .headers
+            .push(("Accept".to_string(), "application/vnd.iota.serializer-v1".to_string()));
+        self.send(request).await
     }
 
     pub(crate) async fn post_json(&self, node: Node, timeout: Duration, json: Value) -> Result<Response> {
-        let mut request_builder = self.client.post(node.url.clone());
-        request_builder = self.build_request(request_builder, &node, timeout);
-        Self::parse_response(request_builder.json(&json).send().await?, &node.url).await
+        let mut request = self.build_request(HttpMethod::Post, &node, timeout);
+        request
+            .headers
+            .push(("Content-Type".to_string(), "application/json".to_string()));
+        request.body = Some(serde_json::to_vec(&json)?);
+        self.send(request).await
     }
 
     pub(crate) async fn post_bytes(&self, node: Node, timeout: Duration, body: &[u8]) -> Result<Response> {
-        let mut request_builder = self.client.post(node.url.clone());
-        request_builder = self.build_request(request_builder, &node, timeout);
-        request_builder = request_builder.header("Content-Type", "application/vnd.iota.serializer-v1");
-        Self::parse_response(request_builder.body(body.to_vec()).send().await?, &node.url).await
+        let mut request = self.build_request(HttpMethod::Post, &node, timeout);
+        request.headers.push((
+            "Content-Type".to_string(),
+            "application/vnd.iota.serializer-v1".to_string(),
+        ));
+        request.body = Some(body.to_vec());
+        self.send(request).await
+    }
+
+    pub(crate) async fn delete(&self, node: Node, timeout: Duration) -> Result<Response> {
+        let request = self.build_request(HttpMethod::Delete, &node, timeout);
+        self.send(request).await
     }
 }
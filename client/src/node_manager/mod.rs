@@ -4,9 +4,14 @@
 //! The node manager that takes care of sending requests with healthy nodes and quorum if enabled
 
 pub mod builder;
-pub(crate) mod http_client;
+pub mod cache;
+/// The HTTP transport used to send requests to nodes
+pub mod http_client;
 /// Structs for nodes
 pub mod node;
+pub mod quorum;
+pub mod retry;
+pub mod scoring;
 pub(crate) mod syncing;
 
 use std::{
@@ -18,7 +23,13 @@ use std::{
 use iota_types::api::core::response::InfoResponse;
 use serde_json::Value;
 
-use self::{http_client::HttpClient, node::Node};
+use self::{
+    cache::ResponseCache,
+    http_client::{HttpClient, HttpMethod, Response},
+    node::Node,
+    retry::RetryPolicy,
+    scoring::NodeScores,
+};
 use crate::{
     error::{Error, Result},
     node_manager::builder::NodeManagerBuilder,
@@ -38,7 +49,10 @@ pub(crate) struct NodeManager {
     quorum: bool,
     min_quorum_size: usize,
     quorum_threshold: usize,
+    retry_policy: RetryPolicy,
+    pub(crate) response_cache: Option<ResponseCache>,
     pub(crate) http_client: HttpClient,
+    pub(crate) node_scores: NodeScores,
 }
 
 impl std::fmt::Debug for NodeManager {
@@ -53,7 +67,10 @@ impl std::fmt::Debug for NodeManager {
         d.field("healthy_nodes", &self.healthy_nodes);
         d.field("quorum", &self.quorum);
         d.field("min_quorum_size", &self.min_quorum_size);
-        d.field("quorum_threshold", &self.quorum_threshold).finish()
+        d.field("quorum_threshold", &self.quorum_threshold);
+        d.field("retry_policy", &self.retry_policy);
+        d.field("response_cache", &self.response_cache);
+        d.field("node_scores", &self.node_scores).finish()
     }
 }
 
@@ -95,8 +112,8 @@ impl NodeManager {
             }
         }
 
-        // Add other nodes in random order, so they are not always used in the same order
-        let nodes_random_order = if !self.ignore_node_health {
+        // Add other nodes, ordered by their score below
+        let other_nodes = if !self.ignore_node_health {
             #[cfg(not(target_family = "wasm"))]
             {
                 self.healthy_nodes
@@ -127,16 +144,20 @@ impl NodeManager {
             self.nodes.clone()
         };
 
-        // Add remaining nodes in random order
-        for node in nodes_random_order {
-            if !nodes_with_modified_url.iter().any(|n| n.url == node.url) {
-                nodes_with_modified_url.push(node);
-            }
-        }
+        // Add remaining nodes, the ones with the best score first
+        let mut remaining_nodes: Vec<Node> = other_nodes
+            .into_iter()
+            .filter(|node| !nodes_with_modified_url.iter().any(|n| n.url == node.url))
+            .collect();
+        self.node_scores.sort_by_score(&mut remaining_nodes);
+        nodes_with_modified_url.extend(remaining_nodes);
 
         // remove disabled nodes
         nodes_with_modified_url.retain(|n| !n.disabled);
 
+        // Nodes that failed repeatedly are only used if all others failed too
+        self.node_scores.move_benched_to_end(&mut nodes_with_modified_url);
+
         if nodes_with_modified_url.is_empty() {
             return Err(crate::Error::HealthyNodePoolEmpty);
         }
@@ -168,161 +189,125 @@ impl NodeManager {
         need_quorum: bool,
         prefer_permanode: bool,
     ) -> Result<T> {
-        let mut result: HashMap<String, usize> = HashMap::new();
+        self.retry_policy
+            .retry(HttpMethod::Get, path, || {
+                self.get_request_once(path, query, timeout, need_quorum, prefer_permanode)
+            })
+            .await
+    }
+
+    async fn get_request_once<T: serde::de::DeserializeOwned + std::fmt::Debug + serde::Serialize>(
+        &self,
+        path: &str,
+        query: Option<&str>,
+        timeout: Duration,
+        need_quorum: bool,
+        prefer_permanode: bool,
+    ) -> Result<T> {
         // primary_pow_node should only be used for post request with remote PoW
         // Get node urls and set path
         let nodes = self.get_nodes(path, query, false, prefer_permanode)?;
-        if self.quorum && need_quorum && nodes.len() < self.min_quorum_size {
-            return Err(Error::QuorumPoolSizeError {
-                available_nodes: nodes.len(),
-                minimum_threshold: self.min_quorum_size,
-            });
+
+        if self.quorum && need_quorum {
+            let body = self.get_quorum_response(&nodes, timeout, false).await?;
+            return Ok(serde_json::from_slice(&body)?);
         }
 
-        // Track amount of results for quorum
-        let mut result_counter = 0;
         let mut error = None;
-        // Send requests parallel for quorum
-        #[cfg(target_family = "wasm")]
-        let wasm = true;
-        #[cfg(not(target_family = "wasm"))]
-        let wasm = false;
-        if !wasm && self.quorum && need_quorum && query.is_none() {
-            #[cfg(not(target_family = "wasm"))]
-            {
-                let mut tasks = Vec::new();
-                for (index, node) in nodes.into_iter().enumerate() {
-                    if index < self.min_quorum_size {
-                        let client_ = self.http_client.clone();
-                        tasks.push(async move { tokio::spawn(async move { client_.get(node, timeout).await }).await });
-                    }
-                }
-                for res in futures::future::try_join_all(tasks).await? {
-                    match res {
-                        Ok(res) => (res.into_text().await).map_or_else(
-                            |_| {
-                                log::warn!("couldn't convert node response to text");
-                            },
-                            |res_text| {
-                                let counters = result.entry(res_text).or_insert(0);
-                                *counters += 1;
-                                result_counter += 1;
-                            },
-                        ),
-                        Err(Error::ResponseError { code: 404, url, .. }) => {
-                            error.replace(crate::Error::NotFound(url));
-                        }
-                        Err(err) => {
-                            error.replace(err);
-                        }
-                    }
-                }
-            }
-        } else {
-            // Send requests
-            for node in nodes {
-                match self.http_client.get(node.clone(), timeout).await {
-                    Ok(res) => {
-                        match res.status() {
-                            200 => {
-                                // Handle node_info extra because we also want to return the url
-                                if path == "api/core/v2/info" {
-                                    let node_info: InfoResponse = res.into_json().await?;
-                                    let wrapper = crate::node_api::core::routes::NodeInfoWrapper {
-                                        node_info,
-                                        url: format!("{}://{}", node.url.scheme(), node.url.host_str().unwrap_or("")),
-                                    };
-                                    let serde_res = serde_json::to_string(&wrapper)?;
-                                    return Ok(serde_json::from_str(&serde_res)?);
-                                }
+        // Send requests, without quorum it's enough if we got one response
+        for node in nodes {
+            let start_time = instant::Instant::now();
+            let res = self.http_client.get(node.clone(), timeout).await;
+            self.node_scores.record(&node.url, start_time, &res);
+            match res {
+                Ok(res) => {
+                    match res.status() {
+                        200 => {
+                            // Handle node_info extra because we also want to return the url
+                            if path == "api/core/v2/info" {
+                                let node_info: InfoResponse = res.into_json()?;
+                                let wrapper = crate::node_api::core::routes::NodeInfoWrapper {
+                                    node_info,
+                                    url: format!("{}://{}", node.url.scheme(), node.url.host_str().unwrap_or("")),
+                                };
+                                let serde_res = serde_json::to_string(&wrapper)?;
+                                return Ok(serde_json::from_str(&serde_res)?);
+                            }
 
-                                match res.into_json::<T>().await {
-                                    Ok(result_data) => {
-                                        let counters = result.entry(serde_json::to_string(&result_data)?).or_insert(0);
-                                        *counters += 1;
-                                        result_counter += 1;
-                                        // Without quorum it's enough if we got one response
-                                        if !self.quorum
-                                            || result_counter >= self.min_quorum_size
-                                            || !need_quorum
-                                            // with query we ignore quorum because the nodes can store a different amount of history
-                                            || query.is_some()
-                                        {
-                                            break;
-                                        }
-                                    }
-                                    Err(e) => {
-                                        error.replace(e);
-                                    }
+                            match res.into_json::<T>() {
+                                Ok(result_data) => return Ok(result_data),
+                                Err(e) => {
+                                    error.replace(e);
                                 }
                             }
+                        }
 
-                            _ => {
-                                error.replace(crate::Error::Node(
-                                    res.into_text()
-                                        .await
-                                        .unwrap_or_else(|_| "couldn't convert node response into text".to_string()),
-                                ));
-                            }
+                        _ => {
+                            error.replace(crate::Error::Node(
+                                res.into_text()
+                                    .unwrap_or_else(|_| "couldn't convert node response into text".to_string()),
+                            ));
                         }
                     }
-                    Err(Error::ResponseError { code: 404, url, .. }) => {
-                        error.replace(crate::Error::NotFound(url));
-                    }
-                    Err(err) => {
-                        error.replace(err);
-                    }
+                }
+                Err(Error::ResponseError { code: 404, url, .. }) => {
+                    error.replace(crate::Error::NotFound(url));
+                }
+                Err(err) => {
+                    error.replace(err);
                 }
             }
         }
 
-        let res = result
-            .into_iter()
-            .max_by_key(|v| v.1)
-            .ok_or_else(|| error.unwrap_or_else(|| Error::Node("couldn't get a result from any node".into())))?;
-
-        // Return if quorum is false or check if quorum was reached
-        if !self.quorum
-            || res.1 as f64 >= self.min_quorum_size as f64 * (self.quorum_threshold as f64 / 100.0)
-            || !need_quorum
-            // with query we ignore quorum because the nodes can store a different amount of history
-            || query.is_some()
-        {
-            Ok(serde_json::from_str(&res.0)?)
-        } else {
-            Err(Error::QuorumThresholdError {
-                quorum_size: res.1,
-                minimum_threshold: self.min_quorum_size,
-            })
-        }
+        Err(error.unwrap_or_else(|| Error::Node("couldn't get a result from any node".into())))
     }
 
-    // Only used for api/core/v2/blocks/{blockID}, that's why we don't need the quorum stuff
+    // Used for the raw routes, quorum responses have to be byte-identical
     pub(crate) async fn get_request_bytes(
         &self,
         path: &str,
         query: Option<&str>,
         timeout: Duration,
+        need_quorum: bool,
+    ) -> Result<Vec<u8>> {
+        self.retry_policy
+            .retry(HttpMethod::Get, path, || {
+                self.get_request_bytes_once(path, query, timeout, need_quorum)
+            })
+            .await
+    }
+
+    async fn get_request_bytes_once(
+        &self,
+        path: &str,
+        query: Option<&str>,
+        timeout: Duration,
+        need_quorum: bool,
     ) -> Result<Vec<u8>> {
         // primary_pow_node should only be used for post request with remote Pow
         // Get node urls and set path
         let nodes = self.get_nodes(path, query, false, false)?;
+
+        if self.quorum && need_quorum {
+            return self.get_quorum_response(&nodes, timeout, true).await;
+        }
+
         let mut error = None;
         // Send requests
         for node in nodes {
-            match self.http_client.get_bytes(node, timeout).await {
+            let start_time = instant::Instant::now();
+            let res = self.http_client.get_bytes(node.clone(), timeout).await;
+            self.node_scores.record(&node.url, start_time, &res);
+            match res {
                 Ok(res) => {
                     let status = res.status();
-                    if let Ok(res_text) = res.into_bytes().await {
-                        // Without quorum it's enough if we got one response
-                        match status {
-                            200 => return Ok(res_text),
-                            _ => error.replace(crate::Error::Node(
-                                String::from_utf8(res_text)
-                                    .map_err(|_| Error::Node("non UTF8 node response".into()))?,
-                            )),
-                        };
-                    }
+                    let res_bytes = res.into_bytes();
+                    match status {
+                        200 => return Ok(res_bytes),
+                        _ => error.replace(crate::Error::Node(
+                            String::from_utf8(res_bytes).map_err(|_| Error::Node("non UTF8 node response".into()))?,
+                        )),
+                    };
                 }
                 Err(Error::ResponseError { code: 404, url, .. }) => {
                     error.replace(crate::Error::NotFound(url));
@@ -341,6 +326,21 @@ impl NodeManager {
         timeout: Duration,
         body: &[u8],
         local_pow: bool,
+    ) -> Result<T> {
+        self.retry_policy
+            .retry(HttpMethod::Post, path, || {
+                self.post_request_bytes_once(path, timeout, body, local_pow)
+            })
+            .await
+            .map_err(into_node_error)
+    }
+
+    async fn post_request_bytes_once<T: serde::de::DeserializeOwned>(
+        &self,
+        path: &str,
+        timeout: Duration,
+        body: &[u8],
+        local_pow: bool,
     ) -> Result<T> {
         // primary_pow_node should only be used for post request with remote PoW
         let nodes = self.get_nodes(path, None, !local_pow, false)?;
@@ -350,22 +350,24 @@ impl NodeManager {
         let mut error = None;
         // Send requests
         for node in nodes {
-            match self.http_client.post_bytes(node, timeout, body).await {
+            let start_time = instant::Instant::now();
+            let res = self.http_client.post_bytes(node.clone(), timeout, body).await;
+            self.node_scores.record(&node.url, start_time, &res);
+            match res {
                 Ok(res) => {
                     match res.status() {
-                        200 | 201 => match res.into_json::<T>().await {
+                        200 | 201 => match res.into_json::<T>() {
                             Ok(res) => return Ok(res),
                             Err(e) => error.replace(e),
                         },
                         _ => error.replace(crate::Error::Node(
                             res.into_text()
-                                .await
                                 .unwrap_or_else(|_| "couldn't convert node response into text".to_string()),
                         )),
                     };
                 }
                 Err(e) => {
-                    error.replace(crate::Error::Node(e.to_string()));
+                    error.replace(e);
                 }
             }
         }
@@ -378,6 +380,21 @@ impl NodeManager {
         timeout: Duration,
         json: Value,
         local_pow: bool,
+    ) -> Result<T> {
+        self.retry_policy
+            .retry(HttpMethod::Post, path, || {
+                self.post_request_json_once(path, timeout, &json, local_pow)
+            })
+            .await
+            .map_err(into_node_error)
+    }
+
+    async fn post_request_json_once<T: serde::de::DeserializeOwned>(
+        &self,
+        path: &str,
+        timeout: Duration,
+        json: &Value,
+        local_pow: bool,
     ) -> Result<T> {
         // primary_pow_node should only be used for post request with remote PoW
         let nodes = self.get_nodes(path, None, !local_pow, false)?;
@@ -387,25 +404,82 @@ impl NodeManager {
         let mut error = None;
         // Send requests
         for node in nodes {
-            match self.http_client.post_json(node, timeout, json.clone()).await {
+            let start_time = instant::Instant::now();
+            let res = self.http_client.post_json(node.clone(), timeout, json.clone()).await;
+            self.node_scores.record(&node.url, start_time, &res);
+            match res {
                 Ok(res) => {
                     match res.status() {
-                        200 | 201 => match res.into_json::<T>().await {
+                        200 | 201 => match res.into_json::<T>() {
                             Ok(res) => return Ok(res),
                             Err(e) => error.replace(e),
                         },
                         _ => error.replace(crate::Error::Node(
                             res.into_text()
-                                .await
                                 .unwrap_or_else(|_| "couldn't convert node response into text".to_string()),
                         )),
                     };
                 }
                 Err(e) => {
-                    error.replace(crate::Error::Node(e.to_string()));
+                    error.replace(e);
                 }
             }
         }
         Err(error.unwrap_or_else(|| Error::Node("couldn't get a result from any node".into())))
     }
+
+    /// Sends a request to a protected route, like the peers or control routes, which the node only answers with a
+    /// valid JWT, provided with the [`NodeAuth`](node::NodeAuth) of the node. These routes manage a single node, so the
+    /// request is only sent to the primary node or, if none is set, to the best scored node with a JWT, without falling
+    /// back to other nodes. Fails with [`Error::NoProtectedRouteNode`] if there's no such node.
+    pub(crate) async fn protected_request(
+        &self,
+        method: HttpMethod,
+        path: &str,
+        timeout: Duration,
+        json: Option<Value>,
+    ) -> Result<Response> {
+        // The primary node is looked up by its URL, as benched nodes are moved to the end.
+        let node = &self
+            .get_nodes(path, None, false, false)?
+            .into_iter()
+            .find(|node| {
+                self.primary_node.as_ref().map_or_else(
+                    || node.auth.as_ref().map_or(false, |auth| auth.jwt.is_some()),
+                    |primary_node| node.url.origin() == primary_node.url.origin(),
+                )
+            })
+            .ok_or_else(|| Error::NoProtectedRouteNode(path.to_string()))?;
+        let json = &json;
+
+        self.retry_policy
+            .retry(method, path, || async move {
+                let start_time = instant::Instant::now();
+                let res = match method {
+                    HttpMethod::Get => self.http_client.get(node.clone(), timeout).await,
+                    HttpMethod::Post => {
+                        self.http_client
+                            .post_json(node.clone(), timeout, json.clone().unwrap_or(Value::Null))
+                            .await
+                    }
+                    HttpMethod::Delete => self.http_client.delete(node.clone(), timeout).await,
+                };
+                self.node_scores.record(&node.url, start_time, &res);
+
+                match res {
+                    Err(Error::ResponseError { code: 404, url, .. }) => Err(Error::NotFound(url)),
+                    res => res,
+                }
+            })
+            .await
+    }
+}
+
+// Failed POST requests are returned as `Error::Node`, the fallback to local PoW depends on the error message of the
+// node.
+fn into_node_error(error: Error) -> Error {
+    match error {
+        Error::ResponseError { .. } | Error::Reqwest(_) => Error::Node(error.to_string()),
+        _ => error,
+    }
 }
@@ -0,0 +1,215 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Comparison of the responses of multiple nodes when quorum is enabled
+
+use std::{collections::HashMap, fmt, time::Duration};
+
+use crypto::hashes::{blake2b::Blake2b256, Digest};
+use serde_json::Value;
+use url::Url;
+
+use crate::{
+    constants::{QUORUM_LEDGER_INDEX_RETRY_INTERVAL, QUORUM_LEDGER_INDEX_ROUNDS},
+    error::{Error, Result},
+    node_manager::{http_client::Response, node::Node, NodeManager},
+};
+
+/// The response of a single node to a request that didn't reach quorum.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuorumResponse {
+    /// The url of the request, without credentials.
+    pub url: String,
+    /// The ledger index of the response, for indexer responses.
+    pub ledger_index: Option<u32>,
+    /// The response as text, or prefix hex encoded for raw routes.
+    pub response: String,
+}
+
+impl fmt::Display for QuorumResponse {
+    // Only a hash of the response is shown, to keep error messages short.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let hash = Blake2b256::digest(self.response.as_bytes());
+        write!(
+            f,
+            "{}: response of length {} with hash {}",
+            self.url,
+            self.response.len(),
+            prefix_hex::encode(hash[..8].to_vec())
+        )?;
+        if let Some(ledger_index) = self.ledger_index {
+            write!(f, " at ledger index {ledger_index}")?;
+        }
+        Ok(())
+    }
+}
+
+// A successful response of a node.
+struct NodeResponse {
+    url: Url,
+    body: Vec<u8>,
+}
+
+impl NodeResponse {
+    fn ledger_index(&self) -> Option<u32> {
+        serde_json::from_slice::<Value>(&self.body)
+            .ok()?
+            .get("ledgerIndex")?
+            .as_u64()
+            .and_then(|ledger_index| u32::try_from(ledger_index).ok())
+    }
+
+    fn into_quorum_response(self, raw: bool) -> QuorumResponse {
+        let ledger_index = if raw { None } else { self.ledger_index() };
+        let mut url = self.url;
+        url.set_username("").ok();
+        url.set_password(None).ok();
+
+        QuorumResponse {
+            url: url.to_string(),
+            ledger_index,
+            response: if raw {
+                prefix_hex::encode(self.body)
+            } else {
+                String::from_utf8_lossy(&self.body).into_owned()
+            },
+        }
+    }
+}
+
+impl NodeManager {
+    /// Sends a GET request to `min_quorum_size` nodes and returns the body that at least `quorum_threshold`% of them
+    /// returned. Raw responses have to be byte-identical. Indexer responses are only compared if they are pinned to the
+    /// same `ledgerIndex`, otherwise the request is repeated a few times to give lagging nodes the chance to catch up.
+    pub(crate) async fn get_quorum_response(&self, nodes: &[Node], timeout: Duration, raw: bool) -> Result<Vec<u8>> {
+        if nodes.len() < self.min_quorum_size {
+            return Err(Error::QuorumPoolSizeError {
+                available_nodes: nodes.len(),
+                minimum_threshold: self.min_quorum_size,
+            });
+        }
+
+        let mut round = 1;
+
+        loop {
+            let (responses, error) = self.get_quorum_round(nodes, timeout, raw).await?;
+
+            let mut counters: HashMap<&[u8], usize> = HashMap::new();
+            for response in &responses {
+                *counters.entry(response.body.as_slice()).or_insert(0) += 1;
+            }
+            let (body, quorum_size) = counters
+                .iter()
+                .max_by_key(|(_, count)| **count)
+                .map(|(body, count)| (body.to_vec(), *count))
+                .ok_or_else(|| error.unwrap_or_else(|| Error::Node("couldn't get a result from any node".into())))?;
+
+            if quorum_size as f64 >= self.min_quorum_size as f64 * (self.quorum_threshold as f64 / 100.0) {
+                return Ok(body);
+            }
+
+            // All nodes agree, but not enough of them answered.
+            if counters.len() == 1 {
+                return Err(Error::QuorumThresholdError {
+                    quorum_size,
+                    minimum_threshold: self.min_quorum_size,
+                });
+            }
+
+            let mut ledger_indexes = responses.iter().map(NodeResponse::ledger_index).collect::<Vec<_>>();
+            ledger_indexes.sort_unstable();
+            ledger_indexes.dedup();
+            let pinned = raw || ledger_indexes.len() == 1;
+
+            if pinned || round >= QUORUM_LEDGER_INDEX_ROUNDS {
+                return Err(Error::QuorumMismatch {
+                    quorum_size,
+                    minimum_threshold: self.min_quorum_size,
+                    responses: responses
+                        .into_iter()
+                        .map(|response| response.into_quorum_response(raw))
+                        .collect(),
+                });
+            }
+
+            log::debug!("nodes returned different ledger indexes {ledger_indexes:?}, repeating quorum request");
+            round += 1;
+
+            #[cfg(not(target_family = "wasm"))]
+            tokio::time::sleep(QUORUM_LEDGER_INDEX_RETRY_INTERVAL).await;
+            #[cfg(target_family = "wasm")]
+            gloo_timers::future::TimeoutFuture::new(
+                QUORUM_LEDGER_INDEX_RETRY_INTERVAL
+                    .as_millis()
+                    .try_into()
+                    .unwrap_or(u32::MAX),
+            )
+            .await;
+        }
+    }
+
+    // Sends the request to the first `min_quorum_size` nodes and returns the successful responses and the last error.
+    async fn get_quorum_round(
+        &self,
+        nodes: &[Node],
+        timeout: Duration,
+        raw: bool,
+    ) -> Result<(Vec<NodeResponse>, Option<Error>)> {
+        let nodes = nodes.iter().take(self.min_quorum_size).cloned();
+
+        #[cfg(not(target_family = "wasm"))]
+        let results: Vec<(Node, Result<Response>)> = {
+            let tasks = nodes.map(|node| {
+                let http_client = self.http_client.clone();
+                let node_scores = self.node_scores.clone();
+                tokio::spawn(async move {
+                    let start_time = instant::Instant::now();
+                    let res = if raw {
+                        http_client.get_bytes(node.clone(), timeout).await
+                    } else {
+                        http_client.get(node.clone(), timeout).await
+                    };
+                    node_scores.record(&node.url, start_time, &res);
+                    (node, res)
+                })
+            });
+            futures::future::try_join_all(tasks).await?
+        };
+        #[cfg(target_family = "wasm")]
+        let results: Vec<(Node, Result<Response>)> = {
+            let mut results = Vec::new();
+            for node in nodes {
+                let start_time = instant::Instant::now();
+                let res = if raw {
+                    self.http_client.get_bytes(node.clone(), timeout).await
+                } else {
+                    self.http_client.get(node.clone(), timeout).await
+                };
+                self.node_scores.record(&node.url, start_time, &res);
+                results.push((node, res));
+            }
+            results
+        };
+
+        let mut responses = Vec::new();
+        let mut error = None;
+
+        for (node, res) in results {
+            match res {
+                Ok(res) => responses.push(NodeResponse {
+                    url: node.url,
+                    body: res.into_bytes(),
+                }),
+                Err(Error::ResponseError { code: 404, url, .. }) => {
+                    error.replace(Error::NotFound(url));
+                }
+                Err(err) => {
+                    error.replace(err);
+                }
+            }
+        }
+
+        Ok((responses, error))
+    }
+}
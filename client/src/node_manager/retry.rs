@@ -0,0 +1,201 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Retry policy for requests to nodes
+
+use std::{future::Future, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    constants::{
+        DEFAULT_RETRY_INITIAL_BACKOFF, DEFAULT_RETRY_MAX_ATTEMPTS, DEFAULT_RETRY_MAX_BACKOFF,
+        DEFAULT_RETRY_STATUS_CODES,
+    },
+    error::{Error, Result},
+    node_manager::http_client::HttpMethod,
+};
+
+/// Defines if and how failed requests to nodes are retried.
+///
+/// A request is retried after all available nodes failed, if the error was a timeout, a connection error or a
+/// response with one of the `retry_status_codes`. GET requests are always idempotent, POST requests are only retried
+/// if their route is listed in `idempotent_post_routes`, so a block is never submitted twice.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RetryPolicy {
+    /// Maximum amount of attempts, including the first one. `1` disables retries.
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+    /// Backoff before the first retry.
+    #[serde(default = "default_initial_backoff")]
+    pub initial_backoff: Duration,
+    /// Upper bound of the backoff between two attempts.
+    #[serde(default = "default_max_backoff")]
+    pub max_backoff: Duration,
+    /// Factor by which the backoff grows with every attempt.
+    #[serde(default = "default_backoff_multiplier")]
+    pub backoff_multiplier: u32,
+    /// Randomizes the second half of every backoff, so multiple clients don't retry at the same time.
+    #[serde(default = "default_jitter")]
+    pub jitter: bool,
+    /// HTTP status codes that are considered transient.
+    #[serde(default = "default_retry_status_codes")]
+    pub retry_status_codes: Vec<u16>,
+    /// Route prefixes of POST requests that are safe to retry, e.g. `api/core/v2/peers`.
+    #[serde(default)]
+    pub idempotent_post_routes: Vec<String>,
+}
+
+fn default_max_attempts() -> u32 {
+    DEFAULT_RETRY_MAX_ATTEMPTS
+}
+
+fn default_initial_backoff() -> Duration {
+    DEFAULT_RETRY_INITIAL_BACKOFF
+}
+
+fn default_max_backoff() -> Duration {
+    DEFAULT_RETRY_MAX_BACKOFF
+}
+
+fn default_backoff_multiplier() -> u32 {
+    2
+}
+
+fn default_jitter() -> bool {
+    true
+}
+
+fn default_retry_status_codes() -> Vec<u16> {
+    DEFAULT_RETRY_STATUS_CODES.to_vec()
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: default_max_attempts(),
+            initial_backoff: default_initial_backoff(),
+            max_backoff: default_max_backoff(),
+            backoff_multiplier: default_backoff_multiplier(),
+            jitter: default_jitter(),
+            retry_status_codes: default_retry_status_codes(),
+            idempotent_post_routes: Vec::new(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Creates a policy that never retries.
+    pub fn no_retry() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the maximum amount of attempts.
+    #[must_use]
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Sets the initial and the maximum backoff.
+    #[must_use]
+    pub fn with_backoff(mut self, initial_backoff: Duration, max_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Sets the factor by which the backoff grows with every attempt.
+    #[must_use]
+    pub fn with_backoff_multiplier(mut self, backoff_multiplier: u32) -> Self {
+        self.backoff_multiplier = backoff_multiplier;
+        self
+    }
+
+    /// Sets whether the backoff should be randomized.
+    #[must_use]
+    pub fn with_jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Sets the HTTP status codes that are retried.
+    #[must_use]
+    pub fn with_retry_status_codes(mut self, retry_status_codes: Vec<u16>) -> Self {
+        self.retry_status_codes = retry_status_codes;
+        self
+    }
+
+    /// Marks POST requests to routes starting with `route` as idempotent, so they can be retried.
+    #[must_use]
+    pub fn with_idempotent_post_route(mut self, route: impl Into<String>) -> Self {
+        self.idempotent_post_routes.push(route.into());
+        self
+    }
+
+    /// Returns true if a request with the given method and path can be retried after it failed with `error`.
+    pub fn is_retryable(&self, method: HttpMethod, path: &str, error: &Error) -> bool {
+        let idempotent = match method {
+            HttpMethod::Get | HttpMethod::Delete => true,
+            HttpMethod::Post => self
+                .idempotent_post_routes
+                .iter()
+                .any(|route| path.starts_with(route.trim_start_matches('/'))),
+        };
+
+        idempotent
+            && match error {
+                Error::ResponseError { code, .. } => self.retry_status_codes.contains(code),
+                Error::Reqwest(e) => e.is_timeout() || e.is_request() || e.is_body(),
+                _ => false,
+            }
+    }
+
+    /// Returns the backoff before the given retry, starting at 1.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponent = retry.saturating_sub(1).min(16);
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(self.backoff_multiplier.saturating_pow(exponent))
+            .min(self.max_backoff);
+
+        if self.jitter {
+            let mut entropy = [0u8; 4];
+            if crypto::utils::rand::fill(&mut entropy).is_ok() {
+                let half = backoff / 2;
+                return half + half.mul_f64(f64::from(u32::from_le_bytes(entropy)) / f64::from(u32::MAX));
+            }
+        }
+
+        backoff
+    }
+
+    /// Runs `request` until it succeeds, fails with an error that can't be retried or no attempts are left.
+    pub(crate) async fn retry<T, F, Fut>(&self, method: HttpMethod, path: &str, mut request: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut retry = 0;
+
+        loop {
+            match request().await {
+                Err(error) if retry + 1 < self.max_attempts && self.is_retryable(method, path, &error) => {
+                    retry += 1;
+                    let backoff = self.backoff(retry);
+                    log::debug!("retrying {path} in {} ms after: {error}", backoff.as_millis());
+
+                    #[cfg(not(target_family = "wasm"))]
+                    tokio::time::sleep(backoff).await;
+                    #[cfg(target_family = "wasm")]
+                    gloo_timers::future::TimeoutFuture::new(backoff.as_millis().try_into().unwrap_or(u32::MAX)).await;
+                }
+                res => return res,
+            }
+        }
+    }
+}
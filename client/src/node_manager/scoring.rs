@@ -0,0 +1,265 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Scoring of nodes based on their latency, error rate and sync lag, used to order nodes for requests and to
+//! temporarily bench failing nodes.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use instant::Instant;
+use url::Url;
+
+use crate::{
+    constants::{NODE_BENCH_BASE_DURATION, NODE_BENCH_FAILURE_THRESHOLD, NODE_BENCH_MAX_DURATION},
+    node_manager::node::Node,
+    Client, Error, Result,
+};
+
+/// Weight of a new sample for the moving averages of latency and error rate.
+const SMOOTHING_FACTOR: f64 = 0.2;
+
+/// Statistics of a node, as collected from the requests sent to it.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeStats {
+    /// The url of the node, without credentials.
+    pub url: Url,
+    /// Amount of requests sent to the node.
+    pub requests: u64,
+    /// Amount of failed requests, not counting responses like 404.
+    pub errors: u64,
+    /// Moving average of the response time.
+    pub latency: Option<Duration>,
+    /// Moving average of the error rate, between 0 and 1.
+    pub error_rate: f64,
+    /// Amount of milestones the node is behind the most synced node, as of the last node sync.
+    pub sync_lag: Option<u32>,
+    /// Remaining time for which the node is benched after repeated failures.
+    pub benched_for: Option<Duration>,
+    /// Score between 0 and 1, nodes with a higher score are used first.
+    pub score: f64,
+}
+
+#[derive(Clone, Debug, Default)]
+struct NodeScore {
+    requests: u64,
+    errors: u64,
+    latency_ms: Option<f64>,
+    error_rate: f64,
+    sync_lag: Option<u32>,
+    consecutive_failures: u32,
+    benched_until: Option<Instant>,
+}
+
+impl NodeScore {
+    fn record_success(&mut self, latency: Duration) {
+        let latency_ms = latency.as_secs_f64() * 1000.0;
+        self.requests += 1;
+        self.latency_ms = Some(
+            self.latency_ms
+                .map_or(latency_ms, |avg| SMOOTHING_FACTOR.mul_add(latency_ms - avg, avg)),
+        );
+        self.error_rate *= 1.0 - SMOOTHING_FACTOR;
+        self.consecutive_failures = 0;
+        self.benched_until = None;
+    }
+
+    fn record_failure(&mut self, now: Instant) {
+        self.requests += 1;
+        self.errors += 1;
+        self.error_rate = SMOOTHING_FACTOR.mul_add(1.0 - self.error_rate, self.error_rate);
+        self.consecutive_failures += 1;
+
+        if self.consecutive_failures >= NODE_BENCH_FAILURE_THRESHOLD {
+            // Double the bench duration with every further failure.
+            let exponent = (self.consecutive_failures - NODE_BENCH_FAILURE_THRESHOLD).min(16);
+            let duration = NODE_BENCH_BASE_DURATION
+                .saturating_mul(1 << exponent)
+                .min(NODE_BENCH_MAX_DURATION);
+            self.benched_until = Some(now + duration);
+        }
+    }
+
+    fn benched_for(&self, now: Instant) -> Option<Duration> {
+        self.benched_until
+            .filter(|benched_until| *benched_until > now)
+            .map(|benched_until| benched_until - now)
+    }
+
+    fn score(&self) -> f64 {
+        // Nodes without measurements yet are not penalized, so they get a chance to be used.
+        let latency_factor = self.latency_ms.map_or(1.0, |latency_ms| 1000.0 / (1000.0 + latency_ms));
+        let sync_factor = 1.0 / (1.0 + f64::from(self.sync_lag.unwrap_or(0)));
+
+        latency_factor * (1.0 - self.error_rate) * sync_factor
+    }
+}
+
+/// Shared scores of all nodes, keyed by the origin of their url.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct NodeScores(Arc<RwLock<HashMap<String, NodeScore>>>);
+
+fn node_key(url: &Url) -> String {
+    url.origin().ascii_serialization()
+}
+
+// A failed request is one where the node didn't answer properly; a 404 for example is a valid answer.
+fn is_node_failure<T>(result: &Result<T>) -> bool {
+    match result {
+        Ok(_) => false,
+        Err(Error::ResponseError { code, .. }) => *code >= 500 || *code == 429,
+        Err(Error::NotFound(_)) => false,
+        Err(_) => true,
+    }
+}
+
+impl NodeScores {
+    /// Records the outcome of a request to a node that was started at `start_time`.
+    pub(crate) fn record<T>(&self, url: &Url, start_time: Instant, result: &Result<T>) {
+        let now = Instant::now();
+
+        if let Ok(mut scores) = self.0.write() {
+            let score = scores.entry(node_key(url)).or_default();
+            if is_node_failure(result) {
+                log::debug!("request to {} failed", url.origin().ascii_serialization());
+                score.record_failure(now);
+            } else {
+                score.record_success(now.duration_since(start_time));
+            }
+        }
+    }
+
+    /// Sets how many milestones a node is behind the most synced node.
+    pub(crate) fn set_sync_lag(&self, url: &Url, sync_lag: u32) {
+        if let Ok(mut scores) = self.0.write() {
+            scores.entry(node_key(url)).or_default().sync_lag = Some(sync_lag);
+        }
+    }
+
+    /// Sorts the nodes by their score, the best first.
+    pub(crate) fn sort_by_score(&self, nodes: &mut [Node]) {
+        if let Ok(scores) = self.0.read() {
+            let score = |node: &Node| scores.get(&node_key(&node.url)).map_or(1.0, NodeScore::score);
+            nodes.sort_by(|a, b| score(b).total_cmp(&score(a)));
+        }
+    }
+
+    /// Moves benched nodes to the end, keeping the order otherwise, so they're only used as a last resort.
+    pub(crate) fn move_benched_to_end(&self, nodes: &mut [Node]) {
+        let now = Instant::now();
+
+        if let Ok(scores) = self.0.read() {
+            nodes.sort_by_key(|node| {
+                scores
+                    .get(&node_key(&node.url))
+                    .and_then(|score| score.benched_for(now))
+                    .is_some()
+            });
+        }
+    }
+
+    /// Returns the stats of the given nodes, the best first.
+    pub(crate) fn stats<'a>(&self, nodes: impl Iterator<Item = &'a Node>) -> Result<Vec<NodeStats>> {
+        let now = Instant::now();
+        let scores = self.0.read().map_err(|_| Error::PoisonError)?;
+        let mut stats: Vec<NodeStats> = Vec::new();
+
+        for node in nodes {
+            let mut url = node.url.clone();
+            // Don't expose credentials.
+            url.set_username("").ok();
+            url.set_password(None).ok();
+
+            if stats.iter().any(|s| node_key(&s.url) == node_key(&url)) {
+                continue;
+            }
+
+            let score = scores.get(&node_key(&url)).cloned().unwrap_or_default();
+            stats.push(NodeStats {
+                url,
+                requests: score.requests,
+                errors: score.errors,
+                latency: score
+                    .latency_ms
+                    .map(|latency_ms| Duration::from_secs_f64(latency_ms / 1000.0)),
+                error_rate: score.error_rate,
+                sync_lag: score.sync_lag,
+                benched_for: score.benched_for(now),
+                score: score.score(),
+            });
+        }
+
+        stats.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+        Ok(stats)
+    }
+}
+
+impl Client {
+    /// Returns the collected stats of all nodes, the ones with the best score first.
+    pub fn node_stats(&self) -> Result<Vec<NodeStats>> {
+        let node_manager = &self.node_manager;
+
+        node_manager.node_scores.stats(
+            node_manager
+                .primary_node
+                .iter()
+                .chain(node_manager.primary_pow_node.iter())
+                .chain(node_manager.nodes.iter())
+                .chain(node_manager.permanodes.iter().flatten()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn node_scoring() {
+        let scores = NodeScores::default();
+        let mut nodes: Vec<Node> = ["http://slow:14265", "http://fast:14265", "http://failing:14265"]
+            .iter()
+            .map(|url| Url::parse(url).unwrap().into())
+            .collect();
+
+        let start_time = Instant::now() - Duration::from_millis(500);
+        scores.record(&nodes[0].url, start_time, &Ok(()));
+        scores.record(&nodes[1].url, Instant::now(), &Ok(()));
+        for _ in 0..NODE_BENCH_FAILURE_THRESHOLD {
+            scores.record::<()>(&nodes[2].url, Instant::now(), &Err(Error::Node("timeout".into())));
+        }
+
+        scores.sort_by_score(&mut nodes);
+        assert_eq!(nodes[0].url.host_str(), Some("fast"));
+        assert_eq!(nodes[1].url.host_str(), Some("slow"));
+
+        // The failing node is benched and moved to the end even if it's the primary node.
+        nodes.rotate_right(1);
+        assert_eq!(nodes[0].url.host_str(), Some("failing"));
+        scores.move_benched_to_end(&mut nodes);
+        assert_eq!(nodes[2].url.host_str(), Some("failing"));
+
+        let stats = scores.stats(nodes.iter()).unwrap();
+        let failing = stats.iter().find(|s| s.url.host_str() == Some("failing")).unwrap();
+        assert_eq!(failing.errors, u64::from(NODE_BENCH_FAILURE_THRESHOLD));
+        assert!(failing.benched_for.is_some());
+
+        // A successful request ends the bench.
+        scores.record(&nodes[2].url, Instant::now(), &Ok(()));
+        let stats = scores.stats(nodes.iter()).unwrap();
+        let failing = stats.iter().find(|s| s.url.host_str() == Some("failing")).unwrap();
+        assert!(failing.benched_for.is_none());
+
+        // Not found responses don't count as failures.
+        scores.record::<()>(&nodes[0].url, Instant::now(), &Err(Error::NotFound("url".into())));
+        let stats = scores.stats(nodes.iter()).unwrap();
+        let fast = stats.iter().find(|s| s.url.host_str() == Some("fast")).unwrap();
+        assert_eq!(fast.requests, 2);
+        assert_eq!(fast.errors, 0);
+    }
+}
@@ -3,13 +3,12 @@
 
 #[cfg(not(target_family = "wasm"))]
 use {
-    crate::NetworkInfo,
+    crate::{node_manager::NodeManager, NetworkInfo},
     iota_types::{api::core::response::InfoResponse, block::protocol::ProtocolParameters},
     std::collections::HashMap,
     std::{
         collections::HashSet,
         sync::{Arc, RwLock},
-        time::Duration,
     },
     tokio::{runtime::Runtime, time::sleep},
 };
@@ -48,18 +47,15 @@ impl Client {
     #[cfg(not(target_family = "wasm"))]
     pub(crate) fn start_sync_process(
         runtime: &Runtime,
-        sync: Arc<RwLock<HashMap<Node, InfoResponse>>>,
-        nodes: HashSet<Node>,
-        node_sync_interval: Duration,
+        node_manager: NodeManager,
         network_info: Arc<RwLock<NetworkInfo>>,
-        ignore_node_health: bool,
     ) -> tokio::task::JoinHandle<()> {
         runtime.spawn(async move {
             loop {
                 // Delay first since the first `sync_nodes` call is made by the builder to ensure the node list is
                 // filled before the client is used.
-                sleep(node_sync_interval).await;
-                if let Err(e) = Self::sync_nodes(&sync, &nodes, &network_info, ignore_node_health).await {
+                sleep(node_manager.node_sync_interval).await;
+                if let Err(e) = Self::sync_nodes(&node_manager, &network_info).await {
                     log::warn!("Syncing nodes failed: {e}");
                 }
             }
@@ -67,20 +63,26 @@ impl Client {
     }
 
     #[cfg(not(target_family = "wasm"))]
-    pub(crate) async fn sync_nodes(
-        sync: &Arc<RwLock<HashMap<Node, InfoResponse>>>,
-        nodes: &HashSet<Node>,
-        network_info: &Arc<RwLock<NetworkInfo>>,
-        ignore_node_health: bool,
-    ) -> Result<()> {
+    pub(crate) async fn sync_nodes(node_manager: &NodeManager, network_info: &Arc<RwLock<NetworkInfo>>) -> Result<()> {
         log::debug!("sync_nodes");
+        let nodes: HashSet<&Node> = node_manager
+            .primary_node
+            .iter()
+            .chain(node_manager.nodes.iter())
+            .collect();
         let mut healthy_nodes = HashMap::new();
         let mut network_nodes: HashMap<String, Vec<(InfoResponse, Node)>> = HashMap::new();
 
         for node in nodes {
             // Put the healthy node url into the network_nodes
-            if let Ok(info) = Self::get_node_info(node.url.as_ref(), node.auth.clone()).await {
-                if info.status.is_healthy || ignore_node_health {
+            let start_time = instant::Instant::now();
+            let info =
+                Self::get_node_info_with_http_client(&node_manager.http_client, node.url.as_ref(), node.auth.clone())
+                    .await;
+            node_manager.node_scores.record(&node.url, start_time, &info);
+
+            if let Ok(info) = info {
+                if info.status.is_healthy || node_manager.ignore_node_health {
                     match network_nodes.get_mut(&info.protocol.network_name) {
                         Some(network_node_entry) => {
                             network_node_entry.push((info, node.clone()));
@@ -114,13 +116,27 @@ impl Client {
                 network_info.protocol_parameters = ProtocolParameters::try_from(info.protocol.clone())?;
             }
 
+            // The sync lag is relative to the most synced node of the network
+            let max_confirmed_index = nodes
+                .iter()
+                .map(|(info, _)| info.status.confirmed_milestone.index)
+                .max()
+                .unwrap_or_default();
+
             for (info, node_url) in nodes {
+                node_manager.node_scores.set_sync_lag(
+                    &node_url.url,
+                    max_confirmed_index - info.status.confirmed_milestone.index,
+                );
                 healthy_nodes.insert(node_url.clone(), info.clone());
             }
         }
 
         // Update the sync list.
-        *sync.write().map_err(|_| crate::Error::PoisonError)? = healthy_nodes;
+        *node_manager
+            .healthy_nodes
+            .write()
+            .map_err(|_| crate::Error::PoisonError)? = healthy_nodes;
 
         Ok(())
     }
@@ -17,7 +17,7 @@ use iota_types::block::{
 };
 
 use super::{types::InputSigningData, GenerateAddressOptions, SecretManage};
-use crate::{constants::HD_WALLET_TYPE, secret::RemainderData, Client, Result};
+use crate::{constants::HD_WALLET_TYPE, secret::RemainderData, Client, MnemonicLanguage, Result};
 
 /// Secret manager that uses only a mnemonic.
 ///
@@ -95,6 +95,18 @@ impl MnemonicSecretManager {
         Ok(Self(Client::mnemonic_to_seed(mnemonic)?))
     }
 
+    /// Create a new [`MnemonicSecretManager`] from a BIP-39 mnemonic of the given language, with a passphrase from
+    /// which the seed is derived together with the mnemonic.
+    pub fn try_from_mnemonic_with_passphrase(
+        mnemonic: &str,
+        passphrase: &str,
+        language: MnemonicLanguage,
+    ) -> Result<Self> {
+        Ok(Self(Client::mnemonic_to_seed_with_passphrase(
+            mnemonic, passphrase, language,
+        )?))
+    }
+
     /// Create a new [`MnemonicSecretManager`] from a hex-encoded raw seed string.
     pub fn try_from_hex_seed(hex: &str) -> Result<Self> {
         let bytes: Vec<u8> = prefix_hex::decode(hex)?;
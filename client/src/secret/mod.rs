@@ -10,6 +10,10 @@ pub mod ledger_nano;
 pub mod mnemonic;
 /// Module for the PlaceholderSecretManager
 pub mod placeholder;
+/// Module for signing with a remote signer
+#[cfg(feature = "remote_signer")]
+#[cfg_attr(docsrs, doc(cfg(feature = "remote_signer")))]
+pub mod remote;
 /// Module for signing with a Stronghold vault
 #[cfg(feature = "stronghold")]
 #[cfg_attr(docsrs, doc(cfg(feature = "stronghold")))]
@@ -17,7 +21,7 @@ pub mod stronghold;
 /// Signing related types
 pub mod types;
 
-#[cfg(feature = "stronghold")]
+#[cfg(any(feature = "stronghold", feature = "remote_signer"))]
 use std::time::Duration;
 use std::{collections::HashMap, ops::Range, str::FromStr};
 
@@ -34,10 +38,14 @@ use zeroize::ZeroizeOnDrop;
 
 #[cfg(feature = "ledger_nano")]
 use self::ledger_nano::LedgerSecretManager;
+#[cfg(feature = "remote_signer")]
+use self::remote::{RemoteSecretManager, RemoteSignerKey};
 #[cfg(feature = "stronghold")]
 use self::stronghold::StrongholdSecretManager;
 pub use self::types::{GenerateAddressOptions, LedgerNanoStatus};
-use self::{mnemonic::MnemonicSecretManager, placeholder::PlaceholderSecretManager};
+use self::{mnemonic::MnemonicSecretManager, placeholder::PlaceholderSecretManager, types::MnemonicDto};
+#[cfg(feature = "remote_signer")]
+use crate::secret::types::RemoteSignerDto;
 #[cfg(feature = "stronghold")]
 use crate::secret::types::StrongholdDto;
 use crate::{
@@ -117,6 +125,15 @@ pub enum SecretManager {
     /// Secret manager that's just a placeholder, so it can be provided to an online wallet, but can't be used for
     /// signing.
     Placeholder(PlaceholderSecretManager),
+
+    /// Secret manager that delegates address generation and signing to a remote signer.
+    #[cfg(feature = "remote_signer")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "remote_signer")))]
+    Remote(RemoteSecretManager),
+
+    /// User-defined secret manager, e.g. backed by an HSM or a remote signer. Transaction essences are signed with
+    /// [`SecretManage::signature_unlock()`] and the same placement of unlocks as for the built-in secret managers.
+    Custom(Box<dyn SecretManage>),
 }
 
 impl std::fmt::Debug for SecretManager {
@@ -128,6 +145,9 @@ impl std::fmt::Debug for SecretManager {
             Self::LedgerNano(_) => f.debug_tuple("LedgerNano").field(&"...").finish(),
             Self::Mnemonic(_) => f.debug_tuple("Mnemonic").field(&"...").finish(),
             Self::Placeholder(_) => f.debug_struct("Placeholder").finish(),
+            #[cfg(feature = "remote_signer")]
+            Self::Remote(secret_manager) => f.debug_tuple("Remote").field(secret_manager.endpoint()).finish(),
+            Self::Custom(_) => f.debug_tuple("Custom").field(&"...").finish(),
         }
     }
 }
@@ -155,13 +175,21 @@ pub enum SecretManagerDto {
     LedgerNano(bool),
     /// Mnemonic
     #[serde(alias = "mnemonic")]
-    Mnemonic(String),
+    Mnemonic(MnemonicDto),
     /// Hex seed
     #[serde(alias = "hexSeed")]
     HexSeed(String),
     /// Placeholder
     #[serde(alias = "placeholder")]
     Placeholder,
+    /// Remote signer
+    #[cfg(feature = "remote_signer")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "remote_signer")))]
+    #[serde(alias = "remote")]
+    Remote(RemoteSignerDto),
+    /// Custom secret manager, which can't be created from its DTO
+    #[serde(alias = "custom")]
+    Custom,
 }
 
 impl TryFrom<&SecretManagerDto> for SecretManager {
@@ -180,17 +208,53 @@ impl TryFrom<&SecretManagerDto> for SecretManager {
                     builder = builder.timeout(Duration::from_secs(*timeout));
                 }
 
+                if let Some(seed_slot) = &stronghold_dto.seed_slot {
+                    builder = builder.seed_slot(seed_slot);
+                }
+
+                if let Some(key_derivation) = stronghold_dto.key_derivation {
+                    builder = builder.key_derivation(key_derivation);
+                }
+
                 Self::Stronghold(builder.build(&stronghold_dto.snapshot_path)?)
             }
 
             #[cfg(feature = "ledger_nano")]
             SecretManagerDto::LedgerNano(is_simulator) => Self::LedgerNano(LedgerSecretManager::new(*is_simulator)),
 
-            SecretManagerDto::Mnemonic(mnemonic) => Self::Mnemonic(MnemonicSecretManager::try_from_mnemonic(mnemonic)?),
+            SecretManagerDto::Mnemonic(MnemonicDto::Phrase(mnemonic)) => {
+                Self::Mnemonic(MnemonicSecretManager::try_from_mnemonic(mnemonic)?)
+            }
+
+            SecretManagerDto::Mnemonic(MnemonicDto::WithPassphrase {
+                mnemonic,
+                passphrase,
+                language,
+            }) => Self::Mnemonic(MnemonicSecretManager::try_from_mnemonic_with_passphrase(
+                mnemonic,
+                passphrase.as_deref().unwrap_or_default(),
+                *language,
+            )?),
 
             SecretManagerDto::HexSeed(hex_seed) => Self::Mnemonic(MnemonicSecretManager::try_from_hex_seed(hex_seed)?),
 
             SecretManagerDto::Placeholder => Self::Placeholder(PlaceholderSecretManager),
+
+            #[cfg(feature = "remote_signer")]
+            SecretManagerDto::Remote(remote_signer_dto) => {
+                let mut secret_manager = RemoteSecretManager::new(
+                    remote_signer_dto.endpoint.clone(),
+                    RemoteSignerKey::try_from_hex(&remote_signer_dto.key)?,
+                );
+
+                if let Some(timeout) = &remote_signer_dto.timeout {
+                    secret_manager = secret_manager.with_timeout(Duration::from_secs(*timeout));
+                }
+
+                Self::Remote(secret_manager)
+            }
+
+            SecretManagerDto::Custom => return Err(crate::Error::CustomSecretManagerDto),
         })
     }
 }
@@ -208,6 +272,8 @@ impl From<&SecretManager> for SecretManagerDto {
                     .into_os_string()
                     .to_string_lossy()
                     .into(),
+                seed_slot: Some(stronghold_adapter.seed_slot().to_string()),
+                key_derivation: Some(stronghold_adapter.key_derivation()),
             }),
 
             #[cfg(feature = "ledger_nano")]
@@ -216,8 +282,16 @@ impl From<&SecretManager> for SecretManagerDto {
             // `MnemonicSecretManager(Seed)` doesn't have Debug or Display implemented and in the current use cases of
             // the client/wallet we also don't need to convert it in this direction with the mnemonic/seed, we only need
             // to know the type
-            SecretManager::Mnemonic(_mnemonic) => Self::Mnemonic("...".to_string()),
+            SecretManager::Mnemonic(_mnemonic) => Self::Mnemonic("...".to_string().into()),
             SecretManager::Placeholder(_) => Self::Placeholder,
+            // The shared key isn't exposed, only the type and the endpoint are needed.
+            #[cfg(feature = "remote_signer")]
+            SecretManager::Remote(remote) => Self::Remote(RemoteSignerDto {
+                endpoint: remote.endpoint().clone(),
+                key: "...".to_string(),
+                timeout: Some(remote.timeout().as_secs()),
+            }),
+            SecretManager::Custom(_) => Self::Custom,
         }
     }
 }
@@ -255,6 +329,17 @@ impl SecretManage for SecretManager {
                     .generate_addresses(coin_type, account_index, address_indexes, internal, options)
                     .await
             }
+            #[cfg(feature = "remote_signer")]
+            Self::Remote(secret_manager) => {
+                secret_manager
+                    .generate_addresses(coin_type, account_index, address_indexes, internal, options)
+                    .await
+            }
+            Self::Custom(secret_manager) => {
+                secret_manager
+                    .generate_addresses(coin_type, account_index, address_indexes, internal, options)
+                    .await
+            }
         }
     }
 
@@ -271,6 +356,9 @@ impl SecretManage for SecretManager {
             Self::LedgerNano(secret_manager) => secret_manager.signature_unlock(input, essence_hash, metadata).await,
             Self::Mnemonic(secret_manager) => secret_manager.signature_unlock(input, essence_hash, metadata).await,
             Self::Placeholder(secret_manager) => secret_manager.signature_unlock(input, essence_hash, metadata).await,
+            #[cfg(feature = "remote_signer")]
+            Self::Remote(secret_manager) => secret_manager.signature_unlock(input, essence_hash, metadata).await,
+            Self::Custom(secret_manager) => secret_manager.signature_unlock(input, essence_hash, metadata).await,
         }
     }
 
@@ -282,6 +370,9 @@ impl SecretManage for SecretManager {
             Self::LedgerNano(secret_manager) => secret_manager.sign_ed25519(msg, chain).await,
             Self::Mnemonic(secret_manager) => secret_manager.sign_ed25519(msg, chain).await,
             Self::Placeholder(secret_manager) => secret_manager.sign_ed25519(msg, chain).await,
+            #[cfg(feature = "remote_signer")]
+            Self::Remote(secret_manager) => secret_manager.sign_ed25519(msg, chain).await,
+            Self::Custom(secret_manager) => secret_manager.sign_ed25519(msg, chain).await,
         }
     }
 }
@@ -305,11 +396,16 @@ impl SecretManageExt for SecretManager {
                     .sign_transaction_essence(prepared_transaction_data, time)
                     .await
             }
-            Self::Mnemonic(_) => {
+            Self::Mnemonic(_) | Self::Custom(_) => {
                 self.default_sign_transaction_essence(prepared_transaction_data, time)
                     .await
             }
             Self::Placeholder(_) => self.sign_transaction_essence(prepared_transaction_data, time).await,
+            #[cfg(feature = "remote_signer")]
+            Self::Remote(_) => {
+                self.default_sign_transaction_essence(prepared_transaction_data, time)
+                    .await
+            }
         }
     }
 }
@@ -325,7 +421,12 @@ impl SecretManager {
         Ok(Self::Mnemonic(MnemonicSecretManager::try_from_hex_seed(seed)?))
     }
 
-    // Shared implementation for MnemonicSecretManager and StrongholdSecretManager
+    /// Creates a [`SecretManager`] from a user-defined [`SecretManage`] implementation.
+    pub fn custom(secret_manager: impl SecretManage + 'static) -> Self {
+        Self::Custom(Box::new(secret_manager))
+    }
+
+    // Shared implementation for MnemonicSecretManager, StrongholdSecretManager and custom secret managers
     async fn default_sign_transaction_essence<'a>(
         &self,
         prepared_transaction_data: &PreparedTransactionData,
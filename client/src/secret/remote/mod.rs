@@ -0,0 +1,239 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Implementation of [`RemoteSecretManager`], which delegates address generation and signing to a remote signer, and
+//! of [`RemoteSignerService`], which serves its requests.
+//!
+//! Over HTTP, every [`RemoteSignerEnvelope`] is posted as JSON and answered with a JSON envelope. Over a Unix
+//! domain socket, the envelopes are sent as single lines of JSON, one request per connection.
+
+mod protocol;
+mod service;
+
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::{ops::Range, time::Duration};
+
+use async_trait::async_trait;
+use crypto::keys::slip10::Chain;
+use iota_types::block::{address::Address, signature::Ed25519Signature, unlock::Unlock};
+use serde::{Deserialize, Serialize};
+#[cfg(unix)]
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use url::Url;
+
+pub use self::{protocol::*, service::RemoteSignerService};
+use super::{types::InputSigningData, GenerateAddressOptions, SecretManage};
+use crate::{
+    api::{RemainderData, RemainderDataDto},
+    secret::types::InputSigningDataDto,
+    Error, Result,
+};
+
+/// Default timeout of a request to a remote signer.
+pub const DEFAULT_REMOTE_SIGNER_TIMEOUT: Duration = Duration::from_secs(30);
+/// Default timeout of a [`RemoteSignerService`] for reading a request from a connection.
+pub const DEFAULT_REMOTE_SIGNER_READ_TIMEOUT: Duration = Duration::from_secs(10);
+/// Default maximum number of connections a [`RemoteSignerService`] serves at once.
+pub const DEFAULT_REMOTE_SIGNER_MAX_CONNECTIONS: usize = 32;
+/// Maximum length of a message of the remote signer protocol.
+#[cfg(unix)]
+const MAX_MESSAGE_LENGTH: usize = 1024 * 1024;
+
+/// The endpoint of a remote signer.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RemoteSignerEndpoint {
+    /// URL the requests are posted to.
+    Http(Url),
+    /// Path of a Unix domain socket.
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+/// Secret manager that delegates address generation and signing to a remote signer, e.g. a signing daemon of a
+/// custody setup.
+///
+/// Requests and responses are authenticated with a [`RemoteSignerKey`] shared with the signer, which only serves the
+/// requests of its [`RemoteSignerAllowList`].
+#[derive(Debug)]
+pub struct RemoteSecretManager {
+    endpoint: RemoteSignerEndpoint,
+    key: RemoteSignerKey,
+    timeout: Duration,
+    http_client: reqwest::Client,
+}
+
+impl RemoteSecretManager {
+    /// Creates a new [`RemoteSecretManager`] sending its requests to the given endpoint.
+    pub fn new(endpoint: RemoteSignerEndpoint, key: RemoteSignerKey) -> Self {
+        Self {
+            endpoint,
+            key,
+            timeout: DEFAULT_REMOTE_SIGNER_TIMEOUT,
+            http_client: reqwest::Client::new(),
+        }
+    }
+
+    /// Sets the timeout of the requests.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Returns the endpoint of the remote signer.
+    pub fn endpoint(&self) -> &RemoteSignerEndpoint {
+        &self.endpoint
+    }
+
+    /// Returns the timeout of the requests.
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    async fn request(&self, request: &RemoteSignerRequest) -> Result<RemoteSignerResponse> {
+        let envelope = RemoteSignerEnvelope::request(&self.key, request)?;
+        let response = tokio::time::timeout(self.timeout, self.send(&envelope))
+            .await
+            .map_err(|_| Error::RemoteSigner("request timed out".to_string()))??;
+
+        match response.open_response(&self.key, &envelope.nonce)? {
+            RemoteSignerResponse::Error(error) => Err(Error::RemoteSigner(error)),
+            response => Ok(response),
+        }
+    }
+
+    async fn send(&self, envelope: &RemoteSignerEnvelope) -> Result<RemoteSignerEnvelope> {
+        match &self.endpoint {
+            RemoteSignerEndpoint::Http(url) => {
+                let response = self.http_client.post(url.clone()).json(envelope).send().await?;
+                let status = response.status();
+
+                if !status.is_success() {
+                    return Err(Error::RemoteSigner(format!(
+                        "request rejected with status code {}: {}",
+                        status.as_u16(),
+                        response.text().await?
+                    )));
+                }
+
+                Ok(response.json().await?)
+            }
+            #[cfg(unix)]
+            RemoteSignerEndpoint::Unix(path) => {
+                let (reader, mut writer) = tokio::net::UnixStream::connect(path).await?.into_split();
+                write_message(&mut writer, envelope).await?;
+
+                match read_message(&mut tokio::io::BufReader::new(reader)).await? {
+                    Some(RemoteSignerMessage::Envelope(envelope)) => Ok(envelope),
+                    Some(RemoteSignerMessage::Rejected { error }) => {
+                        Err(Error::RemoteSigner(format!("request rejected: {error}")))
+                    }
+                    None => Err(Error::RemoteSigner("connection closed without response".to_string())),
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl SecretManage for RemoteSecretManager {
+    async fn generate_addresses(
+        &self,
+        coin_type: u32,
+        account_index: u32,
+        address_indexes: Range<u32>,
+        internal: bool,
+        options: Option<GenerateAddressOptions>,
+    ) -> crate::Result<Vec<Address>> {
+        let request = RemoteSignerRequest::GenerateAddresses {
+            coin_type,
+            account_index,
+            range: address_indexes,
+            internal,
+            options,
+        };
+
+        match self.request(&request).await? {
+            RemoteSignerResponse::GeneratedAddresses(addresses) => Ok(addresses
+                .iter()
+                .map(Address::try_from)
+                .collect::<core::result::Result<_, _>>()?),
+            response => Err(unexpected_response(&response)),
+        }
+    }
+
+    async fn signature_unlock(
+        &self,
+        input: &InputSigningData,
+        essence_hash: &[u8; 32],
+        remainder: &Option<RemainderData>,
+    ) -> crate::Result<Unlock> {
+        let request = RemoteSignerRequest::SignatureUnlock {
+            input: InputSigningDataDto::from(input),
+            essence_hash: prefix_hex::encode(essence_hash),
+            remainder: remainder.as_ref().map(RemainderDataDto::from),
+        };
+
+        match self.request(&request).await? {
+            RemoteSignerResponse::SignatureUnlock(unlock) => Ok(Unlock::try_from(&unlock)?),
+            response => Err(unexpected_response(&response)),
+        }
+    }
+
+    async fn sign_ed25519(&self, msg: &[u8], chain: &Chain) -> crate::Result<Ed25519Signature> {
+        let request = RemoteSignerRequest::SignEd25519 {
+            message: prefix_hex::encode(msg),
+            chain: chain.clone(),
+        };
+
+        match self.request(&request).await? {
+            RemoteSignerResponse::Ed25519Signature(signature) => Ok(Ed25519Signature::try_from(&signature)?),
+            response => Err(unexpected_response(&response)),
+        }
+    }
+}
+
+fn unexpected_response(response: &RemoteSignerResponse) -> Error {
+    Error::RemoteSigner(format!("unexpected response: {response:?}"))
+}
+
+/// A message sent over a Unix domain socket, either an envelope or the rejection of an unauthenticated request.
+#[cfg(unix)]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum RemoteSignerMessage {
+    Envelope(RemoteSignerEnvelope),
+    Rejected { error: String },
+}
+
+/// Reads a line of JSON, `None` if the connection was closed before.
+#[cfg(unix)]
+async fn read_message<R, T>(reader: &mut R) -> Result<Option<T>>
+where
+    R: AsyncBufRead + Unpin,
+    T: serde::de::DeserializeOwned,
+{
+    let mut line = String::new();
+
+    if reader.take(MAX_MESSAGE_LENGTH as u64).read_line(&mut line).await? == 0 {
+        return Ok(None);
+    }
+
+    Ok(Some(serde_json::from_str(&line)?))
+}
+
+/// Writes a line of JSON.
+#[cfg(unix)]
+async fn write_message<W, T>(writer: &mut W, message: &T) -> Result<()>
+where
+    W: AsyncWrite + Unpin + Send,
+    T: Serialize + Sync,
+{
+    let mut line = serde_json::to_vec(message)?;
+    line.push(b'\n');
+    writer.write_all(&line).await?;
+    writer.flush().await?;
+
+    Ok(())
+}
@@ -0,0 +1,318 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Wire format of the remote signer protocol.
+//!
+//! Every message is wrapped into a [`RemoteSignerEnvelope`], authenticated with an HMAC-SHA256 keyed with a
+//! [`RemoteSignerKey`] shared by the client and the signer. Requests and responses use different domains, so a
+//! request can't be replayed as a response, and a response carries the nonce of its request, so both sides know they
+//! talk to a holder of the key.
+
+use std::{collections::HashSet, ops::Range};
+
+use crypto::{keys::slip10::Chain, macs::hmac::HMAC_SHA256, utils};
+use iota_types::block::{address::dto::AddressDto, signature::dto::Ed25519SignatureDto, unlock::dto::UnlockDto};
+use serde::{Deserialize, Serialize};
+use zeroize::ZeroizeOnDrop;
+
+use crate::{
+    api::RemainderDataDto,
+    secret::{types::InputSigningDataDto, GenerateAddressOptions},
+    unix_timestamp_now, Error, Result,
+};
+
+/// Maximum difference in seconds between the timestamp of a message and the local time.
+pub const MAX_CLOCK_DRIFT: u32 = 30;
+
+const REQUEST_DOMAIN: &[u8] = b"iota-remote-signer-request";
+const RESPONSE_DOMAIN: &[u8] = b"iota-remote-signer-response";
+const NONCE_LENGTH: usize = 32;
+const HARDENED: u32 = 0x8000_0000;
+
+/// Key shared by a [`RemoteSecretManager`](super::RemoteSecretManager) and a remote signer to authenticate each
+/// other.
+#[derive(Clone, ZeroizeOnDrop)]
+pub struct RemoteSignerKey([u8; 32]);
+
+impl RemoteSignerKey {
+    /// Creates a new [`RemoteSignerKey`] from its bytes.
+    pub fn new(key: [u8; 32]) -> Self {
+        Self(key)
+    }
+
+    /// Generates a new random [`RemoteSignerKey`].
+    pub fn generate() -> Result<Self> {
+        let mut key = [0u8; 32];
+        utils::rand::fill(&mut key)?;
+        Ok(Self(key))
+    }
+
+    /// Tries to create a [`RemoteSignerKey`] from a hex-encoded string.
+    pub fn try_from_hex(hex: &str) -> Result<Self> {
+        Ok(Self(prefix_hex::decode(hex)?))
+    }
+
+    fn mac(&self, domain: &[u8], nonce: &str, timestamp: u32, payload: &str) -> [u8; 32] {
+        let mut data = Vec::with_capacity(domain.len() + nonce.len() + payload.len() + 12);
+        data.extend_from_slice(domain);
+        // Length prefixes keep the boundaries between the fields unambiguous.
+        data.extend_from_slice(&(nonce.len() as u32).to_be_bytes());
+        data.extend_from_slice(nonce.as_bytes());
+        data.extend_from_slice(&timestamp.to_be_bytes());
+        data.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+        data.extend_from_slice(payload.as_bytes());
+
+        let mut mac = [0u8; 32];
+        HMAC_SHA256(&data, &self.0, &mut mac);
+        mac
+    }
+}
+
+impl core::fmt::Debug for RemoteSignerKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("RemoteSignerKey").field(&"...").finish()
+    }
+}
+
+/// An authenticated request or response of the remote signer protocol.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RemoteSignerEnvelope {
+    /// Random hex-encoded nonce chosen by the client, echoed in the response.
+    pub nonce: String,
+    /// UNIX timestamp in seconds of the creation of the message.
+    pub timestamp: u32,
+    /// The JSON-encoded [`RemoteSignerRequest`] or [`RemoteSignerResponse`].
+    pub payload: String,
+    /// Hex-encoded HMAC-SHA256 of the other fields.
+    pub mac: String,
+}
+
+impl RemoteSignerEnvelope {
+    /// Wraps a request into an envelope with a new random nonce.
+    pub fn request(key: &RemoteSignerKey, request: &RemoteSignerRequest) -> Result<Self> {
+        let mut nonce = [0u8; NONCE_LENGTH];
+        utils::rand::fill(&mut nonce)?;
+
+        Self::seal(
+            key,
+            REQUEST_DOMAIN,
+            prefix_hex::encode(nonce),
+            serde_json::to_string(request)?,
+        )
+    }
+
+    /// Wraps the response to the request with the given nonce into an envelope.
+    pub fn response(key: &RemoteSignerKey, nonce: String, response: &RemoteSignerResponse) -> Result<Self> {
+        Self::seal(key, RESPONSE_DOMAIN, nonce, serde_json::to_string(response)?)
+    }
+
+    /// Authenticates the envelope of a request and returns the request.
+    pub fn open_request(&self, key: &RemoteSignerKey) -> Result<RemoteSignerRequest> {
+        self.open(key, REQUEST_DOMAIN)
+    }
+
+    /// Authenticates the envelope of the response to the request with the given nonce and returns the response.
+    pub fn open_response(&self, key: &RemoteSignerKey, nonce: &str) -> Result<RemoteSignerResponse> {
+        if self.nonce != nonce {
+            return Err(Error::RemoteSignerAuthentication);
+        }
+
+        self.open(key, RESPONSE_DOMAIN)
+    }
+
+    fn seal(key: &RemoteSignerKey, domain: &[u8], nonce: String, payload: String) -> Result<Self> {
+        let timestamp = unix_timestamp_now();
+        let mac = prefix_hex::encode(key.mac(domain, &nonce, timestamp, &payload));
+
+        Ok(Self {
+            nonce,
+            timestamp,
+            payload,
+            mac,
+        })
+    }
+
+    fn open<T: serde::de::DeserializeOwned>(&self, key: &RemoteSignerKey, domain: &[u8]) -> Result<T> {
+        let mac: [u8; 32] = prefix_hex::decode(&self.mac).map_err(|_| Error::RemoteSignerAuthentication)?;
+        let expected_mac = key.mac(domain, &self.nonce, self.timestamp, &self.payload);
+
+        // Constant time comparison to not leak how many bytes of the MAC are correct.
+        if mac.iter().zip(expected_mac).fold(0, |diff, (a, b)| diff | (a ^ b)) != 0 {
+            return Err(Error::RemoteSignerAuthentication);
+        }
+
+        if unix_timestamp_now().abs_diff(self.timestamp) > MAX_CLOCK_DRIFT {
+            return Err(Error::RemoteSignerAuthentication);
+        }
+
+        Ok(serde_json::from_str(&self.payload)?)
+    }
+}
+
+/// The kind of a [`RemoteSignerRequest`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RemoteSignerRequestKind {
+    /// [`RemoteSignerRequest::GenerateAddresses`].
+    GenerateAddresses,
+    /// [`RemoteSignerRequest::SignatureUnlock`].
+    SignatureUnlock,
+    /// [`RemoteSignerRequest::SignEd25519`].
+    SignEd25519,
+}
+
+/// A request to a remote signer, mirroring the methods of [`SecretManage`](crate::secret::SecretManage).
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "name", content = "data", rename_all = "camelCase")]
+pub enum RemoteSignerRequest {
+    /// Generates addresses.
+    /// Expected response: [`GeneratedAddresses`](RemoteSignerResponse::GeneratedAddresses)
+    GenerateAddresses {
+        /// The coin type.
+        #[serde(rename = "coinType")]
+        coin_type: u32,
+        /// The account index.
+        #[serde(rename = "accountIndex")]
+        account_index: u32,
+        /// The range of address indexes.
+        range: Range<u32>,
+        /// Whether to generate internal (change) addresses.
+        internal: bool,
+        /// The address generation options.
+        options: Option<GenerateAddressOptions>,
+    },
+    /// Signs the hash of a transaction essence to unlock an input.
+    /// Expected response: [`SignatureUnlock`](RemoteSignerResponse::SignatureUnlock)
+    SignatureUnlock {
+        /// The input to unlock.
+        input: InputSigningDataDto,
+        /// The hex-encoded hash of the transaction essence.
+        #[serde(rename = "essenceHash")]
+        essence_hash: String,
+        /// The remainder of the transaction.
+        remainder: Option<RemainderDataDto>,
+    },
+    /// Signs a message with the key of the given chain.
+    /// Expected response: [`Ed25519Signature`](RemoteSignerResponse::Ed25519Signature)
+    SignEd25519 {
+        /// The hex-encoded message.
+        message: String,
+        /// The BIP-32 chain of the key.
+        chain: Chain,
+    },
+}
+
+impl RemoteSignerRequest {
+    /// Returns the kind of the request.
+    pub fn kind(&self) -> RemoteSignerRequestKind {
+        match self {
+            Self::GenerateAddresses { .. } => RemoteSignerRequestKind::GenerateAddresses,
+            Self::SignatureUnlock { .. } => RemoteSignerRequestKind::SignatureUnlock,
+            Self::SignEd25519 { .. } => RemoteSignerRequestKind::SignEd25519,
+        }
+    }
+}
+
+/// A response of a remote signer.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "payload", rename_all = "camelCase")]
+pub enum RemoteSignerResponse {
+    /// Response for [`GenerateAddresses`](RemoteSignerRequest::GenerateAddresses)
+    GeneratedAddresses(Vec<AddressDto>),
+    /// Response for [`SignatureUnlock`](RemoteSignerRequest::SignatureUnlock)
+    SignatureUnlock(UnlockDto),
+    /// Response for [`SignEd25519`](RemoteSignerRequest::SignEd25519)
+    Ed25519Signature(Ed25519SignatureDto),
+    /// The request was authenticated but failed or wasn't allowed.
+    Error(String),
+}
+
+/// The requests a remote signer accepts.
+///
+/// Nothing is allowed by default: request kinds and coin types have to be listed explicitly, account indexes are
+/// only restricted if at least one of them is listed.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RemoteSignerAllowList {
+    /// The allowed request kinds.
+    #[serde(default)]
+    pub requests: HashSet<RemoteSignerRequestKind>,
+    /// The allowed coin types.
+    #[serde(default, rename = "coinTypes")]
+    pub coin_types: HashSet<u32>,
+    /// The allowed account indexes, any if empty.
+    #[serde(default, rename = "accountIndexes")]
+    pub account_indexes: HashSet<u32>,
+}
+
+impl RemoteSignerAllowList {
+    /// Creates a new [`RemoteSignerAllowList`] that doesn't allow any request.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allows requests of the given kind.
+    pub fn with_request(mut self, kind: RemoteSignerRequestKind) -> Self {
+        self.requests.insert(kind);
+        self
+    }
+
+    /// Allows keys of the given coin type.
+    pub fn with_coin_type(mut self, coin_type: u32) -> Self {
+        self.coin_types.insert(coin_type);
+        self
+    }
+
+    /// Allows keys of the given account index.
+    pub fn with_account_index(mut self, account_index: u32) -> Self {
+        self.account_indexes.insert(account_index);
+        self
+    }
+
+    /// Checks that the request is allowed.
+    pub fn check(&self, request: &RemoteSignerRequest) -> Result<()> {
+        if !self.requests.contains(&request.kind()) {
+            return Err(Error::RemoteSignerRequestNotAllowed(format!("{:?}", request.kind())));
+        }
+
+        match request {
+            RemoteSignerRequest::GenerateAddresses {
+                coin_type,
+                account_index,
+                ..
+            } => self.check_account(*coin_type, *account_index),
+            RemoteSignerRequest::SignatureUnlock { input, .. } => {
+                self.check_chain(input.chain.as_ref().ok_or(Error::InvalidBIP32ChainData)?)
+            }
+            RemoteSignerRequest::SignEd25519 { chain, .. } => self.check_chain(chain),
+        }
+    }
+
+    fn check_chain(&self, chain: &Chain) -> Result<()> {
+        let segments = chain
+            .segments()
+            .iter()
+            // XXX: "ser32(i)". RTFSC: [crypto::keys::slip10::Segment::from_u32()]
+            .map(|seg| u32::from_be_bytes(seg.bs()) & !HARDENED)
+            .collect::<Vec<_>>();
+
+        match segments.as_slice() {
+            [_, coin_type, account_index, ..] => self.check_account(*coin_type, *account_index),
+            _ => Err(Error::InvalidBIP32ChainData),
+        }
+    }
+
+    fn check_account(&self, coin_type: u32, account_index: u32) -> Result<()> {
+        if !self.coin_types.contains(&coin_type) {
+            return Err(Error::RemoteSignerRequestNotAllowed(format!("coin type {coin_type}")));
+        }
+
+        if !self.account_indexes.is_empty() && !self.account_indexes.contains(&account_index) {
+            return Err(Error::RemoteSignerRequestNotAllowed(format!(
+                "account index {account_index}"
+            )));
+        }
+
+        Ok(())
+    }
+}
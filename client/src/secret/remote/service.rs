@@ -0,0 +1,308 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Serving of remote signer requests with any [`SecretManage`] implementation.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use iota_types::block::{address::dto::AddressDto, signature::dto::Ed25519SignatureDto, unlock::dto::UnlockDto};
+#[cfg(unix)]
+use tokio::net::{UnixListener, UnixStream};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+    sync::{OwnedSemaphorePermit, Semaphore},
+};
+
+#[cfg(unix)]
+use super::{read_message, write_message, RemoteSignerMessage};
+use super::{
+    RemoteSignerAllowList, RemoteSignerEnvelope, RemoteSignerKey, RemoteSignerRequest, RemoteSignerResponse,
+    DEFAULT_REMOTE_SIGNER_MAX_CONNECTIONS, DEFAULT_REMOTE_SIGNER_READ_TIMEOUT, MAX_CLOCK_DRIFT,
+};
+use crate::{
+    api::RemainderData,
+    secret::{types::InputSigningData, SecretManage},
+    unix_timestamp_now, Error, Result,
+};
+
+/// Maximum length of the request line and headers of an HTTP request.
+const MAX_HTTP_HEAD_LENGTH: u64 = 8 * 1024;
+/// Maximum length of the body of an HTTP request.
+const MAX_HTTP_BODY_LENGTH: usize = 1024 * 1024;
+
+/// Serves the requests of [`RemoteSecretManager`](super::RemoteSecretManager)s with a [`SecretManage`]
+/// implementation.
+///
+/// Only requests authenticated with the shared [`RemoteSignerKey`] are served, each nonce only once, and only if the
+/// [`RemoteSignerAllowList`] allows them. As requests are read before they're authenticated, only a limited number of
+/// connections is served at once, and connections that don't send a request within the read timeout are closed.
+#[derive(Debug)]
+pub struct RemoteSignerService<S> {
+    secret_manager: S,
+    key: RemoteSignerKey,
+    allow_list: RemoteSignerAllowList,
+    read_timeout: Duration,
+    connections: Arc<Semaphore>,
+    // The nonces of the served requests with their timestamps, kept until they're too old to be accepted anyway.
+    nonces: Mutex<HashMap<String, u32>>,
+}
+
+impl<S: SecretManage> RemoteSignerService<S> {
+    /// Creates a new [`RemoteSignerService`].
+    pub fn new(secret_manager: S, key: RemoteSignerKey, allow_list: RemoteSignerAllowList) -> Self {
+        Self {
+            secret_manager,
+            key,
+            allow_list,
+            read_timeout: DEFAULT_REMOTE_SIGNER_READ_TIMEOUT,
+            connections: Arc::new(Semaphore::new(DEFAULT_REMOTE_SIGNER_MAX_CONNECTIONS)),
+            nonces: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Sets the timeout for reading a request from a connection.
+    pub fn with_read_timeout(mut self, read_timeout: Duration) -> Self {
+        self.read_timeout = read_timeout;
+        self
+    }
+
+    /// Sets the maximum number of connections that are served at once, further connections wait to be accepted.
+    pub fn with_max_connections(mut self, max_connections: usize) -> Self {
+        self.connections = Arc::new(Semaphore::new(max_connections));
+        self
+    }
+
+    /// Returns the secret manager of the service.
+    pub fn secret_manager(&self) -> &S {
+        &self.secret_manager
+    }
+
+    /// Returns the allow list of the service.
+    pub fn allow_list(&self) -> &RemoteSignerAllowList {
+        &self.allow_list
+    }
+
+    /// Authenticates and serves a request. Requests that fail or aren't allowed are answered with an authenticated
+    /// [`RemoteSignerResponse::Error`], an error is only returned for requests that fail authentication.
+    pub async fn handle(&self, envelope: &RemoteSignerEnvelope) -> Result<RemoteSignerEnvelope> {
+        let request = envelope.open_request(&self.key)?;
+        self.check_nonce(envelope)?;
+
+        let response = self
+            .serve(request)
+            .await
+            .unwrap_or_else(|error| RemoteSignerResponse::Error(error.to_string()));
+
+        RemoteSignerEnvelope::response(&self.key, envelope.nonce.clone(), &response)
+    }
+
+    fn check_nonce(&self, envelope: &RemoteSignerEnvelope) -> Result<()> {
+        let now = unix_timestamp_now();
+        let mut nonces = self.nonces.lock().map_err(|_| Error::PoisonError)?;
+
+        nonces.retain(|_, timestamp| now.abs_diff(*timestamp) <= MAX_CLOCK_DRIFT);
+
+        if nonces.insert(envelope.nonce.clone(), envelope.timestamp).is_some() {
+            return Err(Error::RemoteSignerAuthentication);
+        }
+
+        Ok(())
+    }
+
+    async fn serve(&self, request: RemoteSignerRequest) -> Result<RemoteSignerResponse> {
+        self.allow_list.check(&request)?;
+
+        Ok(match request {
+            RemoteSignerRequest::GenerateAddresses {
+                coin_type,
+                account_index,
+                range,
+                internal,
+                options,
+            } => RemoteSignerResponse::GeneratedAddresses(
+                self.secret_manager
+                    .generate_addresses(coin_type, account_index, range, internal, options)
+                    .await?
+                    .iter()
+                    .map(AddressDto::from)
+                    .collect(),
+            ),
+            RemoteSignerRequest::SignatureUnlock {
+                input,
+                essence_hash,
+                remainder,
+            } => {
+                let input = InputSigningData::try_from_dto_unverified(&input)?;
+                let essence_hash: [u8; 32] = prefix_hex::decode(&essence_hash)?;
+                let remainder = remainder
+                    .as_ref()
+                    .map(RemainderData::try_from_dto_unverified)
+                    .transpose()?;
+                let unlock = self
+                    .secret_manager
+                    .signature_unlock(&input, &essence_hash, &remainder)
+                    .await?;
+
+                RemoteSignerResponse::SignatureUnlock(UnlockDto::from(&unlock))
+            }
+            RemoteSignerRequest::SignEd25519 { message, chain } => {
+                let message: Vec<u8> = prefix_hex::decode(&message)?;
+                let signature = self.secret_manager.sign_ed25519(&message, &chain).await?;
+
+                RemoteSignerResponse::Ed25519Signature(Ed25519SignatureDto::from(&signature))
+            }
+        })
+    }
+}
+
+impl<S: SecretManage + 'static> RemoteSignerService<S> {
+    /// Serves requests posted over HTTP to any path of the listener until accepting a connection fails.
+    pub async fn serve_http(self: Arc<Self>, listener: TcpListener) -> Result<()> {
+        loop {
+            let permit = self.acquire_connection().await?;
+            let (stream, peer) = listener.accept().await?;
+            let service = self.clone();
+
+            tokio::spawn(async move {
+                let _permit = permit;
+                if let Err(e) = service.serve_http_connection(stream).await {
+                    log::debug!("[RemoteSignerService] failed to serve {peer}: {e}");
+                }
+            });
+        }
+    }
+
+    /// Serves requests sent over a Unix domain socket until accepting a connection fails.
+    #[cfg(unix)]
+    pub async fn serve_unix(self: Arc<Self>, listener: UnixListener) -> Result<()> {
+        loop {
+            let permit = self.acquire_connection().await?;
+            let (stream, _) = listener.accept().await?;
+            let service = self.clone();
+
+            tokio::spawn(async move {
+                let _permit = permit;
+                if let Err(e) = service.serve_unix_connection(stream).await {
+                    log::debug!("[RemoteSignerService] failed to serve Unix connection: {e}");
+                }
+            });
+        }
+    }
+
+    // Waits until less than the maximum number of connections are served.
+    async fn acquire_connection(&self) -> Result<OwnedSemaphorePermit> {
+        self.connections
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|e| Error::RemoteSigner(e.to_string()))
+    }
+
+    async fn serve_http_connection(&self, stream: TcpStream) -> Result<()> {
+        let mut reader = BufReader::new(stream);
+
+        let (status, body) = match tokio::time::timeout(self.read_timeout, read_http_request(&mut reader)).await {
+            Ok(request) => match request? {
+                Ok(body) => match serde_json::from_slice::<RemoteSignerEnvelope>(&body) {
+                    Ok(envelope) => match self.handle(&envelope).await {
+                        Ok(response) => ("200 OK", serde_json::to_vec(&response)?),
+                        Err(e) => ("401 Unauthorized", e.to_string().into_bytes()),
+                    },
+                    Err(e) => ("400 Bad Request", e.to_string().into_bytes()),
+                },
+                Err(rejection) => rejection,
+            },
+            Err(_) => ("408 Request Timeout", b"request timed out".to_vec()),
+        };
+
+        let mut stream = reader.into_inner();
+        let content_type = if status == "200 OK" {
+            "application/json"
+        } else {
+            "text/plain"
+        };
+        let head = format!(
+            "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\n",
+            body.len()
+        );
+        stream.write_all(head.as_bytes()).await?;
+        stream.write_all(b"Connection: close\r\n\r\n").await?;
+        stream.write_all(&body).await?;
+        stream.shutdown().await?;
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    async fn serve_unix_connection(&self, stream: UnixStream) -> Result<()> {
+        let (reader, mut writer) = stream.into_split();
+
+        let mut reader = BufReader::new(reader);
+        let message =
+            match tokio::time::timeout(self.read_timeout, read_message::<_, RemoteSignerEnvelope>(&mut reader)).await {
+                Ok(Ok(Some(envelope))) => match self.handle(&envelope).await {
+                    Ok(response) => RemoteSignerMessage::Envelope(response),
+                    Err(e) => RemoteSignerMessage::Rejected { error: e.to_string() },
+                },
+                Ok(Ok(None)) => return Ok(()),
+                Ok(Err(e)) => RemoteSignerMessage::Rejected { error: e.to_string() },
+                Err(_) => RemoteSignerMessage::Rejected {
+                    error: "request timed out".to_string(),
+                },
+            };
+
+        write_message(&mut writer, &message).await
+    }
+}
+
+// Reads the body of a POST request, or returns the status and message it's rejected with. The body is only allocated
+// as it's received.
+async fn read_http_request(
+    reader: &mut BufReader<TcpStream>,
+) -> Result<std::result::Result<Vec<u8>, (&'static str, Vec<u8>)>> {
+    let mut request_line = String::new();
+    let mut content_length = None;
+
+    {
+        let mut head = (&mut *reader).take(MAX_HTTP_HEAD_LENGTH);
+        head.read_line(&mut request_line).await?;
+
+        loop {
+            let mut header = String::new();
+            if head.read_line(&mut header).await? == 0 || header.trim().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                if name.trim().eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse::<usize>().ok();
+                }
+            }
+        }
+    }
+
+    if !request_line.starts_with("POST ") {
+        return Ok(Err((
+            "405 Method Not Allowed",
+            b"only POST requests are served".to_vec(),
+        )));
+    }
+
+    match content_length {
+        Some(length) if length <= MAX_HTTP_BODY_LENGTH => {
+            let mut body = Vec::new();
+            (&mut *reader).take(length as u64).read_to_end(&mut body).await?;
+            if body.len() < length {
+                return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+            }
+
+            Ok(Ok(body))
+        }
+        Some(_) => Ok(Err(("413 Payload Too Large", b"request too large".to_vec()))),
+        None => Ok(Err(("411 Length Required", b"missing content length".to_vec()))),
+    }
+}
@@ -12,10 +12,13 @@ use iota_types::block::{
     },
 };
 use serde::{Deserialize, Serialize};
-#[cfg(feature = "stronghold")]
 use zeroize::ZeroizeOnDrop;
 
-use crate::Result;
+#[cfg(feature = "remote_signer")]
+use crate::secret::remote::RemoteSignerEndpoint;
+#[cfg(feature = "stronghold")]
+use crate::stronghold::migrate::SnapshotKeyDerivation;
+use crate::{MnemonicLanguage, Result};
 
 /// Stronghold DTO to allow the creation of a Stronghold secret manager from bindings
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, ZeroizeOnDrop)]
@@ -29,7 +32,55 @@ pub struct StrongholdDto {
     /// The path for the Stronghold file
     #[serde(rename = "snapshotPath")]
     pub snapshot_path: String,
+    /// The name of the seed slot to use, the default one if not set
+    #[serde(rename = "seedSlot", default, skip_serializing_if = "Option::is_none")]
+    pub seed_slot: Option<String>,
+    /// The derivation of the key from the password, the default one if not set
+    #[serde(rename = "keyDerivation", default, skip_serializing_if = "Option::is_none")]
+    #[zeroize(skip)]
+    pub key_derivation: Option<SnapshotKeyDerivation>,
+}
+
+/// Remote signer DTO to allow the creation of a remote secret manager from bindings
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, ZeroizeOnDrop)]
+#[cfg(feature = "remote_signer")]
+#[cfg_attr(docsrs, doc(cfg(feature = "remote_signer")))]
+pub struct RemoteSignerDto {
+    /// The endpoint of the remote signer
+    #[zeroize(skip)]
+    pub endpoint: RemoteSignerEndpoint,
+    /// The hex-encoded key shared with the remote signer
+    pub key: String,
+    /// The timeout of the requests, in seconds
+    pub timeout: Option<u64>,
+}
+
+/// Mnemonic DTO to allow the creation of a mnemonic secret manager from bindings, either from the plain mnemonic or
+/// together with a BIP-39 passphrase and the language of its wordlist.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, ZeroizeOnDrop)]
+#[serde(untagged)]
+pub enum MnemonicDto {
+    /// An English mnemonic without passphrase.
+    Phrase(String),
+    /// A mnemonic with an optional passphrase.
+    WithPassphrase {
+        /// The mnemonic
+        mnemonic: String,
+        /// The BIP-39 passphrase
+        passphrase: Option<String>,
+        /// The language of the wordlist of the mnemonic
+        #[serde(default)]
+        #[zeroize(skip)]
+        language: MnemonicLanguage,
+    },
 }
+
+impl From<String> for MnemonicDto {
+    fn from(mnemonic: String) -> Self {
+        Self::Phrase(mnemonic)
+    }
+}
+
 /// An account address.
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct AccountAddress {
@@ -3,7 +3,7 @@
 
 //! Commonly used constants and utilities.
 
-use iota_stronghold::KeyProvider;
+use iota_stronghold::{KeyProvider, Location};
 use zeroize::Zeroize;
 
 /// Stronghold vault path to secrets.
@@ -16,6 +16,17 @@ pub(super) const SECRET_VAULT_PATH: &[u8] = b"iota-wallet-secret";
 /// The value has been hard-coded historically.
 pub(super) const SEED_RECORD_PATH: &[u8] = b"iota-wallet-seed";
 
+/// The name of the seed slot stored at the historical seed record path.
+pub const DEFAULT_SEED_SLOT: &str = "default";
+
+/// Stronghold store key to the names of the seed slots other than [`DEFAULT_SEED_SLOT`].
+///
+/// Stronghold can't list the records of a vault, so we keep track of them ourselves.
+pub(super) const SEED_SLOTS_STORE_KEY: &[u8] = b"iota-wallet-seed-slots";
+
+/// Stronghold record path to the key derived from a backup password.
+pub(super) const BACKUP_KEY_RECORD_PATH: &[u8] = b"iota-wallet-backup-key";
+
 /// Stronghold record path to a derived SLIP-10 private key.
 ///
 /// The value has been hard-coded historically.
@@ -26,15 +37,49 @@ pub(super) const DERIVE_OUTPUT_RECORD_PATH: &[u8] = b"iota-wallet-derived";
 /// The value has been hard-coded historically.
 pub(super) const PRIVATE_DATA_CLIENT_PATH: &[u8] = b"iota_seed";
 
+/// Get the Stronghold location of the seed of a slot.
+///
+/// The seed of [`DEFAULT_SEED_SLOT`] stays at [`SEED_RECORD_PATH`] so that existing snapshots keep working.
+pub(super) fn seed_slot_location(slot: &str) -> Location {
+    let record_path = if slot == DEFAULT_SEED_SLOT {
+        SEED_RECORD_PATH.to_vec()
+    } else {
+        [SEED_RECORD_PATH, b"/", slot.as_bytes()].concat()
+    };
+
+    Location::generic(SECRET_VAULT_PATH, record_path)
+}
+
 const PBKDF_SALT: &[u8] = b"wallet.rs";
 const PBKDF_ITER: usize = 100;
 
+/// The salt of the [`Pbkdf2Sha512Rounds100k`] key derivation.
+///
+/// [`Pbkdf2Sha512Rounds100k`]: super::migrate::SnapshotKeyDerivation::Pbkdf2Sha512Rounds100k
+const STRONG_PBKDF_SALT: &[u8] = b"iota.rs-stronghold";
+/// The number of rounds of the [`Pbkdf2Sha512Rounds100k`] key derivation.
+///
+/// [`Pbkdf2Sha512Rounds100k`]: super::migrate::SnapshotKeyDerivation::Pbkdf2Sha512Rounds100k
+const STRONG_PBKDF_ITER: usize = 100_000;
+
 /// Hash a password, deriving a key, for accessing Stronghold.
 pub(super) fn key_provider_from_password(password: &str) -> KeyProvider {
+    key_provider_from_pbkdf2(password, PBKDF_SALT, PBKDF_ITER)
+}
+
+/// Hash a password with more rounds, deriving a key, for accessing a Stronghold snapshot migrated to the
+/// [`Pbkdf2Sha512Rounds100k`] key derivation.
+///
+/// [`Pbkdf2Sha512Rounds100k`]: super::migrate::SnapshotKeyDerivation::Pbkdf2Sha512Rounds100k
+pub(super) fn strong_key_provider_from_password(password: &str) -> KeyProvider {
+    key_provider_from_pbkdf2(password, STRONG_PBKDF_SALT, STRONG_PBKDF_ITER)
+}
+
+fn key_provider_from_pbkdf2(password: &str, salt: &[u8], rounds: usize) -> KeyProvider {
     let mut buffer = [0u8; 64];
 
     // Safe to unwrap because rounds > 0.
-    crypto::keys::pbkdf::PBKDF2_HMAC_SHA512(password.as_bytes(), PBKDF_SALT, PBKDF_ITER, buffer.as_mut()).unwrap();
+    crypto::keys::pbkdf::PBKDF2_HMAC_SHA512(password.as_bytes(), salt, rounds, buffer.as_mut()).unwrap();
 
     // PANIC: the passphrase length is guaranteed to be 32.
     let key_provider = KeyProvider::with_passphrase_truncated(buffer[..32].to_vec()).unwrap();
@@ -0,0 +1,516 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Detection of the version of Stronghold snapshots and their migration to another key derivation.
+//!
+//! The version of a snapshot is made of the version of its file format, which is owned by [`iota_stronghold`], and of
+//! the derivation of its key from the password, see [`SnapshotVersion`]. [`StrongholdAdapter`] never migrates
+//! snapshots by itself: it fails to load a snapshot with another key derivation than its own with
+//! [`Error::StrongholdMigrationRequired`], and the snapshot has to be migrated explicitly with [`migrate_snapshot()`].
+//!
+//! [`StrongholdAdapter`]: super::StrongholdAdapter
+
+use std::{
+    fs::File,
+    io::{ErrorKind, Read},
+    ops::Deref,
+    path::{Path, PathBuf},
+};
+
+use crypto::ciphers::chacha;
+use iota_stronghold::{KeyProvider, SnapshotPath, Stronghold};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use super::common::{key_provider_from_password, strong_key_provider_from_password, PRIVATE_DATA_CLIENT_PATH};
+use crate::{Error, Result};
+
+/// Magic bytes at the start of a Stronghold snapshot file.
+const SNAPSHOT_MAGIC: &[u8; 5] = b"PARTI";
+
+/// The version of the file format of a Stronghold snapshot, stored after its magic bytes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SnapshotFileVersion {
+    /// Written by [`iota_stronghold`] up to 1.x, encrypted with XChaCha20-Poly1305 under the key derived from the
+    /// password. This is the version loaded and written by the [`iota_stronghold`] version in use.
+    V2,
+    /// Written by [`iota_stronghold`] 2.x, encrypted with age under a key derived from the password with scrypt. It
+    /// can't be loaded by the [`iota_stronghold`] version in use.
+    V3,
+}
+
+impl SnapshotFileVersion {
+    /// Returns the version number, as stored in the header of the snapshot.
+    pub const fn number(self) -> u16 {
+        match self {
+            Self::V2 => 2,
+            Self::V3 => 3,
+        }
+    }
+}
+
+impl TryFrom<u16> for SnapshotFileVersion {
+    type Error = Error;
+
+    fn try_from(number: u16) -> Result<Self> {
+        match number {
+            2 => Ok(Self::V2),
+            3 => Ok(Self::V3),
+            _ => Err(Error::StrongholdUnsupportedSnapshotFileVersion(number)),
+        }
+    }
+}
+
+/// The derivation of the key of a Stronghold snapshot from its password.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SnapshotKeyDerivation {
+    /// 100 rounds of PBKDF2-HMAC-SHA512 with the salt `wallet.rs`, used by default.
+    Pbkdf2Sha512Rounds100,
+    /// 100 000 rounds of PBKDF2-HMAC-SHA512 with the salt `iota.rs-stronghold`.
+    Pbkdf2Sha512Rounds100k,
+}
+
+impl Default for SnapshotKeyDerivation {
+    fn default() -> Self {
+        Self::Pbkdf2Sha512Rounds100
+    }
+}
+
+impl SnapshotKeyDerivation {
+    /// Derive the key of a snapshot from its password.
+    pub(super) fn key_provider(self, password: &str) -> KeyProvider {
+        match self {
+            Self::Pbkdf2Sha512Rounds100 => key_provider_from_password(password),
+            Self::Pbkdf2Sha512Rounds100k => strong_key_provider_from_password(password),
+        }
+    }
+}
+
+/// The version of a Stronghold snapshot.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotVersion {
+    /// The version of the file format, as numbered by [`iota_stronghold`].
+    pub file_version: SnapshotFileVersion,
+    /// The derivation of the key from the password.
+    pub key_derivation: SnapshotKeyDerivation,
+}
+
+/// The version of the snapshots written by a [`StrongholdAdapter`](super::StrongholdAdapter) without a configured key
+/// derivation.
+pub const DEFAULT_SNAPSHOT_VERSION: SnapshotVersion = SnapshotVersion {
+    file_version: SnapshotFileVersion::V2,
+    key_derivation: SnapshotKeyDerivation::Pbkdf2Sha512Rounds100,
+};
+
+/// Where [`migrate_snapshot()`] writes the migrated snapshot.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SnapshotMigrationTarget {
+    /// Overwrite the snapshot, after copying it to a backup at its path with `.bak` appended.
+    InPlace,
+    /// Write the migrated snapshot to a new path, leaving the original one untouched.
+    NewPath(PathBuf),
+}
+
+/// The outcome of [`migrate_snapshot()`].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotMigration {
+    /// The version the snapshot has been migrated from.
+    pub from: SnapshotVersion,
+    /// The version the snapshot has been migrated to.
+    pub to: SnapshotVersion,
+    /// The path of the migrated snapshot.
+    pub path: PathBuf,
+    /// The path of the snapshot as it was before the migration: the backup for an in place migration, the original
+    /// snapshot otherwise.
+    pub backup_path: PathBuf,
+}
+
+/// Read the version of the file format of a snapshot from its header, without decrypting it.
+///
+/// Returns [`Error::StrongholdUnsupportedSnapshotFileVersion`] if the version isn't known.
+pub fn snapshot_file_version<P: AsRef<Path>>(path: P) -> Result<SnapshotFileVersion> {
+    let mut header = [0u8; 7];
+
+    File::open(path)?
+        .read_exact(&mut header)
+        .map_err(|err| match err.kind() {
+            ErrorKind::UnexpectedEof => Error::StrongholdInvalidSnapshot,
+            _ => err.into(),
+        })?;
+
+    if !header.starts_with(SNAPSHOT_MAGIC) {
+        return Err(Error::StrongholdInvalidSnapshot);
+    }
+
+    SnapshotFileVersion::try_from(u16::from_le_bytes([header[5], header[6]]))
+}
+
+/// Detect the version of a snapshot by decrypting it with the keys derived from `password` by each key derivation.
+///
+/// Returns [`Error::StrongholdUnsupportedSnapshotFileVersion`] if the file format isn't supported by the
+/// [`iota_stronghold`] version in use, and [`Error::StrongholdInvalidPassword`] if the password is wrong.
+pub fn snapshot_version<P: AsRef<Path>>(path: P, password: &str) -> Result<SnapshotVersion> {
+    let path = path.as_ref();
+
+    let file_version = check_snapshot_file_version(path)?;
+
+    // Try the default key derivation first, as it's the most likely one.
+    for key_derivation in [
+        SnapshotKeyDerivation::Pbkdf2Sha512Rounds100,
+        SnapshotKeyDerivation::Pbkdf2Sha512Rounds100k,
+    ] {
+        if load_snapshot(path, &key_derivation.key_provider(password))?.is_some() {
+            return Ok(SnapshotVersion {
+                file_version,
+                key_derivation,
+            });
+        }
+    }
+
+    Err(Error::StrongholdInvalidPassword)
+}
+
+/// Migrate a snapshot to the key derivation `key_derivation`, re-encrypting it with the key derived from
+/// `new_password`, or from `password` if `None`.
+///
+/// The migrated snapshot is written according to `target`. When migrating in place, the snapshot is copied to a
+/// backup first, which is overwritten if it already exists, and restored from it if the migration fails. The data
+/// saved via the [`StorageProvider`] interface is re-encrypted as well.
+///
+/// A [`StrongholdAdapter`](super::StrongholdAdapter) loads the migrated snapshot if it's configured with the same key
+/// derivation.
+///
+/// [`StorageProvider`]: crate::storage::StorageProvider
+pub fn migrate_snapshot<P: AsRef<Path>>(
+    path: P,
+    password: &str,
+    target: SnapshotMigrationTarget,
+    key_derivation: SnapshotKeyDerivation,
+    new_password: Option<&str>,
+) -> Result<SnapshotMigration> {
+    let path = path.as_ref();
+    let from = snapshot_version(path, password)?;
+    let to = SnapshotVersion {
+        file_version: from.file_version,
+        key_derivation,
+    };
+
+    let key_provider = from.key_derivation.key_provider(password);
+    let new_key_provider = key_derivation.key_provider(new_password.unwrap_or(password));
+
+    // The version has just been detected with this key, so it can only fail to decrypt the snapshot if the file has
+    // been changed in the meantime.
+    let stronghold = load_snapshot(path, &key_provider)?.ok_or(Error::StrongholdInvalidPassword)?;
+    re_encrypt_store(&stronghold, &key_provider, &new_key_provider)?;
+
+    let (new_path, backup_path) = match target {
+        SnapshotMigrationTarget::InPlace => {
+            let mut backup_path = path.as_os_str().to_owned();
+            backup_path.push(".bak");
+            let backup_path = PathBuf::from(backup_path);
+
+            std::fs::copy(path, &backup_path)?;
+
+            (path.to_path_buf(), backup_path)
+        }
+        SnapshotMigrationTarget::NewPath(new_path) => (new_path, path.to_path_buf()),
+    };
+
+    if let Err(err) = write_snapshot(&stronghold, &new_path, &new_key_provider) {
+        if new_path == path {
+            std::fs::copy(&backup_path, path)?;
+        }
+
+        return Err(err);
+    }
+
+    Ok(SnapshotMigration {
+        from,
+        to,
+        path: new_path,
+        backup_path,
+    })
+}
+
+/// Check that the file format of a snapshot is supported by the [`iota_stronghold`] version in use.
+pub(super) fn check_snapshot_file_version(path: &Path) -> Result<SnapshotFileVersion> {
+    match snapshot_file_version(path)? {
+        SnapshotFileVersion::V2 => Ok(SnapshotFileVersion::V2),
+        version => Err(Error::StrongholdUnsupportedSnapshotFileVersion(version.number())),
+    }
+}
+
+/// Load a snapshot into a new Stronghold, `None` if the key doesn't decrypt it.
+fn load_snapshot(path: &Path, key_provider: &KeyProvider) -> Result<Option<Stronghold>> {
+    let stronghold = Stronghold::default();
+
+    match stronghold.load_client_from_snapshot(PRIVATE_DATA_CLIENT_PATH, key_provider, &SnapshotPath::from_path(path)) {
+        Ok(_) => Ok(Some(stronghold)),
+        // Matching the error string is not ideal but stronghold doesn't wrap the error types at the moment.
+        Err(iota_stronghold::ClientError::Inner(ref err_msg)) if err_msg.to_string().contains("XCHACHA20-POLY1305") => {
+            Ok(None)
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Write a snapshot and check that it can be loaded again with the key.
+fn write_snapshot(stronghold: &Stronghold, path: &Path, key_provider: &KeyProvider) -> Result<()> {
+    stronghold.commit_with_keyprovider(&SnapshotPath::from_path(path), key_provider)?;
+
+    load_snapshot(path, key_provider)?
+        .map(|_| ())
+        .ok_or(Error::StrongholdInvalidPassword)
+}
+
+/// Re-encrypt the values of the Stronghold store, which are encrypted with the key of the snapshot.
+fn re_encrypt_store(stronghold: &Stronghold, key_provider: &KeyProvider, new_key_provider: &KeyProvider) -> Result<()> {
+    let store = stronghold.get_client(PRIVATE_DATA_CLIENT_PATH)?.store();
+    let key = key_provider.try_unlock()?;
+    let new_key = new_key_provider.try_unlock()?;
+
+    for k in store.keys()? {
+        if let Some(data) = store.get(&k)? {
+            let value = Zeroizing::new(chacha::aead_decrypt(key.borrow().deref(), &data)?);
+
+            store.insert(k, chacha::aead_encrypt(new_key.borrow().deref(), &value)?, None)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use iota_stronghold::procedures;
+
+    use super::*;
+    use crate::{
+        constants::IOTA_COIN_TYPE,
+        secret::SecretManage,
+        storage::StorageProvider,
+        stronghold::{common::seed_slot_location, StrongholdAdapter, DEFAULT_SEED_SLOT},
+    };
+
+    const MNEMONIC: &str = "giant dynamic museum toddler six deny defense ostrich bomb access mercy blood explain muscle shoot shallow glad autumn author calm heavy hawk abuse rally";
+
+    const STRONG_SNAPSHOT_VERSION: SnapshotVersion = SnapshotVersion {
+        file_version: SnapshotFileVersion::V2,
+        key_derivation: SnapshotKeyDerivation::Pbkdf2Sha512Rounds100k,
+    };
+
+    /// Create a snapshot with the default key derivation, holding a mnemonic and a value in the store.
+    fn create_snapshot(path: &str, password: &str) {
+        let key_provider = key_provider_from_password(password);
+        let stronghold = Stronghold::default();
+        let client = stronghold.create_client(PRIVATE_DATA_CLIENT_PATH).unwrap();
+
+        client
+            .execute_procedure(procedures::BIP39Recover {
+                mnemonic: MNEMONIC.to_string(),
+                passphrase: None,
+                output: seed_slot_location(DEFAULT_SEED_SLOT),
+            })
+            .unwrap();
+
+        let value = chacha::aead_encrypt(key_provider.try_unlock().unwrap().borrow().deref(), b"value").unwrap();
+        client.store().insert(b"key".to_vec(), value, None).unwrap();
+
+        stronghold
+            .commit_with_keyprovider(&SnapshotPath::from_path(path), &key_provider)
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn migrate_snapshot_in_place() {
+        let snapshot_path = "test_migrate_snapshot_in_place.stronghold";
+        let backup_path = "test_migrate_snapshot_in_place.stronghold.bak";
+        std::fs::remove_file(backup_path).unwrap_or(());
+        create_snapshot(snapshot_path, "drowssap");
+
+        assert_eq!(snapshot_file_version(snapshot_path).unwrap(), SnapshotFileVersion::V2);
+        assert_eq!(
+            snapshot_version(snapshot_path, "drowssap").unwrap(),
+            DEFAULT_SNAPSHOT_VERSION
+        );
+        assert!(matches!(
+            snapshot_version(snapshot_path, "password"),
+            Err(Error::StrongholdInvalidPassword)
+        ));
+
+        let migration = migrate_snapshot(
+            snapshot_path,
+            "drowssap",
+            SnapshotMigrationTarget::InPlace,
+            SnapshotKeyDerivation::Pbkdf2Sha512Rounds100k,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(migration.from, DEFAULT_SNAPSHOT_VERSION);
+        assert_eq!(migration.to, STRONG_SNAPSHOT_VERSION);
+        assert_eq!(migration.path, PathBuf::from(snapshot_path));
+        assert_eq!(migration.backup_path, PathBuf::from(backup_path));
+        assert_eq!(
+            snapshot_version(backup_path, "drowssap").unwrap(),
+            DEFAULT_SNAPSHOT_VERSION
+        );
+        assert_eq!(
+            snapshot_version(snapshot_path, "drowssap").unwrap(),
+            STRONG_SNAPSHOT_VERSION
+        );
+
+        let mut stronghold_adapter = StrongholdAdapter::builder()
+            .password("drowssap")
+            .key_derivation(SnapshotKeyDerivation::Pbkdf2Sha512Rounds100k)
+            .build(snapshot_path)
+            .unwrap();
+
+        let addresses = stronghold_adapter
+            .generate_addresses(IOTA_COIN_TYPE, 0, 0..1, false, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            addresses[0].to_bech32("atoi"),
+            "atoi1qpszqzadsym6wpppd6z037dvlejmjuke7s24hm95s9fg9vpua7vluehe53e".to_string()
+        );
+        assert_eq!(stronghold_adapter.get(b"key").await.unwrap(), Some(b"value".to_vec()));
+
+        std::fs::remove_file(snapshot_path).unwrap_or(());
+        std::fs::remove_file(backup_path).unwrap_or(());
+    }
+
+    #[tokio::test]
+    async fn migration_required_on_load() {
+        let snapshot_path = "test_migration_required_on_load.stronghold";
+        let backup_path = "test_migration_required_on_load.stronghold.bak";
+        std::fs::remove_file(backup_path).unwrap_or(());
+        create_snapshot(snapshot_path, "drowssap");
+
+        // A snapshot with another key derivation than the one of the adapter isn't migrated when it's loaded.
+        assert!(matches!(
+            StrongholdAdapter::builder()
+                .password("drowssap")
+                .key_derivation(SnapshotKeyDerivation::Pbkdf2Sha512Rounds100k)
+                .build(snapshot_path),
+            Err(Error::StrongholdMigrationRequired(version)) if version == DEFAULT_SNAPSHOT_VERSION
+        ));
+        assert!(!Path::new(backup_path).exists());
+        assert_eq!(
+            snapshot_version(snapshot_path, "drowssap").unwrap(),
+            DEFAULT_SNAPSHOT_VERSION
+        );
+
+        // A wrong password is still reported as such.
+        assert!(matches!(
+            StrongholdAdapter::builder().password("password").build(snapshot_path),
+            Err(Error::StrongholdInvalidPassword)
+        ));
+
+        let mut stronghold_adapter = StrongholdAdapter::builder()
+            .password("drowssap")
+            .build(snapshot_path)
+            .unwrap();
+        assert_eq!(stronghold_adapter.get(b"key").await.unwrap(), Some(b"value".to_vec()));
+
+        std::fs::remove_file(snapshot_path).unwrap_or(());
+    }
+
+    #[tokio::test]
+    async fn migrate_snapshot_to_new_path() {
+        let snapshot_path = "test_migrate_snapshot_to_new_path.stronghold";
+        let new_snapshot_path = "test_migrate_snapshot_to_new_path_migrated.stronghold";
+        create_snapshot(snapshot_path, "drowssap");
+
+        let migration = migrate_snapshot(
+            snapshot_path,
+            "drowssap",
+            SnapshotMigrationTarget::NewPath(new_snapshot_path.into()),
+            SnapshotKeyDerivation::Pbkdf2Sha512Rounds100k,
+            Some("password"),
+        )
+        .unwrap();
+
+        assert_eq!(migration.from, DEFAULT_SNAPSHOT_VERSION);
+        assert_eq!(migration.to, STRONG_SNAPSHOT_VERSION);
+        assert_eq!(migration.path, PathBuf::from(new_snapshot_path));
+        assert_eq!(migration.backup_path, PathBuf::from(snapshot_path));
+        assert_eq!(
+            snapshot_version(snapshot_path, "drowssap").unwrap(),
+            DEFAULT_SNAPSHOT_VERSION
+        );
+        assert_eq!(
+            snapshot_version(new_snapshot_path, "password").unwrap(),
+            STRONG_SNAPSHOT_VERSION
+        );
+        assert!(matches!(
+            snapshot_version(new_snapshot_path, "drowssap"),
+            Err(Error::StrongholdInvalidPassword)
+        ));
+
+        let mut stronghold_adapter = StrongholdAdapter::builder()
+            .password("password")
+            .key_derivation(SnapshotKeyDerivation::Pbkdf2Sha512Rounds100k)
+            .build(new_snapshot_path)
+            .unwrap();
+        assert_eq!(stronghold_adapter.get(b"key").await.unwrap(), Some(b"value".to_vec()));
+
+        // Migrating back to the default key derivation works the same way.
+        let migration = migrate_snapshot(
+            new_snapshot_path,
+            "password",
+            SnapshotMigrationTarget::InPlace,
+            SnapshotKeyDerivation::default(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(migration.from, STRONG_SNAPSHOT_VERSION);
+        assert_eq!(migration.to, DEFAULT_SNAPSHOT_VERSION);
+
+        let mut stronghold_adapter = StrongholdAdapter::builder()
+            .password("password")
+            .build(new_snapshot_path)
+            .unwrap();
+        assert_eq!(stronghold_adapter.get(b"key").await.unwrap(), Some(b"value".to_vec()));
+
+        std::fs::remove_file(snapshot_path).unwrap_or(());
+        std::fs::remove_file(new_snapshot_path).unwrap_or(());
+        std::fs::remove_file(migration.backup_path).unwrap_or(());
+    }
+
+    #[test]
+    fn unsupported_snapshot() {
+        let snapshot_path = "test_unsupported_snapshot.stronghold";
+
+        // A snapshot written by iota_stronghold 2.x.
+        std::fs::write(snapshot_path, [&SNAPSHOT_MAGIC[..], &[3, 0], &[0; 128]].concat()).unwrap();
+        assert_eq!(snapshot_file_version(snapshot_path).unwrap(), SnapshotFileVersion::V3);
+        assert!(matches!(
+            snapshot_version(snapshot_path, "drowssap"),
+            Err(Error::StrongholdUnsupportedSnapshotFileVersion(3))
+        ));
+        assert!(matches!(
+            StrongholdAdapter::builder().password("drowssap").build(snapshot_path),
+            Err(Error::StrongholdUnsupportedSnapshotFileVersion(3))
+        ));
+
+        // A snapshot with an unknown version.
+        std::fs::write(snapshot_path, [&SNAPSHOT_MAGIC[..], &[9, 0], &[0; 128]].concat()).unwrap();
+        assert!(matches!(
+            snapshot_file_version(snapshot_path),
+            Err(Error::StrongholdUnsupportedSnapshotFileVersion(9))
+        ));
+
+        std::fs::write(snapshot_path, b"not a snapshot").unwrap();
+        assert!(matches!(
+            snapshot_file_version(snapshot_path),
+            Err(Error::StrongholdInvalidSnapshot)
+        ));
+
+        std::fs::remove_file(snapshot_path).unwrap_or(());
+    }
+}
@@ -37,6 +37,14 @@
 //! [`read_stronghold_snapshot()`] or [`write_stronghold_snapshot()`]. The latter can be used to create a snapshot file
 //! after creating a [`StrongholdAdapter`] with a non-existent snapshot path.
 //!
+//! A snapshot can hold several seeds in named slots, see [`seed_slot()`]. A [`StrongholdAdapter`] generates
+//! addresses and signs with the seed of its own slot, [`DEFAULT_SEED_SLOT`] unless configured otherwise. Slots can be
+//! listed, renamed, deleted and exported to or imported from password-encrypted backups to move seeds between
+//! snapshots.
+//!
+//! Snapshots are encrypted with a key derived from the password, see [`key_derivation()`]. Snapshots with another key
+//! derivation aren't loaded until they're migrated explicitly with the [`migrate`] module.
+//!
 //! [Stronghold]: iota_stronghold
 //! [`StorageProvider`]: crate::storage::StorageProvider
 //! [`SecretManage`]: crate::secret::SecretManage
@@ -45,9 +53,13 @@
 //! [`set_timeout()`]: self::StrongholdAdapter::set_timeout()
 //! [`read_stronghold_snapshot()`]: self::StrongholdAdapter::read_stronghold_snapshot()
 //! [`write_stronghold_snapshot()`]: self::StrongholdAdapter::write_stronghold_snapshot()
+//! [`seed_slot()`]: self::StrongholdAdapterBuilder::seed_slot()
+//! [`key_derivation()`]: self::StrongholdAdapterBuilder::key_derivation()
 
 mod common;
+pub mod migrate;
 mod secret;
+mod slots;
 mod storage;
 
 use std::{
@@ -62,7 +74,8 @@ use log::{debug, error, warn};
 use tokio::{sync::Mutex, task::JoinHandle};
 use zeroize::Zeroizing;
 
-use self::common::PRIVATE_DATA_CLIENT_PATH;
+pub use self::common::DEFAULT_SEED_SLOT;
+use self::{common::PRIVATE_DATA_CLIENT_PATH, migrate::SnapshotKeyDerivation};
 use crate::{storage::StorageProvider, Error, Result};
 
 /// A wrapper on [Stronghold].
@@ -79,11 +92,13 @@ pub struct StrongholdAdapter {
     ///
     /// Note that in [`StrongholdAdapterBuilder`] there isn't a `key()` setter, because we don't want a user to
     /// directly set this field. Instead, [`password()`] is provided to hash a user-input password string and
-    /// derive a key from it.
+    /// derive a key from it. The builder keeps the password until [`build()`] derives the key and checks it against
+    /// the snapshot.
     ///
     /// [`password()`]: self::StrongholdAdapterBuilder::password()
+    /// [`build()`]: self::StrongholdAdapterBuilder::build()
     #[builder(setter(custom))]
-    #[builder(field(type = "Option<KeyProvider>"))]
+    #[builder(field(type = "Option<Zeroizing<String>>"))]
     key_provider: Arc<Mutex<Option<KeyProvider>>>,
 
     /// An interval of time, after which `key` will be cleared from the memory.
@@ -105,19 +120,39 @@ pub struct StrongholdAdapter {
     /// The path to a Stronghold snapshot file.
     #[builder(setter(skip))]
     pub snapshot_path: PathBuf,
+
+    /// The name of the seed slot to generate addresses and sign with.
+    ///
+    /// If not set, [`DEFAULT_SEED_SLOT`] is used, which is where snapshots created before seed slots keep their seed.
+    #[builder(setter(into))]
+    seed_slot: String,
+
+    /// The derivation of the key from the password.
+    ///
+    /// If not set, [`SnapshotKeyDerivation::Pbkdf2Sha512Rounds100`] is used, which all snapshots have unless they've
+    /// been migrated with [`migrate_snapshot()`](self::migrate::migrate_snapshot()).
+    key_derivation: SnapshotKeyDerivation,
 }
 
 fn check_or_create_snapshot(
     stronghold: &Stronghold,
     key_provider: &KeyProvider,
-    snapshot_path: &SnapshotPath,
+    key_derivation: SnapshotKeyDerivation,
+    password: &str,
+    path: &Path,
 ) -> Result<()> {
-    let result = stronghold.load_client_from_snapshot(PRIVATE_DATA_CLIENT_PATH, key_provider, snapshot_path);
+    // Snapshots written by another version of Stronghold can't be loaded.
+    if path.exists() {
+        self::migrate::check_snapshot_file_version(path)?;
+    }
+
+    let snapshot_path = SnapshotPath::from_path(path);
+    let result = stronghold.load_client_from_snapshot(PRIVATE_DATA_CLIENT_PATH, key_provider, &snapshot_path);
 
     match result {
         Err(iota_stronghold::ClientError::SnapshotFileMissing(_)) => {
             stronghold.create_client(PRIVATE_DATA_CLIENT_PATH)?;
-            stronghold.commit_with_keyprovider(snapshot_path, key_provider)?;
+            stronghold.commit_with_keyprovider(&snapshot_path, key_provider)?;
         }
         Err(iota_stronghold::ClientError::ClientAlreadyLoaded(_)) => {
             stronghold.get_client(PRIVATE_DATA_CLIENT_PATH)?;
@@ -125,7 +160,14 @@ fn check_or_create_snapshot(
         Err(iota_stronghold::ClientError::Inner(ref err_msg)) => {
             // Matching the error string is not ideal but stronghold doesn't wrap the error types at the moment.
             if err_msg.to_string().contains("XCHACHA20-POLY1305") {
-                return Err(Error::StrongholdInvalidPassword);
+                // The password may still be right if the snapshot has another key derivation.
+                return Err(match self::migrate::snapshot_version(path, password) {
+                    Ok(version) if version.key_derivation != key_derivation => {
+                        Error::StrongholdMigrationRequired(version)
+                    }
+                    Ok(_) => Error::StrongholdInvalidPassword,
+                    Err(err) => err,
+                });
             }
         }
         _ => {}
@@ -139,7 +181,7 @@ impl StrongholdAdapterBuilder {
     /// Use an user-input password string to derive a key to use Stronghold.
     pub fn password(mut self, password: &str) -> Self {
         // Note that derive_builder always adds another layer of Option<T>.
-        self.key_provider = Some(self::common::key_provider_from_password(password));
+        self.key_provider = Some(Zeroizing::new(password.to_string()));
 
         self
     }
@@ -161,13 +203,27 @@ impl StrongholdAdapterBuilder {
     pub fn build<P: AsRef<Path>>(mut self, snapshot_path: P) -> Result<StrongholdAdapter> {
         // In any case, Stronghold - as a necessary component - needs to be present at this point.
         let stronghold = self.stronghold.unwrap_or_default();
-
-        if let Some(key_provider) = &self.key_provider {
-            check_or_create_snapshot(&stronghold, key_provider, &SnapshotPath::from_path(&snapshot_path))?;
-        }
-
-        let has_key_provider = self.key_provider.is_some();
-        let key_provider = Arc::new(Mutex::new(self.key_provider));
+        let key_derivation = self.key_derivation.unwrap_or_default();
+
+        let key_provider = self
+            .key_provider
+            .as_deref()
+            .map(|password| {
+                let key_provider = key_derivation.key_provider(password);
+
+                check_or_create_snapshot(
+                    &stronghold,
+                    &key_provider,
+                    key_derivation,
+                    password,
+                    snapshot_path.as_ref(),
+                )
+                .map(|_| key_provider)
+            })
+            .transpose()?;
+
+        let has_key_provider = key_provider.is_some();
+        let key_provider = Arc::new(Mutex::new(key_provider));
         let stronghold = Arc::new(Mutex::new(stronghold));
 
         // If both `key` and `timeout` are set, then we spawn the task and keep its join handle.
@@ -203,6 +259,8 @@ impl StrongholdAdapterBuilder {
             timeout: self.timeout.unwrap_or(None),
             timeout_task: self.timeout_task.unwrap_or_else(|| Arc::new(Mutex::new(None))),
             snapshot_path: snapshot_path.as_ref().to_path_buf(),
+            seed_slot: self.seed_slot.unwrap_or_else(|| DEFAULT_SEED_SLOT.to_string()),
+            key_derivation,
         })
     }
 }
@@ -227,7 +285,7 @@ impl StrongholdAdapter {
     pub async fn set_password(&mut self, password: &str) -> Result<()> {
         let mut key_provider_guard = self.key_provider.lock().await;
 
-        let key_provider = self::common::key_provider_from_password(password);
+        let key_provider = self.key_derivation.key_provider(password);
 
         if let Some(old_key_provider) = &*key_provider_guard {
             if old_key_provider.try_unlock()? != key_provider.try_unlock()? {
@@ -235,10 +293,15 @@ impl StrongholdAdapter {
             }
         }
 
-        let snapshot_path = SnapshotPath::from_path(&self.snapshot_path);
         let stronghold = self.stronghold.lock().await;
 
-        check_or_create_snapshot(&stronghold, &key_provider, &snapshot_path)?;
+        check_or_create_snapshot(
+            &stronghold,
+            &key_provider,
+            self.key_derivation,
+            password,
+            &self.snapshot_path,
+        )?;
 
         *key_provider_guard = Some(key_provider);
         drop(key_provider_guard);
@@ -331,7 +394,7 @@ impl StrongholdAdapter {
         let old_key_provider = {
             let mut lock = self.key_provider.lock().await;
             let old_key_provider = lock.take();
-            *lock = Some(self::common::key_provider_from_password(new_password));
+            *lock = Some(self.key_derivation.key_provider(new_password));
 
             old_key_provider
         };
@@ -406,6 +469,11 @@ impl StrongholdAdapter {
         debug!("cleared stronghold key");
     }
 
+    /// Get the derivation of the key from the password.
+    pub fn key_derivation(&self) -> SnapshotKeyDerivation {
+        self.key_derivation
+    }
+
     /// Get timeout for the key clearing task.
     pub fn get_timeout(&self) -> Option<Duration> {
         self.timeout
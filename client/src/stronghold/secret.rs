@@ -16,15 +16,17 @@ use iota_types::block::{
     signature::{Ed25519Signature, Signature},
     unlock::{SignatureUnlock, Unlock},
 };
+use unicode_normalization::UnicodeNormalization;
 use zeroize::Zeroize;
 
 use super::{
-    common::{DERIVE_OUTPUT_RECORD_PATH, PRIVATE_DATA_CLIENT_PATH, SECRET_VAULT_PATH, SEED_RECORD_PATH},
+    common::{seed_slot_location, DERIVE_OUTPUT_RECORD_PATH, PRIVATE_DATA_CLIENT_PATH, SECRET_VAULT_PATH},
     StrongholdAdapter,
 };
 use crate::{
     api::RemainderData,
     secret::{types::InputSigningData, GenerateAddressOptions, SecretManage},
+    utils::{normalize_mnemonic, verify_mnemonic, MnemonicLanguage},
     Error, Result,
 };
 
@@ -48,7 +50,7 @@ impl SecretManage for StrongholdAdapter {
         }
 
         // Stronghold arguments.
-        let seed_location = Slip10DeriveInput::Seed(Location::generic(SECRET_VAULT_PATH, SEED_RECORD_PATH));
+        let seed_location = Slip10DeriveInput::Seed(seed_slot_location(&self.seed_slot));
         let derive_location = Location::generic(SECRET_VAULT_PATH, DERIVE_OUTPUT_RECORD_PATH);
 
         // Addresses to return.
@@ -103,7 +105,7 @@ impl SecretManage for StrongholdAdapter {
         }
 
         // Stronghold arguments.
-        let seed_location = Slip10DeriveInput::Seed(Location::generic(SECRET_VAULT_PATH, SEED_RECORD_PATH));
+        let seed_location = Slip10DeriveInput::Seed(seed_slot_location(&self.seed_slot));
         let derive_location = Location::generic(SECRET_VAULT_PATH, DERIVE_OUTPUT_RECORD_PATH);
 
         // Stronghold asks for an older version of [Chain], so we have to perform a conversion here.
@@ -202,23 +204,40 @@ impl StrongholdAdapter {
             })?)
     }
 
-    /// Store a mnemonic into the Stronghold vault.
-    pub async fn store_mnemonic(&mut self, mut mnemonic: String) -> Result<()> {
+    /// Store a mnemonic into the seed slot of this adapter in the Stronghold vault.
+    pub async fn store_mnemonic(&mut self, mnemonic: String) -> Result<()> {
+        self.store_mnemonic_with_passphrase(mnemonic, None, MnemonicLanguage::English)
+            .await
+    }
+
+    /// Store a mnemonic of the given language into the seed slot of this adapter in the Stronghold vault, with an
+    /// optional BIP-39 passphrase from which the seed is derived together with the mnemonic.
+    pub async fn store_mnemonic_with_passphrase(
+        &mut self,
+        mut mnemonic: String,
+        passphrase: Option<String>,
+        language: MnemonicLanguage,
+    ) -> Result<()> {
         // The key needs to be supplied first.
         if self.key_provider.lock().await.is_none() {
             return Err(Error::StrongholdKeyCleared);
         };
 
         // Stronghold arguments.
-        let output = Location::generic(SECRET_VAULT_PATH, SEED_RECORD_PATH);
+        let output = seed_slot_location(&self.seed_slot);
 
-        // Trim the mnemonic, in case it hasn't been, as otherwise the restored seed would be wrong.
-        let trimmed_mnemonic = mnemonic.trim().to_string();
+        // Normalize the mnemonic and the passphrase, in case they haven't been, as otherwise the restored seed would be
+        // wrong.
+        let normalized_mnemonic = normalize_mnemonic(&mnemonic);
         mnemonic.zeroize();
+        let passphrase = passphrase.map(|mut passphrase| {
+            let normalized_passphrase = passphrase.nfkd().collect::<String>();
+            passphrase.zeroize();
+            normalized_passphrase
+        });
 
         // Check if the mnemonic is valid.
-        crypto::keys::bip39::wordlist::verify(&trimmed_mnemonic, &crypto::keys::bip39::wordlist::ENGLISH)
-            .map_err(|e| crate::Error::InvalidMnemonic(format!("{e:?}")))?;
+        verify_mnemonic(&normalized_mnemonic, language)?;
 
         // We need to check if there has been a mnemonic stored in Stronghold or not to prevent overwriting it.
         if self
@@ -232,7 +251,11 @@ impl StrongholdAdapter {
         }
 
         // Execute the BIP-39 recovery procedure to put it into the vault (in memory).
-        self.bip39_recover(trimmed_mnemonic, None, output).await?;
+        self.bip39_recover(normalized_mnemonic, passphrase, output).await?;
+
+        // Keep track of the slot, as Stronghold can't list the records of a vault.
+        let seed_slot = self.seed_slot.clone();
+        self.add_to_seed_slot_index(&seed_slot).await?;
 
         // Persist Stronghold to the disk
         self.write_stronghold_snapshot(None).await?;
@@ -0,0 +1,408 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Management of the seed slots of a [StrongholdAdapter].
+
+use std::path::Path;
+
+use crypto::utils::rand;
+use iota_stronghold::{
+    procedures::{self, AesKeyWrapCipher, Sha2Hash},
+    Client, Location,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    common::{
+        seed_slot_location, BACKUP_KEY_RECORD_PATH, DEFAULT_SEED_SLOT, PRIVATE_DATA_CLIENT_PATH, SECRET_VAULT_PATH,
+        SEED_SLOTS_STORE_KEY,
+    },
+    StrongholdAdapter,
+};
+use crate::{storage::StorageProvider, Error, Result};
+
+/// Version of the format of seed slot backups.
+const SEED_SLOT_BACKUP_VERSION: u16 = 1;
+
+/// Number of PBKDF2 iterations to derive the key of a seed slot backup from its password.
+const SEED_SLOT_BACKUP_PBKDF_ITER: u32 = 100_000;
+
+/// Length of the salt of a seed slot backup.
+const SEED_SLOT_BACKUP_SALT_LENGTH: usize = 32;
+
+/// The seed of a slot, exported with [`StrongholdAdapter::export_seed_slot()`].
+///
+/// The seed never leaves the Stronghold vault in plaintext: it's wrapped with AES-256 key wrap in the vault, using a
+/// key derived from the backup password with PBKDF2-HMAC-SHA256.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SeedSlotBackup {
+    version: u16,
+    salt: String,
+    wrapped_seed: String,
+}
+
+impl StrongholdAdapter {
+    /// Get the name of the seed slot used to generate addresses and sign.
+    pub fn seed_slot(&self) -> &str {
+        &self.seed_slot
+    }
+
+    /// Set the name of the seed slot used to generate addresses and sign.
+    ///
+    /// The slot doesn't need to hold a seed yet, a mnemonic can then be stored into it with [`store_mnemonic()`].
+    ///
+    /// [`store_mnemonic()`]: Self::store_mnemonic()
+    pub fn set_seed_slot(&mut self, seed_slot: impl Into<String>) {
+        self.seed_slot = seed_slot.into();
+    }
+
+    /// Check if a seed slot holds a seed.
+    pub async fn seed_slot_exists(&self, slot: &str) -> Result<bool> {
+        if !self.is_key_available().await {
+            return Err(Error::StrongholdKeyCleared);
+        }
+
+        Ok(self.client().await?.record_exists(&seed_slot_location(slot))?)
+    }
+
+    /// List the names of the seed slots holding a seed.
+    pub async fn list_seed_slots(&mut self) -> Result<Vec<String>> {
+        let mut slots = Vec::new();
+
+        // Snapshots created before seed slots hold their seed in the default slot, which isn't in the index.
+        if self.seed_slot_exists(DEFAULT_SEED_SLOT).await? {
+            slots.push(DEFAULT_SEED_SLOT.to_string());
+        }
+
+        slots.extend(self.read_seed_slot_index().await?);
+
+        Ok(slots)
+    }
+
+    /// Rename a seed slot, the snapshot is then persisted.
+    ///
+    /// Adapters still using the old name of the slot won't find its seed anymore.
+    pub async fn rename_seed_slot(&mut self, slot: &str, new_slot: &str) -> Result<()> {
+        if !self.seed_slot_exists(slot).await? {
+            return Err(Error::StrongholdSeedSlotNotFound(slot.to_string()));
+        }
+        if self.seed_slot_exists(new_slot).await? {
+            return Err(Error::StrongholdSeedSlotAlreadyExists(new_slot.to_string()));
+        }
+
+        let client = self.client().await?;
+        client.execute_procedure(procedures::CopyRecord {
+            source: seed_slot_location(slot),
+            target: seed_slot_location(new_slot),
+        })?;
+        client.execute_procedure(procedures::RevokeData {
+            location: seed_slot_location(slot),
+            should_gc: true,
+        })?;
+
+        self.remove_from_seed_slot_index(slot).await?;
+        self.add_to_seed_slot_index(new_slot).await?;
+
+        self.write_stronghold_snapshot(None).await
+    }
+
+    /// Delete a seed slot with its seed, the snapshot is then persisted.
+    ///
+    /// The seed is lost unless it has been exported with [`export_seed_slot()`] or its mnemonic is known.
+    ///
+    /// [`export_seed_slot()`]: Self::export_seed_slot()
+    pub async fn delete_seed_slot(&mut self, slot: &str) -> Result<()> {
+        if !self.seed_slot_exists(slot).await? {
+            return Err(Error::StrongholdSeedSlotNotFound(slot.to_string()));
+        }
+
+        self.client().await?.execute_procedure(procedures::RevokeData {
+            location: seed_slot_location(slot),
+            should_gc: true,
+        })?;
+
+        self.remove_from_seed_slot_index(slot).await?;
+
+        self.write_stronghold_snapshot(None).await
+    }
+
+    /// Export the seed of a slot to a backup file at `backup_path`, encrypted with a key derived from `password`.
+    ///
+    /// The backup can be imported into a slot of any snapshot with [`import_seed_slot()`].
+    ///
+    /// [`import_seed_slot()`]: Self::import_seed_slot()
+    pub async fn export_seed_slot<P: AsRef<Path>>(&self, slot: &str, backup_path: P, password: &str) -> Result<()> {
+        if !self.seed_slot_exists(slot).await? {
+            return Err(Error::StrongholdSeedSlotNotFound(slot.to_string()));
+        }
+
+        let mut salt = [0u8; SEED_SLOT_BACKUP_SALT_LENGTH];
+        rand::fill(&mut salt)?;
+
+        let client = self.client().await?;
+        let backup_key = derive_backup_key(&client, password, &salt)?;
+        let wrapped_seed = client.execute_procedure(procedures::AesKeyWrapEncrypt {
+            cipher: AesKeyWrapCipher::Aes256,
+            encryption_key: backup_key.clone(),
+            wrap_key: seed_slot_location(slot),
+        });
+
+        // Remove the backup key from the vault whether the seed could be wrapped or not.
+        client.execute_procedure(procedures::RevokeData {
+            location: backup_key,
+            should_gc: true,
+        })?;
+
+        let backup = SeedSlotBackup {
+            version: SEED_SLOT_BACKUP_VERSION,
+            salt: prefix_hex::encode(salt),
+            wrapped_seed: prefix_hex::encode(wrapped_seed?),
+        };
+
+        tokio::fs::write(backup_path, serde_json::to_vec(&backup)?).await?;
+
+        Ok(())
+    }
+
+    /// Import a backup created with [`export_seed_slot()`] into `slot`, which must not hold a seed yet, the snapshot is
+    /// then persisted.
+    ///
+    /// The integrity of the backup is checked with the key derived from `password`, so both a wrong password and a
+    /// tampered backup result in [`Error::StrongholdInvalidPassword`].
+    ///
+    /// [`export_seed_slot()`]: Self::export_seed_slot()
+    pub async fn import_seed_slot<P: AsRef<Path>>(&mut self, backup_path: P, password: &str, slot: &str) -> Result<()> {
+        if self.seed_slot_exists(slot).await? {
+            return Err(Error::StrongholdSeedSlotAlreadyExists(slot.to_string()));
+        }
+
+        let backup: SeedSlotBackup = serde_json::from_slice(&tokio::fs::read(backup_path).await?)?;
+
+        if backup.version != SEED_SLOT_BACKUP_VERSION {
+            return Err(Error::StrongholdUnsupportedSeedSlotBackupVersion(backup.version));
+        }
+
+        let salt: Vec<u8> = prefix_hex::decode(&backup.salt)?;
+        let wrapped_key: Vec<u8> = prefix_hex::decode(&backup.wrapped_seed)?;
+
+        let client = self.client().await?;
+        let backup_key = derive_backup_key(&client, password, &salt)?;
+        let unwrapped = client.execute_procedure(procedures::AesKeyWrapDecrypt {
+            cipher: AesKeyWrapCipher::Aes256,
+            decryption_key: backup_key.clone(),
+            wrapped_key,
+            output: seed_slot_location(slot),
+        });
+
+        // Remove the backup key from the vault whether the seed could be unwrapped or not.
+        client.execute_procedure(procedures::RevokeData {
+            location: backup_key,
+            should_gc: true,
+        })?;
+
+        unwrapped.map_err(|_| Error::StrongholdInvalidPassword)?;
+
+        self.add_to_seed_slot_index(slot).await?;
+
+        self.write_stronghold_snapshot(None).await
+    }
+
+    /// Add a slot to the index of the seed slots, if it isn't the default one.
+    pub(super) async fn add_to_seed_slot_index(&mut self, slot: &str) -> Result<()> {
+        let mut index = self.read_seed_slot_index().await?;
+
+        if slot != DEFAULT_SEED_SLOT && !index.iter().any(|s| s == slot) {
+            index.push(slot.to_string());
+            self.insert(SEED_SLOTS_STORE_KEY, &serde_json::to_vec(&index)?).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Remove a slot from the index of the seed slots.
+    async fn remove_from_seed_slot_index(&mut self, slot: &str) -> Result<()> {
+        let mut index = self.read_seed_slot_index().await?;
+        let len = index.len();
+
+        index.retain(|s| s != slot);
+
+        if index.len() != len {
+            self.insert(SEED_SLOTS_STORE_KEY, &serde_json::to_vec(&index)?).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Read the index of the seed slots other than the default one from the Stronghold store.
+    async fn read_seed_slot_index(&mut self) -> Result<Vec<String>> {
+        match self.get(SEED_SLOTS_STORE_KEY).await? {
+            Some(index) => Ok(serde_json::from_slice(&index)?),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    async fn client(&self) -> Result<Client> {
+        Ok(self.stronghold.lock().await.get_client(PRIVATE_DATA_CLIENT_PATH)?)
+    }
+}
+
+/// Execute [Procedure::Pbkdf2Hmac] in Stronghold to derive the key of a seed slot backup in the Stronghold vault.
+fn derive_backup_key(client: &Client, password: &str, salt: &[u8]) -> Result<Location> {
+    let backup_key = Location::generic(SECRET_VAULT_PATH, BACKUP_KEY_RECORD_PATH);
+
+    client.execute_procedure(procedures::Pbkdf2Hmac {
+        hash_type: Sha2Hash::Sha256,
+        password: password.as_bytes().to_vec(),
+        salt: salt.to_vec(),
+        count: SEED_SLOT_BACKUP_PBKDF_ITER,
+        output: backup_key.clone(),
+    })?;
+
+    Ok(backup_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        constants::IOTA_COIN_TYPE,
+        secret::{mnemonic::MnemonicSecretManager, SecretManage},
+    };
+
+    const MNEMONIC: &str = "giant dynamic museum toddler six deny defense ostrich bomb access mercy blood explain muscle shoot shallow glad autumn author calm heavy hawk abuse rally";
+    const OTHER_MNEMONIC: &str = "acoustic trophy damage hint search taste love bicycle foster cradle brown govern endless depend situate athlete pudding blame question genius transfer van random vast";
+
+    async fn first_address(secret_manager: &impl SecretManage) -> String {
+        secret_manager
+            .generate_addresses(IOTA_COIN_TYPE, 0, 0..1, false, None)
+            .await
+            .unwrap()[0]
+            .to_bech32("atoi")
+    }
+
+    #[tokio::test]
+    async fn seed_slots() {
+        let stronghold_path = "test_seed_slots.stronghold";
+        std::fs::remove_file(stronghold_path).unwrap_or(());
+        let mut stronghold_adapter = StrongholdAdapter::builder()
+            .password("drowssap")
+            .build(stronghold_path)
+            .unwrap();
+
+        assert_eq!(stronghold_adapter.seed_slot(), DEFAULT_SEED_SLOT);
+        stronghold_adapter.store_mnemonic(MNEMONIC.to_string()).await.unwrap();
+
+        stronghold_adapter.set_seed_slot("other");
+        stronghold_adapter
+            .store_mnemonic(OTHER_MNEMONIC.to_string())
+            .await
+            .unwrap();
+        assert!(matches!(
+            stronghold_adapter.store_mnemonic(OTHER_MNEMONIC.to_string()).await,
+            Err(Error::StrongholdMnemonicAlreadyStored)
+        ));
+
+        assert_eq!(
+            stronghold_adapter.list_seed_slots().await.unwrap(),
+            vec![DEFAULT_SEED_SLOT.to_string(), "other".to_string()]
+        );
+
+        // Each slot generates the addresses of its own seed.
+        let other_address = first_address(&MnemonicSecretManager::try_from_mnemonic(OTHER_MNEMONIC).unwrap()).await;
+        assert_eq!(first_address(&stronghold_adapter).await, other_address);
+        stronghold_adapter.set_seed_slot(DEFAULT_SEED_SLOT);
+        assert_eq!(
+            first_address(&stronghold_adapter).await,
+            "atoi1qpszqzadsym6wpppd6z037dvlejmjuke7s24hm95s9fg9vpua7vluehe53e"
+        );
+
+        stronghold_adapter.rename_seed_slot("other", "renamed").await.unwrap();
+        assert!(matches!(
+            stronghold_adapter.rename_seed_slot("renamed", DEFAULT_SEED_SLOT).await,
+            Err(Error::StrongholdSeedSlotAlreadyExists(_))
+        ));
+        stronghold_adapter.delete_seed_slot(DEFAULT_SEED_SLOT).await.unwrap();
+        assert!(matches!(
+            stronghold_adapter.delete_seed_slot(DEFAULT_SEED_SLOT).await,
+            Err(Error::StrongholdSeedSlotNotFound(_))
+        ));
+
+        // Slots survive reloading the snapshot.
+        drop(stronghold_adapter);
+        let mut stronghold_adapter = StrongholdAdapter::builder()
+            .password("drowssap")
+            .seed_slot("renamed")
+            .build(stronghold_path)
+            .unwrap();
+
+        assert_eq!(
+            stronghold_adapter.list_seed_slots().await.unwrap(),
+            vec!["renamed".to_string()]
+        );
+        assert_eq!(first_address(&stronghold_adapter).await, other_address);
+
+        std::fs::remove_file(stronghold_path).unwrap_or(());
+    }
+
+    #[tokio::test]
+    async fn seed_slot_backup() {
+        let stronghold_path = "test_seed_slot_backup.stronghold";
+        let other_stronghold_path = "test_seed_slot_backup_other.stronghold";
+        let backup_path = "test_seed_slot_backup.json";
+        for path in [stronghold_path, other_stronghold_path, backup_path] {
+            std::fs::remove_file(path).unwrap_or(());
+        }
+
+        let mut stronghold_adapter = StrongholdAdapter::builder()
+            .password("drowssap")
+            .build(stronghold_path)
+            .unwrap();
+        stronghold_adapter.store_mnemonic(MNEMONIC.to_string()).await.unwrap();
+        stronghold_adapter
+            .export_seed_slot(DEFAULT_SEED_SLOT, backup_path, "backup_password")
+            .await
+            .unwrap();
+
+        // The backup doesn't contain the seed in plaintext, neither as bytes nor hex encoded like its fields.
+        let hex_seed = crate::utils::mnemonic_to_hex_seed(MNEMONIC).unwrap();
+        let seed: Vec<u8> = prefix_hex::decode(&hex_seed).unwrap();
+        let backup = std::fs::read(backup_path).unwrap();
+        assert!(!backup.windows(seed.len()).any(|window| window == seed));
+        assert!(!String::from_utf8(backup)
+            .unwrap()
+            .contains(hex_seed.trim_start_matches("0x")));
+
+        let mut other_stronghold_adapter = StrongholdAdapter::builder()
+            .password("other_drowssap")
+            .seed_slot("imported")
+            .build(other_stronghold_path)
+            .unwrap();
+
+        assert!(matches!(
+            other_stronghold_adapter
+                .import_seed_slot(backup_path, "wrong_password", "imported")
+                .await,
+            Err(Error::StrongholdInvalidPassword)
+        ));
+        assert!(!other_stronghold_adapter.seed_slot_exists("imported").await.unwrap());
+
+        other_stronghold_adapter
+            .import_seed_slot(backup_path, "backup_password", "imported")
+            .await
+            .unwrap();
+
+        assert_eq!(
+            first_address(&other_stronghold_adapter).await,
+            first_address(&stronghold_adapter).await
+        );
+        assert_eq!(
+            other_stronghold_adapter.list_seed_slots().await.unwrap(),
+            vec!["imported".to_string()]
+        );
+
+        for path in [stronghold_path, other_stronghold_path, backup_path] {
+            std::fs::remove_file(path).unwrap_or(());
+        }
+    }
+}
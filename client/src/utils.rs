@@ -15,6 +15,8 @@ use iota_types::block::{
     output::{AliasId, NftId},
     payload::TaggedDataPayload,
 };
+use serde::{Deserialize, Serialize};
+use unicode_normalization::UnicodeNormalization;
 use zeroize::Zeroize;
 
 use super::Client;
@@ -68,38 +70,183 @@ pub fn is_address_valid(address: &str) -> bool {
     Address::try_from_bech32(address).is_ok()
 }
 
-/// Generates a new mnemonic.
-pub fn generate_mnemonic() -> Result<String> {
+/// The language of the wordlist of a BIP-39 mnemonic.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MnemonicLanguage {
+    /// English wordlist.
+    English,
+    /// Japanese wordlist.
+    Japanese,
+}
+
+impl Default for MnemonicLanguage {
+    fn default() -> Self {
+        Self::English
+    }
+}
+
+impl MnemonicLanguage {
+    fn wordlist(&self) -> &'static wordlist::Wordlist<'static> {
+        match self {
+            Self::English => &wordlist::ENGLISH,
+            Self::Japanese => &wordlist::JAPANESE,
+        }
+    }
+
+    // The character between the words of the wordlist, Japanese mnemonics are separated by ideographic spaces.
+    fn separator(&self) -> char {
+        match self {
+            Self::English => ' ',
+            Self::Japanese => '\u{3000}',
+        }
+    }
+}
+
+/// The number of words of a BIP-39 mnemonic, which determines the size of its entropy.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "u8", into = "u8")]
+pub enum MnemonicLength {
+    /// 12 words, 128 bits of entropy.
+    Words12,
+    /// 15 words, 160 bits of entropy.
+    Words15,
+    /// 18 words, 192 bits of entropy.
+    Words18,
+    /// 21 words, 224 bits of entropy.
+    Words21,
+    /// 24 words, 256 bits of entropy.
+    Words24,
+}
+
+impl Default for MnemonicLength {
+    fn default() -> Self {
+        Self::Words24
+    }
+}
+
+impl MnemonicLength {
+    /// Returns the number of words.
+    pub fn word_count(&self) -> u8 {
+        match self {
+            Self::Words12 => 12,
+            Self::Words15 => 15,
+            Self::Words18 => 18,
+            Self::Words21 => 21,
+            Self::Words24 => 24,
+        }
+    }
+
+    /// Returns the size of the entropy in bytes.
+    pub fn entropy_size(&self) -> usize {
+        // Each word encodes 11 bits, 32 out of 33 bits are entropy and the remaining ones are the checksum.
+        self.word_count() as usize * 4 / 3
+    }
+}
+
+impl TryFrom<u8> for MnemonicLength {
+    type Error = Error;
+
+    fn try_from(word_count: u8) -> Result<Self> {
+        Ok(match word_count {
+            12 => Self::Words12,
+            15 => Self::Words15,
+            18 => Self::Words18,
+            21 => Self::Words21,
+            24 => Self::Words24,
+            _ => {
+                return Err(Error::InvalidMnemonic(format!(
+                    "unsupported word count {word_count}, expected 12, 15, 18, 21 or 24"
+                )));
+            }
+        })
+    }
+}
+
+impl From<MnemonicLength> for u8 {
+    fn from(length: MnemonicLength) -> Self {
+        length.word_count()
+    }
+}
+
+/// Generates a new English mnemonic with the given number of words.
+pub fn generate_mnemonic(length: MnemonicLength) -> Result<String> {
+    generate_mnemonic_with_language(length, MnemonicLanguage::English)
+}
+
+/// Generates a new mnemonic with the given number of words from the wordlist of the given language.
+pub fn generate_mnemonic_with_language(length: MnemonicLength, language: MnemonicLanguage) -> Result<String> {
     let mut entropy = [0u8; 32];
-    utils::rand::fill(&mut entropy)?;
-    let mnemonic = wordlist::encode(&entropy, &crypto::keys::bip39::wordlist::ENGLISH)
-        .map_err(|e| crate::Error::InvalidMnemonic(format!("{e:?}")))?;
+    let entropy_size = length.entropy_size();
+    utils::rand::fill(&mut entropy[..entropy_size])?;
+    let mnemonic = wordlist::encode(&entropy[..entropy_size], language.wordlist())
+        .map_err(|e| crate::Error::InvalidMnemonic(format!("{e:?}")));
     entropy.zeroize();
-    Ok(mnemonic)
+    mnemonic
+}
+
+/// Verifies that a mnemonic consists of words of the wordlist of the given language and has a valid checksum.
+pub fn verify_mnemonic(mnemonic: &str, language: MnemonicLanguage) -> Result<()> {
+    // The wordlists hold composed characters, so the words are compared in NFC and joined by the wordlist separator.
+    let mnemonic = normalize_mnemonic(mnemonic)
+        .split(' ')
+        .map(|word| word.nfc().collect::<String>())
+        .collect::<Vec<_>>()
+        .join(&language.separator().to_string());
+    wordlist::verify(&mnemonic, language.wordlist()).map_err(|e| crate::Error::InvalidMnemonic(format!("{e:?}")))
+}
+
+// Returns the NFKD form of a mnemonic, from which BIP-39 derives the seed, with single spaces between the words. NFKD
+// maps ideographic spaces to spaces, other whitespace is dropped because it would create a different seed
+// https://github.com/iotaledger/crypto.rs/issues/125
+pub(crate) fn normalize_mnemonic(mnemonic: &str) -> String {
+    mnemonic
+        .nfkd()
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
 }
 
 /// Returns a hex encoded seed for a mnemonic.
 pub fn mnemonic_to_hex_seed(mnemonic: &str) -> Result<String> {
-    // trim because empty spaces could create a different seed https://github.com/iotaledger/crypto.rs/issues/125
-    let mnemonic = mnemonic.trim();
-    // first we check if the mnemonic is valid to give meaningful errors
-    crypto::keys::bip39::wordlist::verify(mnemonic, &crypto::keys::bip39::wordlist::ENGLISH)
-        .map_err(|e| crate::Error::InvalidMnemonic(format!("{e:?}")))?;
-    let mut mnemonic_seed = [0u8; 64];
-    crypto::keys::bip39::mnemonic_to_seed(mnemonic, "", &mut mnemonic_seed);
-    Ok(prefix_hex::encode(mnemonic_seed))
+    mnemonic_to_hex_seed_with_passphrase(mnemonic, "", MnemonicLanguage::English)
+}
+
+/// Returns a hex encoded seed for a mnemonic of the given language, protected by a BIP-39 passphrase.
+pub fn mnemonic_to_hex_seed_with_passphrase(
+    mnemonic: &str,
+    passphrase: &str,
+    language: MnemonicLanguage,
+) -> Result<String> {
+    let mut mnemonic_seed = mnemonic_to_seed_bytes(mnemonic, passphrase, language)?;
+    let hex_seed = prefix_hex::encode(mnemonic_seed);
+    mnemonic_seed.zeroize();
+    Ok(hex_seed)
 }
 
 /// Returns a seed for a mnemonic.
 pub fn mnemonic_to_seed(mnemonic: &str) -> Result<Seed> {
-    // trim because empty spaces could create a different seed https://github.com/iotaledger/crypto.rs/issues/125
-    let mnemonic = mnemonic.trim();
+    mnemonic_to_seed_with_passphrase(mnemonic, "", MnemonicLanguage::English)
+}
+
+/// Returns a seed for a mnemonic of the given language, protected by a BIP-39 passphrase.
+pub fn mnemonic_to_seed_with_passphrase(mnemonic: &str, passphrase: &str, language: MnemonicLanguage) -> Result<Seed> {
+    let mut mnemonic_seed = mnemonic_to_seed_bytes(mnemonic, passphrase, language)?;
+    let seed = Seed::from_bytes(&mnemonic_seed);
+    mnemonic_seed.zeroize();
+    Ok(seed)
+}
+
+fn mnemonic_to_seed_bytes(mnemonic: &str, passphrase: &str, language: MnemonicLanguage) -> Result<[u8; 64]> {
     // first we check if the mnemonic is valid to give meaningful errors
-    crypto::keys::bip39::wordlist::verify(mnemonic, &crypto::keys::bip39::wordlist::ENGLISH)
-        .map_err(|e| crate::Error::InvalidMnemonic(format!("{e:?}")))?;
+    verify_mnemonic(mnemonic, language)?;
+    // BIP-39 derives the seed from the NFKD forms of the mnemonic and the passphrase
+    let mnemonic = normalize_mnemonic(mnemonic);
+    let passphrase = passphrase.nfkd().collect::<String>();
     let mut mnemonic_seed = [0u8; 64];
-    crypto::keys::bip39::mnemonic_to_seed(mnemonic, "", &mut mnemonic_seed);
-    Ok(Seed::from_bytes(&mnemonic_seed))
+    crypto::keys::bip39::mnemonic_to_seed(&mnemonic, &passphrase, &mut mnemonic_seed);
+    Ok(mnemonic_seed)
 }
 
 /// Requests funds from a faucet
@@ -161,9 +308,23 @@ impl Client {
         is_address_valid(address)
     }
 
-    /// Generates a new mnemonic.
-    pub fn generate_mnemonic() -> Result<String> {
-        generate_mnemonic()
+    /// Generates a new English mnemonic with the given number of words.
+    pub fn generate_mnemonic(length: MnemonicLength) -> Result<String> {
+        generate_mnemonic(length)
+    }
+
+    /// Generates a new mnemonic with the given number of words from the wordlist of the given language.
+    pub fn generate_mnemonic_with_language(length: MnemonicLength, language: MnemonicLanguage) -> Result<String> {
+        generate_mnemonic_with_language(length, language)
+    }
+
+    /// Returns a seed for a mnemonic of the given language, protected by a BIP-39 passphrase.
+    pub fn mnemonic_to_seed_with_passphrase(
+        mnemonic: &str,
+        passphrase: &str,
+        language: MnemonicLanguage,
+    ) -> Result<Seed> {
+        mnemonic_to_seed_with_passphrase(mnemonic, passphrase, language)
     }
 
     /// Returns a seed for a mnemonic.
@@ -0,0 +1,129 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+mod common;
+
+use std::{
+    str::FromStr,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use futures::{StreamExt, TryStreamExt};
+use iota_client::{api::AddressEvent, Error};
+use iota_types::block::{address::Address, output::OutputId, rand::address::rand_ed25519_address};
+
+use self::common::{json, mock_client, not_found, ok, output_with_metadata, MockTransport};
+
+const TRANSACTION_ID: &str = "0x1e857d380f813d8035e487b6dfd2ff4740b6775273ba1b576f01381ba2a1a44c";
+const INDEXER_PATH: &str = "/api/indexer/v1/outputs/basic";
+
+fn output_id(index: u16) -> OutputId {
+    OutputId::new(TRANSACTION_ID.parse().unwrap(), index).unwrap()
+}
+
+// Lists the outputs 0 and 1 on the first request to the indexer, afterwards output 0 is spent and 2 got created. If
+// `pruned` is set, the spent output 0 is not found anymore.
+fn ledger_transport(pruned: bool) -> MockTransport {
+    let indexer_requests = AtomicUsize::new(0);
+    MockTransport::new(move |request| {
+        let path = request.url.path();
+
+        if path == INDEXER_PATH {
+            let items = if indexer_requests.fetch_add(1, Ordering::SeqCst) == 0 {
+                [output_id(0), output_id(1)]
+            } else {
+                [output_id(1), output_id(2)]
+            };
+            ok(format!(
+                "{{\"ledgerIndex\":10,\"cursor\":null,\"items\":[\"{}\",\"{}\"]}}",
+                items[0], items[1]
+            ))
+        } else if path == "/api/indexer/v1/outputs/nft" {
+            ok("{\"ledgerIndex\":10,\"cursor\":null,\"items\":[]}")
+        } else if let Some(output_id) = path.strip_prefix("/api/core/v2/outputs/") {
+            let output_id = OutputId::from_str(output_id).unwrap();
+            let is_spent = output_id.index() == 0 && indexer_requests.load(Ordering::SeqCst) > 1;
+            if pruned && is_spent {
+                not_found()
+            } else {
+                json(&output_with_metadata(&output_id, is_spent, 10))
+            }
+        } else {
+            not_found()
+        }
+    })
+}
+
+#[tokio::test]
+async fn watch_addresses() {
+    let transport = ledger_transport(false);
+    let client = mock_client(&transport);
+    let address = Address::Ed25519(rand_ed25519_address()).to_bech32("rms");
+
+    let events = client
+        .watch_addresses(vec![address.clone()])
+        .with_poll_interval(Duration::from_millis(10))
+        .with_existing_outputs(true)
+        .finish()
+        .await
+        .unwrap()
+        .take(4)
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+
+    assert!(events.iter().all(|event| event.address() == address));
+    let mut created = events
+        .iter()
+        .filter(|event| matches!(event, AddressEvent::OutputCreated { .. }))
+        .map(|event| *event.output_id())
+        .collect::<Vec<_>>();
+    created.sort();
+    assert_eq!(created, vec![output_id(0), output_id(1), output_id(2)]);
+    // Output 1 stays unspent and output 0 is only returned once as spent.
+    assert!(matches!(events[3], AddressEvent::OutputSpent { .. }));
+    assert_eq!(*events[3].output_id(), output_id(0));
+    assert!(transport.requests_to(INDEXER_PATH) >= 2);
+}
+
+#[tokio::test]
+async fn watch_addresses_pruned_output() {
+    let transport = ledger_transport(true);
+    let client = mock_client(&transport);
+    let address = Address::Ed25519(rand_ed25519_address()).to_bech32("rms");
+
+    let mut events = Box::pin(
+        client
+            .watch_addresses(vec![address])
+            .with_poll_interval(Duration::from_millis(10))
+            .with_existing_outputs(true)
+            .finish()
+            .await
+            .unwrap(),
+    );
+    let mut created = Vec::new();
+    for _ in 0..3 {
+        let event = events.next().await.unwrap().unwrap();
+        assert!(matches!(event, AddressEvent::OutputCreated { .. }));
+        created.push(*event.output_id());
+    }
+    created.sort();
+    assert_eq!(created, vec![output_id(0), output_id(1), output_id(2)]);
+
+    // The pruned output is dropped instead of failing every poll.
+    assert!(tokio::time::timeout(Duration::from_millis(100), events.next())
+        .await
+        .is_err());
+    assert!(transport.requests_to(INDEXER_PATH) > 2);
+}
+
+#[tokio::test]
+async fn watch_invalid_address() {
+    let client = mock_client(&ledger_transport(false));
+
+    assert!(matches!(
+        client.watch_addresses(vec!["rms1invalid".to_string()]).finish().await,
+        Err(Error::Block(_))
+    ));
+}
@@ -232,7 +232,7 @@ async fn address_generation() {
                 options: None,
             };
             let message = Message::GenerateAddresses {
-                secret_manager: SecretManagerDto::Mnemonic(address.mnemonic.clone()),
+                secret_manager: SecretManagerDto::Mnemonic(address.mnemonic.clone().into()),
                 options,
             };
 
@@ -262,6 +262,8 @@ async fn address_generation() {
                 password: Some("some_hopefully_secure_password".to_string()),
                 timeout: None,
                 snapshot_path: stronghold_filename.clone(),
+                seed_slot: None,
+                key_derivation: None,
             };
             let message = Message::StoreMnemonic {
                 secret_manager: SecretManagerDto::Stronghold(secret_manager_dto.clone()),
@@ -0,0 +1,45 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+mod common;
+
+use std::time::Duration;
+
+use iota_client::Error;
+
+use self::common::{mock_client, not_found, ok, MockTransport};
+
+#[tokio::test]
+async fn batch_requests() {
+    // Answers UTXO changes requests for all milestone indexes apart from 2, delayed so that batched requests overlap.
+    let transport = MockTransport::new(|request| {
+        let index = request
+            .url
+            .path()
+            .strip_prefix("/api/core/v2/milestones/by-index/")
+            .and_then(|path| path.strip_suffix("/utxo-changes"))
+            .and_then(|index| index.parse::<u32>().ok());
+
+        match index {
+            Some(index) if index != 2 => ok(format!(
+                "{{\"index\":{index},\"createdOutputs\":[],\"consumedOutputs\":[]}}"
+            )),
+            _ => not_found(),
+        }
+    })
+    .with_delay(Duration::from_millis(20));
+    let client = mock_client(&transport);
+
+    let utxo_changes = client.get_utxo_changes(&[1, 2, 3, 4, 5, 6], 3).await;
+
+    // The failed request doesn't abort the batch and the results are in the order of the indexes.
+    assert_eq!(utxo_changes.len(), 6);
+    assert!(matches!(utxo_changes[1], Err(Error::NotFound(_))));
+    for (utxo_changes, index) in utxo_changes.iter().zip(1..).filter(|(_, index)| *index != 2) {
+        assert_eq!(utxo_changes.as_ref().unwrap().index, index);
+    }
+    assert_eq!(transport.max_in_flight(), 3);
+
+    // An empty batch doesn't send any request.
+    assert!(client.get_utxo_changes(&[], 3).await.is_empty());
+}
@@ -0,0 +1,351 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+mod common;
+
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use futures::StreamExt;
+use iota_client::{api::BlockState, Client, Error, Result};
+use iota_types::{
+    api::core::{
+        dto::LedgerInclusionStateDto,
+        response::{BlockMetadataResponse, SubmitBlockResponse, TipsResponse},
+    },
+    block::{
+        address::{Address, Ed25519Address},
+        input::{Input, UtxoInput},
+        output::{unlock_condition::AddressUnlockCondition, BasicOutput, Output},
+        parent::Parents,
+        payload::{
+            transaction::{RegularTransactionEssence, TransactionEssence, TransactionPayload},
+            Payload,
+        },
+        protocol::ProtocolParameters,
+        rand::{
+            block::{rand_block_id, rand_block_ids},
+            output::rand_inputs_commitment,
+            transaction::rand_transaction_id,
+        },
+        semantic::ConflictReason,
+        signature::{Ed25519Signature, Signature},
+        unlock::{SignatureUnlock, Unlock, Unlocks},
+        Block, BlockBuilder, BlockDto, BlockId,
+    },
+};
+
+use self::common::{json, mock_client_builder, not_found, MockTransport, NODE_LOCAL};
+
+// The metadata of a block that is referenced without a transaction, or isn't referenced and needs neither promotion
+// nor reattachment.
+fn metadata(block_id: BlockId, referenced: bool) -> BlockMetadataResponse {
+    BlockMetadataResponse {
+        block_id: block_id.to_string(),
+        parents: vec![rand_block_id().to_string()],
+        is_solid: true,
+        referenced_by_milestone_index: referenced.then_some(7),
+        milestone_index: None,
+        ledger_inclusion_state: referenced.then_some(LedgerInclusionStateDto::NoTransaction),
+        conflict_reason: None,
+        white_flag_index: None,
+        should_promote: (!referenced).then_some(false),
+        should_reattach: (!referenced).then_some(false),
+    }
+}
+
+// A block with a transaction payload, which isn't checked against the ledger.
+fn transaction_block() -> Block {
+    let protocol_parameters = ProtocolParameters::default();
+    let output = Output::Basic(
+        BasicOutput::build_with_amount(1_000_000)
+            .unwrap()
+            .add_unlock_condition(AddressUnlockCondition::new(Address::from(Ed25519Address::new([0; 32]))).into())
+            .finish(protocol_parameters.token_supply())
+            .unwrap(),
+    );
+    let essence = TransactionEssence::Regular(
+        RegularTransactionEssence::builder(protocol_parameters.network_id(), rand_inputs_commitment())
+            .with_inputs(vec![Input::Utxo(UtxoInput::new(rand_transaction_id(), 0).unwrap())])
+            .add_output(output)
+            .finish(&protocol_parameters)
+            .unwrap(),
+    );
+    let unlocks = Unlocks::new(vec![Unlock::Signature(SignatureUnlock::from(Signature::Ed25519(
+        Ed25519Signature::new([0; 32], [0; 64]),
+    )))])
+    .unwrap();
+
+    BlockBuilder::new(Parents::new(rand_block_ids(1)).unwrap())
+        .with_payload(Payload::from(TransactionPayload::new(essence, unlocks).unwrap()))
+        .finish()
+        .unwrap()
+}
+
+struct Tangle {
+    client: Client,
+    transport: MockTransport,
+    posted_block_ids: Arc<Mutex<Vec<BlockId>>>,
+}
+
+// Answers metadata requests with `metadata`, which gets the ID of the block and the number of previous metadata
+// requests. Posted blocks get random IDs, and a transaction block is returned for all block and included block
+// requests.
+fn tangle(metadata: impl Fn(BlockId, usize) -> BlockMetadataResponse + Send + Sync + 'static) -> Tangle {
+    let block = transaction_block();
+    let metadata_requests = AtomicUsize::new(0);
+    let posted_block_ids = Arc::new(Mutex::new(Vec::new()));
+    let transport = MockTransport::new({
+        let posted_block_ids = posted_block_ids.clone();
+        move |request| {
+            let path = request.url.path();
+
+            if let Some(block_id) = path
+                .strip_prefix("/api/core/v2/blocks/")
+                .and_then(|path| path.strip_suffix("/metadata"))
+            {
+                let request_index = metadata_requests.fetch_add(1, Ordering::SeqCst);
+                json(&metadata(block_id.parse().unwrap(), request_index))
+            } else if path == "/api/core/v2/blocks" {
+                let block_id = rand_block_id();
+                posted_block_ids.lock().unwrap().push(block_id);
+                json(&SubmitBlockResponse {
+                    block_id: block_id.to_string(),
+                })
+            } else if path.starts_with("/api/core/v2/blocks/") || path.ends_with("/included-block") {
+                json(&BlockDto::from(&block))
+            } else if path == "/api/core/v2/tips" {
+                json(&TipsResponse {
+                    tips: vec![rand_block_id().to_string()],
+                })
+            } else {
+                not_found()
+            }
+        }
+    });
+    let client = mock_client_builder(&[NODE_LOCAL], &transport)
+        .with_local_pow(false)
+        .finish()
+        .unwrap();
+
+    Tangle {
+        client,
+        transport,
+        posted_block_ids,
+    }
+}
+
+async fn collect_states(client: &Client, block_id: BlockId) -> Vec<BlockState> {
+    client
+        .track_block(block_id)
+        .with_interval(Duration::from_millis(10))
+        .stream()
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>>>()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn track_block_states() {
+    let Tangle { client, .. } = tangle(|block_id, request_index| metadata(block_id, request_index >= 2));
+    let block_id = rand_block_id();
+
+    assert_eq!(
+        collect_states(&client, block_id).await,
+        vec![
+            BlockState::Pending { block_id },
+            BlockState::Referenced {
+                block_id,
+                milestone_index: 7
+            },
+            BlockState::Finalized {
+                block_id,
+                milestone_index: 7
+            },
+        ]
+    );
+    assert_eq!(
+        client.track_block(block_id).finish().await.unwrap(),
+        BlockState::Referenced {
+            block_id,
+            milestone_index: 7
+        }
+    );
+}
+
+#[tokio::test]
+async fn track_block_max_attempts() {
+    let Tangle { client, transport, .. } = tangle(|block_id, _| metadata(block_id, false));
+    let block_id = rand_block_id();
+
+    let states = client
+        .track_block(block_id)
+        .with_interval(Duration::from_millis(10))
+        .with_max_attempts(3)
+        .stream()
+        .collect::<Vec<_>>()
+        .await;
+
+    assert_eq!(states.len(), 2);
+    assert_eq!(states[0].as_ref().unwrap(), &BlockState::Pending { block_id });
+    assert!(matches!(&states[1], Err(Error::TangleInclusion(id)) if *id == block_id.to_string()));
+    assert_eq!(
+        transport
+            .requests()
+            .iter()
+            .filter(|request| request.url.path().ends_with("/metadata"))
+            .count(),
+        3
+    );
+}
+
+#[tokio::test]
+async fn track_block_promoted() {
+    let Tangle {
+        client,
+        posted_block_ids,
+        ..
+    } = tangle(|block_id, request_index| {
+        if request_index == 0 {
+            BlockMetadataResponse {
+                should_promote: Some(true),
+                ..metadata(block_id, false)
+            }
+        } else {
+            metadata(block_id, true)
+        }
+    });
+    let block_id = rand_block_id();
+
+    let states = collect_states(&client, block_id).await;
+
+    let posted_block_ids = posted_block_ids.lock().unwrap().clone();
+    assert_eq!(posted_block_ids.len(), 1);
+    assert_eq!(
+        states,
+        vec![
+            BlockState::Pending { block_id },
+            BlockState::Promoted {
+                block_id,
+                promotion_block_id: posted_block_ids[0]
+            },
+            BlockState::Referenced {
+                block_id,
+                milestone_index: 7
+            },
+            BlockState::Finalized {
+                block_id,
+                milestone_index: 7
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn track_block_reattached() {
+    let block_id = rand_block_id();
+    // Only the reattachment gets referenced and included.
+    let Tangle {
+        client,
+        posted_block_ids,
+        ..
+    } = tangle(move |metadata_block_id, _| {
+        if metadata_block_id == block_id {
+            BlockMetadataResponse {
+                should_reattach: Some(true),
+                ..metadata(metadata_block_id, false)
+            }
+        } else {
+            BlockMetadataResponse {
+                ledger_inclusion_state: Some(LedgerInclusionStateDto::Included),
+                ..metadata(metadata_block_id, true)
+            }
+        }
+    });
+
+    let states = collect_states(&client, block_id).await;
+
+    let posted_block_ids = posted_block_ids.lock().unwrap().clone();
+    assert_eq!(posted_block_ids.len(), 1);
+    let reattachment_block_id = posted_block_ids[0];
+    assert_eq!(
+        states,
+        vec![
+            BlockState::Pending { block_id },
+            BlockState::Reattached {
+                block_id,
+                reattachment_block_id
+            },
+            BlockState::Referenced {
+                block_id: reattachment_block_id,
+                milestone_index: 7
+            },
+            BlockState::Included {
+                block_id: reattachment_block_id
+            },
+            BlockState::Finalized {
+                block_id: reattachment_block_id,
+                milestone_index: 7
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn track_block_conflicting_included_by_other_attachment() {
+    let Tangle { client, .. } = tangle(|block_id, _| BlockMetadataResponse {
+        ledger_inclusion_state: Some(LedgerInclusionStateDto::Conflicting),
+        conflict_reason: Some(1),
+        ..metadata(block_id, true)
+    });
+    let block_id = rand_block_id();
+    // The transport returns the same block for the conflicting attachment and the one that included its transaction,
+    // whose ID differs from the tracked one.
+    let included_block_id = client.get_block(&block_id).await.unwrap().id();
+
+    assert_eq!(
+        collect_states(&client, block_id).await,
+        vec![
+            BlockState::Referenced {
+                block_id,
+                milestone_index: 7
+            },
+            BlockState::Conflicting {
+                block_id,
+                reason: ConflictReason::InputUtxoAlreadySpent
+            },
+            BlockState::Included {
+                block_id: included_block_id
+            },
+            BlockState::Finalized {
+                block_id,
+                milestone_index: 7
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn track_block_unknown_conflict_reason() {
+    let Tangle { client, .. } = tangle(|block_id, _| BlockMetadataResponse {
+        ledger_inclusion_state: Some(LedgerInclusionStateDto::Conflicting),
+        conflict_reason: Some(42),
+        ..metadata(block_id, true)
+    });
+
+    assert!(matches!(
+        client
+            .track_block(rand_block_id())
+            .with_interval(Duration::from_millis(10))
+            .finish()
+            .await,
+        Err(Error::Conflict(_))
+    ));
+}
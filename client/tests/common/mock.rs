@@ -0,0 +1,163 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use iota_client::{
+    node_manager::http_client::{HttpRequest, HttpResponse, HttpTransport},
+    Client, ClientBuilder, Result,
+};
+use iota_types::{
+    api::core::response::OutputWithMetadataResponse,
+    block::{
+        output::{
+            dto::{OutputDto, OutputMetadataDto},
+            OutputId,
+        },
+        rand::{block::rand_block_id, output::rand_output},
+    },
+};
+use serde::Serialize;
+
+use super::NODE_LOCAL;
+
+const INFO_PATH: &str = "/api/core/v2/info";
+const TOKEN_SUPPLY: u64 = 1_813_620_509_061_365;
+
+type Handler = dyn Fn(&HttpRequest) -> HttpResponse + Send + Sync;
+
+/// An [`HttpTransport`] that answers requests with a handler instead of a node and records them. Clones share the
+/// recorded requests.
+#[derive(Clone)]
+pub struct MockTransport {
+    handler: Arc<Handler>,
+    delay: Option<Duration>,
+    requests: Arc<Mutex<Vec<HttpRequest>>>,
+    in_flight: Arc<AtomicUsize>,
+    max_in_flight: Arc<AtomicUsize>,
+}
+
+impl MockTransport {
+    /// Creates a transport that answers all requests with `handler`.
+    pub fn new(handler: impl Fn(&HttpRequest) -> HttpResponse + Send + Sync + 'static) -> Self {
+        Self {
+            handler: Arc::new(handler),
+            delay: None,
+            requests: Default::default(),
+            in_flight: Default::default(),
+            max_in_flight: Default::default(),
+        }
+    }
+
+    /// Delays all responses, so that concurrent requests are in flight at the same time.
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay.replace(delay);
+        self
+    }
+
+    /// Returns the recorded requests, without the node info requests of the node syncing.
+    pub fn requests(&self) -> Vec<HttpRequest> {
+        self.requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|request| request.url.path() != INFO_PATH)
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the number of recorded requests to `path`.
+    pub fn requests_to(&self, path: &str) -> usize {
+        self.requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|request| request.url.path() == path)
+            .count()
+    }
+
+    /// Returns the highest number of requests that were in flight at the same time.
+    pub fn max_in_flight(&self) -> usize {
+        self.max_in_flight.load(Ordering::SeqCst)
+    }
+}
+
+#[async_trait::async_trait]
+impl HttpTransport for MockTransport {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+        let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+        if let Some(delay) = self.delay {
+            tokio::time::sleep(delay).await;
+        }
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+        // The request is recorded after the handler ran, so that the handler only sees previous requests.
+        let response = (self.handler)(&request);
+        self.requests.lock().unwrap().push(request);
+
+        Ok(response)
+    }
+}
+
+/// Returns a response with the status code and body.
+pub fn response(status: u16, body: impl Into<Vec<u8>>) -> HttpResponse {
+    HttpResponse {
+        status,
+        body: body.into(),
+    }
+}
+
+/// Returns a 200 response with the body.
+pub fn ok(body: impl Into<Vec<u8>>) -> HttpResponse {
+    response(200, body)
+}
+
+/// Returns a 200 response with the value serialized as JSON.
+pub fn json(value: &impl Serialize) -> HttpResponse {
+    ok(serde_json::to_vec(value).unwrap())
+}
+
+/// Returns a 404 response.
+pub fn not_found() -> HttpResponse {
+    response(404, "not found")
+}
+
+/// Returns a random output with the ID, booked at milestone 5 and read at `ledger_index`.
+pub fn output_with_metadata(output_id: &OutputId, is_spent: bool, ledger_index: u32) -> OutputWithMetadataResponse {
+    OutputWithMetadataResponse {
+        metadata: OutputMetadataDto {
+            block_id: rand_block_id().to_string(),
+            transaction_id: output_id.transaction_id().to_string(),
+            output_index: output_id.index(),
+            is_spent,
+            milestone_index_spent: None,
+            milestone_timestamp_spent: None,
+            transaction_id_spent: None,
+            milestone_index_booked: 5,
+            milestone_timestamp_booked: 0,
+            ledger_index,
+        },
+        output: OutputDto::from(&rand_output(TOKEN_SUPPLY)),
+    }
+}
+
+/// Returns a client builder with the nodes that ignores the node health and sends all requests through the transport.
+pub fn mock_client_builder(nodes: &[&str], transport: &MockTransport) -> ClientBuilder {
+    Client::builder()
+        .with_nodes(nodes)
+        .unwrap()
+        .with_ignore_node_health()
+        .with_http_transport(transport.clone())
+}
+
+/// Returns a client with the node `NODE_LOCAL` that sends all requests through the transport.
+pub fn mock_client(transport: &MockTransport) -> Client {
+    mock_client_builder(&[NODE_LOCAL], transport).finish().unwrap()
+}
@@ -1,16 +1,20 @@
 // Copyright 2023 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-#![allow(clippy::redundant_pub_crate, dead_code)]
+#![allow(clippy::redundant_pub_crate, dead_code, unused_imports)]
 
 mod constants;
+mod mock;
 
 use iota_client::{
     constants::SHIMMER_COIN_TYPE, node_api::indexer::query_parameters::QueryParameter, request_funds_from_faucet,
-    secret::SecretManager, Client, Result,
+    secret::SecretManager, Client, MnemonicLength, Result,
 };
 
-pub use self::constants::{FAUCET_URL, NODE_LOCAL};
+pub use self::{
+    constants::{FAUCET_URL, NODE_LOCAL},
+    mock::{json, mock_client, mock_client_builder, not_found, ok, output_with_metadata, response, MockTransport},
+};
 
 /// Sets up a Client with node health ignored.
 pub fn setup_client_with_node_health_ignored() -> Client {
@@ -22,7 +26,9 @@ pub fn setup_client_with_node_health_ignored() -> Client {
 pub async fn create_client_and_secret_manager_with_funds(mnemonic: Option<&str>) -> Result<(Client, SecretManager)> {
     let client = Client::builder().with_node(NODE_LOCAL)?.finish()?;
 
-    let secret_manager = SecretManager::try_from_mnemonic(mnemonic.unwrap_or(&Client::generate_mnemonic().unwrap()))?;
+    let secret_manager = SecretManager::try_from_mnemonic(
+        mnemonic.unwrap_or(&Client::generate_mnemonic(MnemonicLength::Words24).unwrap()),
+    )?;
 
     let address = &client
         .get_addresses(&secret_manager)
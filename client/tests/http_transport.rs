@@ -0,0 +1,117 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+mod common;
+
+use std::{collections::HashSet, sync::Mutex, time::Duration};
+
+use iota_client::{
+    node_manager::{http_client::HttpMethod, retry::RetryPolicy},
+    ClientBuilder, Error,
+};
+use iota_types::block::rand::block::rand_block;
+
+use self::common::{mock_client_builder, not_found, ok, response, MockTransport, NODE_LOCAL};
+
+const TIP: &str = "0xe0b6e19a1d0a3a2d3b0a6a2ef4a91cd7d07e2b6a4b1e0fa9ba40ce7a6f3ae2c4";
+
+fn tips() -> Vec<u8> {
+    format!("{{\"tips\":[\"{TIP}\"]}}").into_bytes()
+}
+
+#[tokio::test]
+async fn custom_http_transport() {
+    let transport = MockTransport::new(|request| match (request.method, request.url.path()) {
+        (HttpMethod::Get, "/api/core/v2/tips") => ok(tips()),
+        _ => not_found(),
+    });
+    let client = mock_client_builder(&[NODE_LOCAL], &transport)
+        .with_user_agent("mock-agent".to_string())
+        .finish()
+        .unwrap();
+
+    let tips = client.get_tips().await.unwrap();
+    assert_eq!(tips.len(), 1);
+    assert_eq!(tips[0].to_string(), TIP);
+
+    assert!(matches!(client.get_routes().await, Err(Error::NotFound(_))));
+
+    // The node syncing also goes through the custom transport.
+    assert!(transport.requests_to("/api/core/v2/info") > 0);
+
+    let requests = transport.requests();
+    let tips_request = requests.iter().find(|r| r.url.path() == "/api/core/v2/tips").unwrap();
+    assert_eq!(tips_request.header("user-agent"), Some("mock-agent"));
+    assert_eq!(tips_request.body, None);
+}
+
+#[tokio::test]
+async fn retry_policy() {
+    // Fails the first request to every route with a 503.
+    let attempted_paths = Mutex::new(HashSet::new());
+    let transport = MockTransport::new(move |request| {
+        let first_attempt = attempted_paths.lock().unwrap().insert(request.url.path().to_string());
+        match (first_attempt, request.method, request.url.path()) {
+            (true, _, _) => response(503, "service unavailable"),
+            (false, HttpMethod::Get, "/api/core/v2/tips") => ok(tips()),
+            _ => not_found(),
+        }
+    });
+    let client = mock_client_builder(&[NODE_LOCAL], &transport)
+        .with_retry_policy(RetryPolicy::default().with_backoff(Duration::from_millis(1), Duration::from_millis(10)))
+        .finish()
+        .unwrap();
+
+    // GET requests are retried after a transient error.
+    let tips = client.get_tips().await.unwrap();
+    assert_eq!(tips.len(), 1);
+
+    // Submitting a block isn't idempotent and must only be sent once.
+    assert!(matches!(
+        client.post_block_raw(&rand_block()).await,
+        Err(Error::Node(_))
+    ));
+
+    assert_eq!(transport.requests_to("/api/core/v2/tips"), 2);
+    assert_eq!(transport.requests_to("/api/core/v2/blocks"), 1);
+}
+
+#[test]
+fn retry_policy_from_json() {
+    let client_builder = ClientBuilder::new()
+        .from_json(
+            r#"{
+                "nodes": ["http://localhost:14265"],
+                "retryPolicy": {
+                    "maxAttempts": 5,
+                    "retryStatusCodes": [503],
+                    "idempotentPostRoutes": ["api/core/v2/outputs"]
+                }
+            }"#,
+        )
+        .unwrap();
+    let retry_policy = client_builder.node_manager_builder.retry_policy;
+
+    assert_eq!(retry_policy.max_attempts, 5);
+    assert_eq!(retry_policy.retry_status_codes, vec![503]);
+    // Missing fields are set to their defaults.
+    assert_eq!(retry_policy.initial_backoff, RetryPolicy::default().initial_backoff);
+    assert!(retry_policy.is_retryable(
+        HttpMethod::Post,
+        "api/core/v2/outputs",
+        &Error::ResponseError {
+            code: 503,
+            text: String::new(),
+            url: String::new(),
+        }
+    ));
+    assert!(!retry_policy.is_retryable(
+        HttpMethod::Post,
+        "api/core/v2/blocks",
+        &Error::ResponseError {
+            code: 503,
+            text: String::new(),
+            url: String::new(),
+        }
+    ));
+}
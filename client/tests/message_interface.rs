@@ -216,6 +216,102 @@ async fn stronghold() {
     std::fs::remove_file("teststronghold.stronghold").unwrap_or(());
 }
 
+#[cfg(feature = "stronghold")]
+#[tokio::test]
+async fn migrate_stronghold_snapshot() {
+    use iota_client::stronghold::migrate::{SnapshotKeyDerivation, DEFAULT_SNAPSHOT_VERSION};
+
+    let message_handler = message_interface::create_message_handler(None).unwrap();
+
+    let secret_manager_dto = r#"{"stronghold": {"password": "some_hopefully_secure_password", "snapshotPath": "test_migrate_message.stronghold"}}"#;
+    let message = Message::StoreMnemonic {
+        secret_manager: serde_json::from_str(secret_manager_dto).unwrap(),
+        mnemonic: "acoustic trophy damage hint search taste love bicycle foster cradle brown govern endless depend situate athlete pudding blame question genius transfer van random vast".to_string(),
+    };
+    let _response = message_handler.send_message(message).await;
+
+    let message = Message::MigrateStrongholdSnapshot {
+        snapshot_path: "test_migrate_message.stronghold".into(),
+        password: "some_hopefully_secure_password".to_string(),
+        new_snapshot_path: Some("test_migrate_message_migrated.stronghold".into()),
+        key_derivation: Some(SnapshotKeyDerivation::Pbkdf2Sha512Rounds100k),
+        new_password: Some("another_hopefully_secure_password".to_string()),
+    };
+    let response = message_handler.send_message(message).await;
+
+    match response {
+        Response::MigratedStrongholdSnapshot(migration) => {
+            assert_eq!(migration.from, DEFAULT_SNAPSHOT_VERSION);
+            assert_eq!(
+                migration.to.key_derivation,
+                SnapshotKeyDerivation::Pbkdf2Sha512Rounds100k
+            );
+            assert_eq!(
+                migration.path,
+                std::path::PathBuf::from("test_migrate_message_migrated.stronghold")
+            );
+            assert_eq!(
+                migration.backup_path,
+                std::path::PathBuf::from("test_migrate_message.stronghold")
+            );
+        }
+        response_type => panic!("Unexpected response type: {response_type:?}"),
+    }
+
+    // The migrated snapshot holds the same mnemonic, encrypted with the new password
+    let message = Message::GenerateAddresses {
+        secret_manager: serde_json::from_str(
+            r#"{"stronghold": {"password": "another_hopefully_secure_password", "snapshotPath": "test_migrate_message_migrated.stronghold", "keyDerivation": "pbkdf2Sha512Rounds100k"}}"#,
+        )
+        .unwrap(),
+        options: GenerateAddressesOptions {
+            coin_type: None,
+            account_index: None,
+            range: Some(std::ops::Range { start: 0, end: 1 }),
+            internal: None,
+            bech32_hrp: Some("rms".to_string()),
+            options: None,
+        },
+    };
+    let response = message_handler.send_message(message).await;
+
+    match response {
+        Response::GeneratedAddresses(addresses) => {
+            assert_eq!(
+                addresses[0],
+                "rms1qzev36lk0gzld0k28fd2fauz26qqzh4hd4cwymlqlv96x7phjxcw6v3ea5a".to_string(),
+            );
+        }
+        response_type => panic!("Unexpected response type: {response_type:?}"),
+    }
+
+    std::fs::remove_file("test_migrate_message.stronghold").unwrap_or(());
+    std::fs::remove_file("test_migrate_message_migrated.stronghold").unwrap_or(());
+}
+
+#[tokio::test]
+async fn generate_mnemonic() {
+    let message_handler = message_interface::create_message_handler(None).unwrap();
+
+    for (message, word_count, separator) in [
+        (r#"{"name": "generateMnemonic", "data": {}}"#, 24, ' '),
+        (
+            r#"{"name": "generateMnemonic", "data": {"length": 12, "language": "japanese"}}"#,
+            12,
+            '\u{3000}',
+        ),
+    ] {
+        let message = serde_json::from_str::<Message>(message).unwrap();
+
+        match message_handler.send_message(message).await {
+            Response::GeneratedMnemonic(mnemonic) => {
+                assert_eq!(mnemonic.split(separator).count(), word_count);
+            }
+            response_type => panic!("Unexpected response type: {response_type:?}"),
+        }
+    }
+}
+
 #[tokio::test]
 async fn hash_transaction_essence() {
     let message_handler = message_interface::create_message_handler(None).unwrap();
@@ -0,0 +1,115 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(feature = "mqtt")]
+
+mod common;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use futures::StreamExt;
+use iota_client::{
+    mqtt::{MqttPayload, Topic, TopicEvent},
+    Error,
+};
+use iota_types::block::{
+    payload::{
+        milestone::{dto::MilestonePayloadDto, MilestoneEssence, MilestoneIndex, MilestoneOptions},
+        MilestonePayload,
+    },
+    protocol::ProtocolParameters,
+    rand::{
+        bytes::{rand_bytes, rand_bytes_array},
+        milestone::{rand_merkle_root, rand_milestone_id},
+        parents::rand_parents,
+    },
+    signature::{Ed25519Signature, Signature},
+};
+
+use self::common::{json, mock_client, not_found, ok, MockTransport};
+
+const MILESTONES_PATH: &str = "/api/core/v2/milestones/by-index/";
+
+fn milestone(index: u32) -> MilestonePayload {
+    let essence = MilestoneEssence::new(
+        MilestoneIndex::new(index),
+        index,
+        ProtocolParameters::default().protocol_version(),
+        rand_milestone_id(),
+        rand_parents(),
+        rand_merkle_root(),
+        rand_merkle_root(),
+        rand_bytes(32),
+        MilestoneOptions::new(vec![]).unwrap(),
+    )
+    .unwrap();
+    let signatures = vec![Signature::from(Ed25519Signature::new(
+        rand_bytes_array(),
+        rand_bytes_array(),
+    ))];
+
+    MilestonePayload::new(essence, signatures).unwrap()
+}
+
+fn milestone_event(index: u32) -> Result<TopicEvent, iota_client::mqtt::Error> {
+    Ok(TopicEvent {
+        topic: Topic::Milestones,
+        payload: MqttPayload::MilestonePayload(milestone(index)),
+    })
+}
+
+#[tokio::test]
+async fn milestone_feed_backfill() {
+    // Answers milestone and UTXO changes requests, apart from the first request of milestone 5.
+    let failed = AtomicBool::new(false);
+    let transport = MockTransport::new(move |request| {
+        let Some(path) = request.url.path().strip_prefix(MILESTONES_PATH) else {
+            return not_found();
+        };
+
+        match path.strip_suffix("/utxo-changes") {
+            Some(index) => ok(format!(
+                "{{\"index\":{index},\"createdOutputs\":[],\"consumedOutputs\":[]}}"
+            )),
+            None if path == "5" && !failed.swap(true, Ordering::SeqCst) => not_found(),
+            None => json(&MilestonePayloadDto::from(&milestone(path.parse().unwrap()))),
+        }
+    });
+    let client = mock_client(&transport);
+
+    // Milestones 1, 2, 4 and 5 are missed, 3 is received twice.
+    let events = futures::stream::iter([3, 3, 6].map(milestone_event));
+    let feed = client.milestone_feed_from_events(events, Some(1));
+    let feed = feed
+        .map(|event| event.map(|event| (event.index, event.backfilled)))
+        .collect::<Vec<_>>()
+        .await;
+
+    assert_eq!(feed.len(), 7);
+    assert_eq!(
+        feed[..4]
+            .iter()
+            .map(|event| *event.as_ref().unwrap())
+            .collect::<Vec<_>>(),
+        [(1, true), (2, true), (3, false), (4, true)]
+    );
+    // The failed request is returned and retried on the next poll, keeping the order.
+    assert!(matches!(feed[4], Err(Error::NotFound(_))));
+    assert_eq!(
+        feed[5..]
+            .iter()
+            .map(|event| *event.as_ref().unwrap())
+            .collect::<Vec<_>>(),
+        [(5, true), (6, false)]
+    );
+
+    let milestone_requests = |index: u32| transport.requests_to(&format!("{MILESTONES_PATH}{index}"));
+    let utxo_changes_requests = |index: u32| transport.requests_to(&format!("{MILESTONES_PATH}{index}/utxo-changes"));
+
+    // Only the missed milestones are requested, the UTXO changes of all of them.
+    assert_eq!((1..=6).map(milestone_requests).collect::<Vec<_>>(), [1, 1, 0, 1, 2, 0]);
+    assert_eq!(
+        (1..=6).map(utxo_changes_requests).collect::<Vec<_>>(),
+        [1, 1, 1, 1, 1, 1]
+    );
+}
@@ -1,11 +1,12 @@
 // Copyright 2021 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use iota_client::{Client, Result};
+use iota_client::{Client, MnemonicLanguage, MnemonicLength, Result};
+use unicode_normalization::UnicodeNormalization;
 
 #[tokio::test]
 async fn mnemonic() -> Result<()> {
-    let mnemonic = Client::generate_mnemonic()?;
+    let mnemonic = Client::generate_mnemonic(MnemonicLength::Words24)?;
     assert!(Client::mnemonic_to_hex_seed(&mnemonic).is_ok());
     assert!(Client::mnemonic_to_hex_seed("until fire hat mountain zoo grocery real deny advance change marble taste goat ivory wheat bubble panic banner tattoo client ticket action race rocket").is_ok());
     assert!(Client::mnemonic_to_hex_seed("fire until hat mountain zoo grocery real deny advance change marble taste goat ivory wheat bubble panic banner tattoo client ticket action race rocket").is_err());
@@ -19,3 +20,89 @@ async fn mnemonic() -> Result<()> {
     );
     Ok(())
 }
+
+#[test]
+fn mnemonic_lengths() -> Result<()> {
+    for (word_count, length) in [
+        (12, MnemonicLength::Words12),
+        (15, MnemonicLength::Words15),
+        (18, MnemonicLength::Words18),
+        (21, MnemonicLength::Words21),
+        (24, MnemonicLength::Words24),
+    ] {
+        assert_eq!(MnemonicLength::try_from(word_count)?, length);
+
+        let mnemonic = Client::generate_mnemonic(length)?;
+        assert_eq!(mnemonic.split_whitespace().count(), word_count as usize);
+        assert!(Client::mnemonic_to_hex_seed(&mnemonic).is_ok());
+    }
+
+    assert!(MnemonicLength::try_from(13).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn mnemonic_passphrase() -> Result<()> {
+    // Test vector of BIP-39.
+    let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    assert_eq!(
+        iota_client::mnemonic_to_hex_seed_with_passphrase(mnemonic, "TREZOR", MnemonicLanguage::English)?,
+        "0xc55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04"
+    );
+    assert_ne!(
+        iota_client::mnemonic_to_hex_seed_with_passphrase(mnemonic, "TREZOR", MnemonicLanguage::English)?,
+        Client::mnemonic_to_hex_seed(mnemonic)?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn mnemonic_japanese() -> Result<()> {
+    // Official BIP-39 test vectors of the Japanese wordlist.
+    #[derive(serde::Deserialize)]
+    struct Bip39Vector {
+        mnemonic: String,
+        passphrase: String,
+        seed: String,
+    }
+
+    let file = std::fs::File::open("./tests/fixtures/bip39_japanese.json").unwrap();
+    let vectors: Vec<Bip39Vector> = serde_json::from_reader(file).unwrap();
+
+    for vector in &vectors {
+        let seed = format!("0x{}", vector.seed);
+        assert!(iota_client::verify_mnemonic(&vector.mnemonic, MnemonicLanguage::Japanese).is_ok());
+        assert!(iota_client::verify_mnemonic(&vector.mnemonic, MnemonicLanguage::English).is_err());
+        assert_eq!(
+            iota_client::mnemonic_to_hex_seed_with_passphrase(
+                &vector.mnemonic,
+                &vector.passphrase,
+                MnemonicLanguage::Japanese
+            )?,
+            seed
+        );
+        // Decomposed mnemonics separated by spaces are normalized to the same seed.
+        let decomposed_mnemonic = vector
+            .mnemonic
+            .nfd()
+            .map(|c| if c == '\u{3000}' { ' ' } else { c })
+            .collect::<String>();
+        assert_eq!(
+            iota_client::mnemonic_to_hex_seed_with_passphrase(
+                &decomposed_mnemonic,
+                &vector.passphrase,
+                MnemonicLanguage::Japanese
+            )?,
+            seed
+        );
+        assert!(Client::mnemonic_to_hex_seed(&vector.mnemonic).is_err());
+    }
+
+    let mnemonic = Client::generate_mnemonic_with_language(MnemonicLength::Words12, MnemonicLanguage::Japanese)?;
+    assert!(iota_client::verify_mnemonic(&mnemonic, MnemonicLanguage::Japanese).is_ok());
+
+    Ok(())
+}
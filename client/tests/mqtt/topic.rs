@@ -1,7 +1,12 @@
 // Copyright 2023 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use iota_client::mqtt::{Error, Topic};
+use std::str::FromStr;
+
+use iota_client::{
+    block::BlockId,
+    mqtt::{Error, Topic, UnlockKind},
+};
 
 #[test]
 fn valid_topics() {
@@ -87,3 +92,54 @@ fn invalid_tags() {
         Err(Error::InvalidTopic(_))
     ));
 }
+
+#[test]
+fn typed_topics() {
+    let block_id = "0x36845227a59864ac12d3d2389fcb4ea0bdd1a5d1d4ed464bde3154216c3246c4";
+    let address = "iota1qrwfnskm4f7utdrxqnkfntfqxehtpj8s0kf68zkcwm0yrhuemzjp5sjfw5v";
+
+    assert_eq!(
+        Topic::try_new(format!("block-metadata/{block_id}")).unwrap(),
+        Topic::BlockMetadata(BlockId::from_str(block_id).unwrap())
+    );
+    assert_eq!(
+        Topic::try_new("block-metadata/referenced").unwrap(),
+        Topic::ReferencedBlockMetadata
+    );
+    assert_eq!(
+        Topic::try_new(format!("outputs/unlock/+/{address}/spent")).unwrap(),
+        Topic::OutputsByUnlock {
+            kind: UnlockKind::Any,
+            address: address.to_string(),
+            spent: true,
+        }
+    );
+    assert_eq!(
+        Topic::try_new("blocks/tagged-data/0x0123456789abcdef").unwrap(),
+        Topic::TaggedDataBlocks {
+            tag: Some(vec![0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef])
+        }
+    );
+
+    // Typed topics are formatted as the topics they're parsed from.
+    for topic in [
+        format!("block-metadata/{block_id}"),
+        format!("transactions/{block_id}/included-block"),
+        format!("outputs/{block_id}0100"),
+        format!("outputs/unlock/storage-return/{address}"),
+        format!("outputs/unlock/immutable-alias/{address}/spent"),
+        "blocks/transaction/tagged-data/0x0123456789abcdef".to_string(),
+        "milestone-info/confirmed".to_string(),
+    ] {
+        assert_eq!(Topic::try_new(topic.clone()).unwrap().to_string(), topic);
+    }
+
+    assert!(matches!(
+        Topic::try_new(format!("outputs/unlock/sender/{address}")),
+        Err(Error::InvalidTopic(_))
+    ));
+    assert!(matches!(
+        Topic::try_new(format!("outputs/unlock/address/{address}/unspent")),
+        Err(Error::InvalidTopic(_))
+    ));
+}
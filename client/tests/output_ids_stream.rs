@@ -0,0 +1,120 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+mod common;
+
+use std::str::FromStr;
+
+use futures::{StreamExt, TryStreamExt};
+use iota_client::{node_api::indexer::query_parameters::QueryParameter, Error};
+use iota_types::block::output::OutputId;
+
+use self::common::{json, mock_client, not_found, ok, output_with_metadata, MockTransport};
+
+const TRANSACTION_ID: &str = "0x1e857d380f813d8035e487b6dfd2ff4740b6775273ba1b576f01381ba2a1a44c";
+const INDEXER_PATH: &str = "/api/indexer/v1/outputs/nft";
+const PAGES: u16 = 3;
+
+fn output_id(index: u16) -> String {
+    OutputId::new(TRANSACTION_ID.parse().unwrap(), index)
+        .unwrap()
+        .to_string()
+}
+
+// Serves `PAGES` pages of NFT output IDs with two IDs each, the cursor is the index of the next page.
+fn pages_transport() -> MockTransport {
+    MockTransport::new(|request| {
+        let path = request.url.path();
+
+        if path == INDEXER_PATH {
+            let page = request
+                .url
+                .query_pairs()
+                .find(|(key, _)| key == "cursor")
+                .map_or(0, |(_, cursor)| cursor.parse::<u16>().unwrap());
+            let cursor = if page + 1 < PAGES {
+                format!("\"{}\"", page + 1)
+            } else {
+                "null".to_string()
+            };
+            ok(format!(
+                "{{\"ledgerIndex\":10,\"cursor\":{cursor},\"items\":[\"{}\",\"{}\"]}}",
+                output_id(page * 2),
+                output_id(page * 2 + 1)
+            ))
+        } else if let Some(output_id) = path.strip_prefix("/api/core/v2/outputs/") {
+            json(&output_with_metadata(
+                &OutputId::from_str(output_id).unwrap(),
+                false,
+                10,
+            ))
+        } else {
+            not_found()
+        }
+    })
+}
+
+#[tokio::test]
+async fn output_ids_stream_pages() {
+    let transport = pages_transport();
+    let client = mock_client(&transport);
+
+    let pages = client
+        .nft_output_ids_stream(Vec::new())
+        .unwrap()
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    assert_eq!(pages.len(), usize::from(PAGES));
+    assert_eq!(pages[2].items[1].to_string(), output_id(5));
+    assert_eq!(transport.requests_to(INDEXER_PATH), usize::from(PAGES));
+}
+
+#[tokio::test]
+async fn output_ids_stream_early_termination() {
+    let transport = pages_transport();
+    let client = mock_client(&transport);
+
+    // Nothing is requested before the stream is polled and no further pages after it's dropped.
+    let stream = client.nft_output_ids_stream(Vec::new()).unwrap();
+    assert_eq!(transport.requests_to(INDEXER_PATH), 0);
+    let first_page = stream.take(1).try_collect::<Vec<_>>().await.unwrap();
+    assert_eq!(first_page[0].items.len(), 2);
+    assert_eq!(transport.requests_to(INDEXER_PATH), 1);
+
+    // Pagination starts at a provided cursor.
+    let pages = client
+        .nft_output_ids_stream(vec![QueryParameter::Cursor("2".to_string())])
+        .unwrap()
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    assert_eq!(pages.len(), 1);
+    assert_eq!(pages[0].items[0].to_string(), output_id(4));
+}
+
+#[tokio::test]
+async fn output_ids_stream_resolve_outputs() {
+    let client = mock_client(&pages_transport());
+
+    let outputs = client
+        .outputs_stream(client.nft_output_ids_stream(Vec::new()).unwrap(), 2)
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+
+    // All outputs are resolved in the order of their IDs.
+    assert_eq!(outputs.len(), usize::from(PAGES) * 2);
+    for (index, output) in outputs.iter().enumerate() {
+        assert_eq!(
+            output.metadata.output_id().unwrap().to_string(),
+            output_id(index as u16)
+        );
+    }
+
+    // Invalid query parameters are rejected before any request is sent.
+    assert!(matches!(
+        client.nft_output_ids_stream(vec![QueryParameter::StateController(String::new())]),
+        Err(Error::UnsupportedQueryParameter(_))
+    ));
+}
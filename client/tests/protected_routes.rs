@@ -0,0 +1,139 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+mod common;
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use iota_client::{
+    node_api::core::routes::PruneDatabaseTarget,
+    node_manager::{http_client::HttpMethod, node::NodeAuth},
+    Client, Error,
+};
+
+use self::common::{mock_client_builder, not_found, response, MockTransport};
+
+const PEER_ID: &str = "12D3KooWRbZHCpZ2BWmM1TgYhjKzbtdaJoTE7MFHAJomrJZuaaxM";
+const PEER: &str = r#"{"id":"12D3KooWRbZHCpZ2BWmM1TgYhjKzbtdaJoTE7MFHAJomrJZuaaxM","multiAddresses":["/ip4/127.0.0.1/tcp/15600"],"alias":"peer","relation":"known","connected":true}"#;
+
+// Answers the peers and control routes like a node that requires a JWT, or with a 503 while `unavailable` is set.
+fn admin_transport(unavailable: Arc<AtomicBool>) -> MockTransport {
+    MockTransport::new(move |request| {
+        let path = request.url.path();
+        if path == "/api/core/v2/info" {
+            not_found()
+        } else if unavailable.load(Ordering::SeqCst) {
+            response(503, "unavailable")
+        } else if request.header("Authorization") != Some("Bearer secret") {
+            response(401, "unauthorized")
+        } else {
+            match (request.method, path) {
+                (HttpMethod::Get, "/api/core/v2/peers") => response(200, format!("[{PEER}]")),
+                (HttpMethod::Post, "/api/core/v2/peers") => response(201, PEER),
+                (HttpMethod::Delete, path) if path == format!("/api/core/v2/peers/{PEER_ID}") => response(204, ""),
+                (HttpMethod::Post, "/api/core/v2/control/database/prune") => response(200, "{\"index\":10}"),
+                _ => not_found(),
+            }
+        }
+    })
+}
+
+fn client(transport: &MockTransport) -> Client {
+    mock_client_builder(&["http://node-a:14265"], transport)
+        .with_node_auth(
+            "http://node-b:14265",
+            Some(NodeAuth {
+                jwt: Some("secret".to_string()),
+                basic_auth_name_pwd: None,
+            }),
+        )
+        .unwrap()
+        .finish()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn protected_routes() {
+    let transport = admin_transport(Arc::default());
+    let client = client(&transport);
+
+    // Protected requests are only sent to the node with a JWT.
+    assert_eq!(client.get_peers().await.unwrap()[0].id, PEER_ID);
+    let peer = client.add_peer("/ip4/127.0.0.1/tcp/15600", Some("peer")).await.unwrap();
+    assert_eq!(peer.alias.as_deref(), Some("peer"));
+    client.remove_peer(PEER_ID).await.unwrap();
+    assert_eq!(
+        client
+            .prune_database(PruneDatabaseTarget::Depth(100))
+            .await
+            .unwrap()
+            .index,
+        10
+    );
+    assert!(matches!(client.get_peer("unknown").await, Err(Error::NotFound(_))));
+
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 5);
+    assert!(requests.iter().all(|request| request.url.host_str() == Some("node-b")));
+    assert_eq!(requests[2].method, HttpMethod::Delete);
+
+    let body =
+        |index: usize| serde_json::from_slice::<serde_json::Value>(requests[index].body.as_ref().unwrap()).unwrap();
+    assert_eq!(
+        body(1),
+        serde_json::json!({ "multiAddress": "/ip4/127.0.0.1/tcp/15600", "alias": "peer" })
+    );
+    assert_eq!(body(3), serde_json::json!({ "depth": 100 }));
+}
+
+#[tokio::test]
+async fn protected_routes_primary_node() {
+    let unavailable = Arc::new(AtomicBool::new(true));
+    let transport = admin_transport(unavailable.clone());
+    let auth = Some(NodeAuth {
+        jwt: Some("secret".to_string()),
+        basic_auth_name_pwd: None,
+    });
+    let client = mock_client_builder(&[], &transport)
+        .with_primary_node("http://node-a:14265", auth.clone())
+        .unwrap()
+        .with_node_auth("http://node-b:14265", auth)
+        .unwrap()
+        .finish()
+        .unwrap();
+
+    // The failed attempts bench the primary node, but protected requests are still only sent to it.
+    assert!(client.get_peers().await.is_err());
+    unavailable.store(false, Ordering::SeqCst);
+    assert_eq!(client.get_peers().await.unwrap()[0].id, PEER_ID);
+
+    assert!(transport
+        .requests()
+        .iter()
+        .all(|request| request.url.host_str() == Some("node-a")));
+}
+
+#[tokio::test]
+async fn protected_routes_without_jwt() {
+    let transport = admin_transport(Arc::default());
+    let client = mock_client_builder(&["http://node-a:14265"], &transport)
+        .finish()
+        .unwrap();
+
+    assert!(matches!(
+        client.get_peer(PEER_ID).await,
+        Err(Error::NoProtectedRouteNode(_))
+    ));
+    assert!(transport.requests().is_empty());
+
+    // Getting the peers falls back to the nodes without a JWT, which this one rejects.
+    assert!(client.get_peers().await.is_err());
+    let requests = transport.requests();
+    assert!(!requests.is_empty());
+    assert!(requests.iter().all(|request| request.url.host_str() == Some("node-a")
+        && request.url.path() == "/api/core/v2/peers"
+        && request.header("Authorization").is_none()));
+}
@@ -0,0 +1,96 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+mod common;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use iota_client::{node_api::indexer::query_parameters::QueryParameter, Client, Error};
+use iota_types::block::rand::{block::rand_block_id, output::rand_output_id};
+
+use self::common::{mock_client_builder, not_found, ok, MockTransport};
+
+const OUTPUT_ID: &str = "0x1e857d380f813d8035e487b6dfd2ff4740b6775273ba1b576f01381ba2a1a44c0000";
+const INDEXER_PATH: &str = "/api/indexer/v1/outputs/basic";
+
+// Answers like two nodes, where node-b returns a different raw block and node-a lags one milestone behind on its first
+// indexer request.
+fn two_nodes_transport() -> MockTransport {
+    let node_a_lagging = AtomicBool::new(true);
+    MockTransport::new(move |request| {
+        let node_a = request.url.host_str() == Some("node-a");
+        let path = request.url.path();
+
+        if path.starts_with("/api/core/v2/outputs/") {
+            ok(vec![1, 2, 3])
+        } else if path.starts_with("/api/core/v2/blocks/") {
+            ok(if node_a { vec![1] } else { vec![2] })
+        } else if path == INDEXER_PATH {
+            let lagging = node_a && node_a_lagging.swap(false, Ordering::SeqCst);
+            let ledger_index = if lagging { 10 } else { 11 };
+            ok(format!(
+                "{{\"ledgerIndex\":{ledger_index},\"cursor\":null,\"items\":[\"{OUTPUT_ID}\"]}}"
+            ))
+        } else {
+            not_found()
+        }
+    })
+}
+
+fn quorum_client(transport: &MockTransport) -> Client {
+    mock_client_builder(&["http://node-a:14265", "http://node-b:14265"], transport)
+        .with_quorum(true)
+        .with_min_quorum_size(2)
+        .with_quorum_threshold(100)
+        .finish()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn quorum_raw_routes() {
+    let client = quorum_client(&two_nodes_transport());
+
+    assert_eq!(client.get_output_raw(&rand_output_id()).await.unwrap(), vec![1, 2, 3]);
+
+    let error = client.get_block_raw(&rand_block_id()).await.unwrap_err();
+    // The message only contains the node URLs and a summary of the responses, which are kept in the error.
+    let message = error.to_string();
+    assert!(message.contains("http://node-a:14265/api/core/v2/blocks/"));
+    assert!(message.contains("response of length 4 with hash 0x"));
+
+    match error {
+        Error::QuorumMismatch {
+            quorum_size, responses, ..
+        } => {
+            assert_eq!(quorum_size, 1);
+            assert_eq!(responses.len(), 2);
+            let node_a = responses.iter().find(|r| r.url.contains("node-a")).unwrap();
+            let node_b = responses.iter().find(|r| r.url.contains("node-b")).unwrap();
+            assert_eq!(node_a.response, "0x01");
+            assert_eq!(node_b.response, "0x02");
+        }
+        error => panic!("expected quorum mismatch, got {error:?}"),
+    }
+}
+
+#[tokio::test]
+async fn quorum_indexer_pinned_to_ledger_index() {
+    let transport = two_nodes_transport();
+    let client = quorum_client(&transport);
+
+    // The first responses are at different ledger indexes, so the request is repeated until both nodes agree.
+    let output_ids = client
+        .basic_output_ids(vec![QueryParameter::HasExpiration(false)])
+        .await
+        .unwrap();
+    assert_eq!(output_ids.ledger_index, 11);
+    assert_eq!(output_ids.items[0].to_string(), OUTPUT_ID);
+    assert_eq!(
+        transport
+            .requests()
+            .iter()
+            .filter(|r| r.url.host_str() == Some("node-a") && r.url.path() == INDEXER_PATH)
+            .count(),
+        2
+    );
+}
@@ -0,0 +1,235 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(feature = "remote_signer")]
+
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crypto::keys::slip10::Chain;
+use iota_client::{
+    api::GetAddressesBuilder,
+    constants::{HD_WALLET_TYPE, IOTA_COIN_TYPE, SHIMMER_COIN_TYPE, SHIMMER_TESTNET_BECH32_HRP},
+    secret::{
+        mnemonic::MnemonicSecretManager,
+        remote::{
+            RemoteSecretManager, RemoteSignerAllowList, RemoteSignerEndpoint, RemoteSignerEnvelope, RemoteSignerKey,
+            RemoteSignerRequest, RemoteSignerRequestKind, RemoteSignerService,
+        },
+        SecretManage, SecretManager,
+    },
+    Error, Result, Url,
+};
+use tokio::io::AsyncReadExt;
+
+const MNEMONIC: &str = "acoustic trophy damage hint search taste love bicycle foster cradle brown govern endless depend situate athlete pudding blame question genius transfer van random vast";
+const KEY: &str = "0x9f6c9a8d3ae8b4f1bd8fe35e0a3d29c4c8eb6ef4ac9b5a20b5e6b7e2a2c4f1d3";
+
+fn service(allow_list: RemoteSignerAllowList) -> Result<Arc<RemoteSignerService<MnemonicSecretManager>>> {
+    Ok(Arc::new(RemoteSignerService::new(
+        MnemonicSecretManager::try_from_mnemonic(MNEMONIC)?,
+        RemoteSignerKey::try_from_hex(KEY)?,
+        allow_list,
+    )))
+}
+
+fn allow_all() -> RemoteSignerAllowList {
+    RemoteSignerAllowList::new()
+        .with_request(RemoteSignerRequestKind::GenerateAddresses)
+        .with_request(RemoteSignerRequestKind::SignatureUnlock)
+        .with_request(RemoteSignerRequestKind::SignEd25519)
+        .with_coin_type(SHIMMER_COIN_TYPE)
+}
+
+async fn spawn_http_signer(allow_list: RemoteSignerAllowList) -> Result<RemoteSignerEndpoint> {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let url = Url::parse(&format!("http://{}", listener.local_addr()?))?;
+
+    tokio::spawn(service(allow_list)?.serve_http(listener));
+
+    Ok(RemoteSignerEndpoint::Http(url))
+}
+
+fn chain(coin_type: u32, account_index: u32) -> Chain {
+    Chain::from_u32_hardened(vec![HD_WALLET_TYPE, coin_type, account_index, 0, 0])
+}
+
+#[tokio::test]
+async fn remote_signer_http() -> Result<()> {
+    let endpoint = spawn_http_signer(allow_all()).await?;
+    let secret_manager = SecretManager::Remote(RemoteSecretManager::new(endpoint, RemoteSignerKey::try_from_hex(KEY)?));
+
+    let addresses = GetAddressesBuilder::new(&secret_manager)
+        .with_bech32_hrp(SHIMMER_TESTNET_BECH32_HRP)
+        .with_account_index(0)
+        .with_range(0..1)
+        .finish()
+        .await?;
+
+    assert_eq!(
+        addresses[0],
+        "rms1qzev36lk0gzld0k28fd2fauz26qqzh4hd4cwymlqlv96x7phjxcw6v3ea5a".to_string()
+    );
+
+    let chain = chain(SHIMMER_COIN_TYPE, 0);
+    let signature = secret_manager.sign_ed25519(b"message", &chain).await?;
+    let expected = MnemonicSecretManager::try_from_mnemonic(MNEMONIC)?
+        .sign_ed25519(b"message", &chain)
+        .await?;
+
+    assert_eq!(signature, expected);
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn remote_signer_unix() -> Result<()> {
+    let path = std::env::temp_dir().join(format!("remote_signer_unix_{}.sock", std::process::id()));
+    std::fs::remove_file(&path).unwrap_or(());
+    let listener = tokio::net::UnixListener::bind(&path)?;
+    tokio::spawn(service(allow_all())?.serve_unix(listener));
+
+    let secret_manager = RemoteSecretManager::new(
+        RemoteSignerEndpoint::Unix(path.clone()),
+        RemoteSignerKey::try_from_hex(KEY)?,
+    );
+    let addresses = secret_manager
+        .generate_addresses(SHIMMER_COIN_TYPE, 0, 0..2, true, None)
+        .await?;
+    let expected = MnemonicSecretManager::try_from_mnemonic(MNEMONIC)?
+        .generate_addresses(SHIMMER_COIN_TYPE, 0, 0..2, true, None)
+        .await?;
+
+    assert_eq!(addresses, expected);
+
+    // A client with another key is rejected.
+    let unauthenticated =
+        RemoteSecretManager::new(RemoteSignerEndpoint::Unix(path.clone()), RemoteSignerKey::generate()?);
+    assert!(matches!(
+        unauthenticated
+            .generate_addresses(SHIMMER_COIN_TYPE, 0, 0..1, false, None)
+            .await,
+        Err(Error::RemoteSigner(_))
+    ));
+
+    std::fs::remove_file(&path).unwrap_or(());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn remote_signer_allow_list() -> Result<()> {
+    let allow_list = RemoteSignerAllowList::new()
+        .with_request(RemoteSignerRequestKind::GenerateAddresses)
+        .with_coin_type(SHIMMER_COIN_TYPE)
+        .with_account_index(1);
+    let endpoint = spawn_http_signer(allow_list).await?;
+    let secret_manager = RemoteSecretManager::new(endpoint, RemoteSignerKey::try_from_hex(KEY)?);
+
+    assert!(secret_manager
+        .generate_addresses(SHIMMER_COIN_TYPE, 1, 0..1, false, None)
+        .await
+        .is_ok());
+
+    for result in [
+        secret_manager
+            .generate_addresses(SHIMMER_COIN_TYPE, 0, 0..1, false, None)
+            .await
+            .map(|_| ()),
+        secret_manager
+            .generate_addresses(IOTA_COIN_TYPE, 1, 0..1, false, None)
+            .await
+            .map(|_| ()),
+        secret_manager
+            .sign_ed25519(b"message", &chain(SHIMMER_COIN_TYPE, 1))
+            .await
+            .map(|_| ()),
+    ] {
+        match result {
+            Err(Error::RemoteSigner(error)) => assert!(error.contains("not allowed"), "{error}"),
+            other => panic!("expected a rejected request, got {other:?}"),
+        }
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn remote_signer_authentication() -> Result<()> {
+    let service = service(allow_all())?;
+    let key = RemoteSignerKey::try_from_hex(KEY)?;
+    let request = RemoteSignerRequest::SignEd25519 {
+        message: "0x00".to_string(),
+        chain: chain(SHIMMER_COIN_TYPE, 0),
+    };
+
+    // Requests with another key or a tampered payload are rejected.
+    let envelope = RemoteSignerEnvelope::request(&RemoteSignerKey::generate()?, &request)?;
+    assert!(matches!(
+        service.handle(&envelope).await,
+        Err(Error::RemoteSignerAuthentication)
+    ));
+
+    let mut envelope = RemoteSignerEnvelope::request(&key, &request)?;
+    envelope.payload = envelope.payload.replace("0x00", "0x01");
+    assert!(matches!(
+        service.handle(&envelope).await,
+        Err(Error::RemoteSignerAuthentication)
+    ));
+
+    // A request is only served once.
+    let envelope = RemoteSignerEnvelope::request(&key, &request)?;
+    let response = service.handle(&envelope).await?;
+    assert!(matches!(
+        service.handle(&envelope).await,
+        Err(Error::RemoteSignerAuthentication)
+    ));
+
+    // The response is bound to the nonce of its request and can't be used as a request.
+    assert!(response.open_response(&key, &envelope.nonce).is_ok());
+    assert!(matches!(
+        response.open_response(&key, "0x00"),
+        Err(Error::RemoteSignerAuthentication)
+    ));
+    assert!(matches!(
+        response.open_request(&key),
+        Err(Error::RemoteSignerAuthentication)
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn remote_signer_idle_connection() -> Result<()> {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let address = listener.local_addr()?;
+    let service = RemoteSignerService::new(
+        MnemonicSecretManager::try_from_mnemonic(MNEMONIC)?,
+        RemoteSignerKey::try_from_hex(KEY)?,
+        allow_all(),
+    )
+    .with_read_timeout(Duration::from_millis(100))
+    .with_max_connections(1);
+    tokio::spawn(Arc::new(service).serve_http(listener));
+
+    // A connection that doesn't send a request holds the only connection slot until its read timeout passed.
+    let mut idle_connection = tokio::net::TcpStream::connect(address).await?;
+    let started = Instant::now();
+    let secret_manager = RemoteSecretManager::new(
+        RemoteSignerEndpoint::Http(Url::parse(&format!("http://{address}"))?),
+        RemoteSignerKey::try_from_hex(KEY)?,
+    );
+    secret_manager
+        .generate_addresses(SHIMMER_COIN_TYPE, 0, 0..1, false, None)
+        .await?;
+    assert!(started.elapsed() >= Duration::from_millis(50));
+
+    let mut response = String::new();
+    idle_connection.read_to_string(&mut response).await?;
+    assert!(response.starts_with("HTTP/1.1 408 Request Timeout"));
+
+    Ok(())
+}
@@ -0,0 +1,181 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+mod common;
+
+use std::{str::FromStr, time::Duration};
+
+use iota_client::{
+    node_manager::cache::{CacheMetrics, ResponseCache},
+    Client, Error,
+};
+use iota_types::block::{
+    output::{OutputId, RentStructure},
+    parent::Parents,
+    payload::milestone::{
+        dto::MilestonePayloadDto, MerkleRoot, MilestoneEssence, MilestoneId, MilestoneOptions, MilestonePayload,
+    },
+    protocol::ProtocolParameters,
+    signature::{Ed25519Signature, Signature},
+    BlockId,
+};
+
+use self::common::{json, mock_client_builder, not_found, ok, MockTransport, NODE_LOCAL};
+
+const OUTPUT_ID: &str = "0x1e857d380f813d8035e487b6dfd2ff4740b6775273ba1b576f01381ba2a1a44c0000";
+
+// A milestone that isn't checked against any keys.
+fn milestone(index: u32) -> MilestonePayload {
+    let essence = MilestoneEssence::new(
+        index.into(),
+        0,
+        2,
+        MilestoneId::null(),
+        Parents::new(vec![BlockId::new([0; 32])]).unwrap(),
+        MerkleRoot::null(),
+        MerkleRoot::null(),
+        Vec::new(),
+        MilestoneOptions::new(Vec::new()).unwrap(),
+    )
+    .unwrap();
+
+    MilestonePayload::new(
+        essence,
+        vec![Signature::Ed25519(Ed25519Signature::new([0; 32], [0; 64]))],
+    )
+    .unwrap()
+}
+
+// Answers UTXO changes, output metadata and milestone requests. Milestone 2 is returned for every milestone request.
+fn cache_transport() -> MockTransport {
+    MockTransport::new(|request| {
+        match request.url.path() {
+        "/api/core/v2/milestones/by-index/1/utxo-changes" => ok(format!(
+            "{{\"index\":1,\"createdOutputs\":[\"{OUTPUT_ID}\"],\"consumedOutputs\":[]}}"
+        )),
+        path if path == format!("/api/core/v2/outputs/{OUTPUT_ID}/metadata") => ok(format!(
+            "{{\"blockId\":\"0x0000000000000000000000000000000000000000000000000000000000000000\",\"transactionId\":\"{}\",\"outputIndex\":0,\"isSpent\":false,\"milestoneIndexBooked\":1,\"milestoneTimestampBooked\":0,\"ledgerIndex\":1}}",
+            &OUTPUT_ID[..66]
+        )),
+        path if path.starts_with("/api/core/v2/milestones/") => json(&MilestonePayloadDto::from(&milestone(2))),
+        _ => not_found(),
+    }
+    })
+}
+
+fn client(transport: &MockTransport, response_cache: Option<ResponseCache>) -> Client {
+    let mut builder = mock_client_builder(&[NODE_LOCAL], transport);
+    if let Some(response_cache) = response_cache {
+        builder = builder.with_response_cache(response_cache);
+    }
+    builder.finish().unwrap()
+}
+
+#[tokio::test]
+async fn response_cache_disabled() {
+    let transport = cache_transport();
+    let client = client(&transport, None);
+
+    client.get_utxo_changes_by_index(1).await.unwrap();
+    client.get_utxo_changes_by_index(1).await.unwrap();
+    assert_eq!(transport.requests().len(), 2);
+    assert_eq!(client.response_cache_metrics(), None);
+}
+
+#[tokio::test]
+async fn response_cache_immutable() {
+    let transport = cache_transport();
+    let client = client(&transport, Some(ResponseCache::in_memory(1024)));
+
+    let utxo_changes = client.get_utxo_changes_by_index(1).await.unwrap();
+    assert_eq!(client.get_utxo_changes_by_index(1).await.unwrap(), utxo_changes);
+    assert_eq!(transport.requests().len(), 1);
+
+    // Failed requests aren't cached.
+    assert!(client.get_utxo_changes_by_index(2).await.is_err());
+    assert!(client.get_utxo_changes_by_index(2).await.is_err());
+    assert_eq!(transport.requests().len(), 3);
+
+    assert_eq!(
+        client.response_cache_metrics(),
+        Some(CacheMetrics { hits: 1, misses: 3 })
+    );
+
+    client.clear_response_cache().await.unwrap();
+    client.get_utxo_changes_by_index(1).await.unwrap();
+    assert_eq!(transport.requests().len(), 4);
+}
+
+#[tokio::test]
+async fn response_cache_mutable_ttl() {
+    let transport = cache_transport();
+    let client = client(
+        &transport,
+        Some(ResponseCache::in_memory(1024).with_mutable_ttl(Duration::from_millis(100))),
+    );
+    let output_id = OutputId::from_str(OUTPUT_ID).unwrap();
+
+    client.get_output_metadata(&output_id).await.unwrap();
+    client.get_output_metadata(&output_id).await.unwrap();
+    assert_eq!(transport.requests().len(), 1);
+
+    // Output metadata is requested again once the TTL passed.
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    client.get_output_metadata(&output_id).await.unwrap();
+    assert_eq!(transport.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn response_cache_rejected_milestone() {
+    let transport = cache_transport();
+    let client = client(&transport, Some(ResponseCache::in_memory(1024)));
+
+    // Another milestone than the requested one is neither returned nor cached.
+    for _ in 0..2 {
+        assert!(matches!(
+            client.get_milestone_by_index(1).await,
+            Err(Error::UnexpectedMilestone { .. })
+        ));
+        assert!(matches!(
+            client.get_milestone_by_id(&MilestoneId::null()).await,
+            Err(Error::UnexpectedMilestone { .. })
+        ));
+    }
+    assert_eq!(transport.requests().len(), 4);
+
+    let milestone = milestone(2);
+    assert_eq!(client.get_milestone_by_index(2).await.unwrap(), milestone);
+    assert_eq!(client.get_milestone_by_index(2).await.unwrap(), milestone);
+    assert_eq!(client.get_milestone_by_id(&milestone.id()).await.unwrap(), milestone);
+    assert_eq!(client.get_milestone_by_id(&milestone.id()).await.unwrap(), milestone);
+    assert_eq!(transport.requests().len(), 6);
+}
+
+#[tokio::test]
+async fn response_cache_shared_between_networks() {
+    let transport = cache_transport();
+    let response_cache = ResponseCache::in_memory(1024);
+    let client = client(&transport, Some(response_cache.clone()));
+
+    let mut builder = mock_client_builder(&[NODE_LOCAL], &transport).with_response_cache(response_cache);
+    builder.network_info.protocol_parameters = ProtocolParameters::new(
+        2,
+        String::from("other-network"),
+        String::from("rms"),
+        1500,
+        15,
+        RentStructure::default(),
+        1_813_620_509_061_365,
+    )
+    .unwrap();
+    let other_network_client = builder.finish().unwrap();
+
+    // Responses cached for one network aren't returned for another one.
+    client.get_utxo_changes_by_index(1).await.unwrap();
+    other_network_client.get_utxo_changes_by_index(1).await.unwrap();
+    assert_eq!(transport.requests().len(), 2);
+
+    client.get_utxo_changes_by_index(1).await.unwrap();
+    other_network_client.get_utxo_changes_by_index(1).await.unwrap();
+    assert_eq!(transport.requests().len(), 2);
+}
@@ -1,7 +1,25 @@
 // Copyright 2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use iota_client::{api::GetAddressesBuilder, constants::SHIMMER_TESTNET_BECH32_HRP, secret::SecretManager, Result};
+use std::{
+    ops::Range,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use crypto::keys::slip10::Chain;
+use iota_client::{
+    api::{GetAddressesBuilder, RemainderData},
+    block::{address::Address, signature::Ed25519Signature, unlock::Unlock},
+    constants::SHIMMER_TESTNET_BECH32_HRP,
+    secret::{
+        mnemonic::MnemonicSecretManager, types::InputSigningData, GenerateAddressOptions, SecretManage, SecretManager,
+        SecretManagerDto,
+    },
+    Error, MnemonicLanguage, Result,
+};
 
 #[tokio::test]
 async fn mnemonic_secret_manager_dto() -> Result<()> {
@@ -24,6 +42,38 @@ async fn mnemonic_secret_manager_dto() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn mnemonic_with_passphrase_secret_manager_dto() -> Result<()> {
+    let mnemonic = "acoustic trophy damage hint search taste love bicycle foster cradle brown govern endless depend situate athlete pudding blame question genius transfer van random vast";
+    let dto = format!(r#"{{"mnemonic": {{"mnemonic": "{mnemonic}", "passphrase": "passphrase"}}}}"#);
+    let secret_manager: SecretManager = dto.parse()?;
+    let expected = SecretManager::Mnemonic(MnemonicSecretManager::try_from_mnemonic_with_passphrase(
+        mnemonic,
+        "passphrase",
+        MnemonicLanguage::English,
+    )?);
+
+    let addresses = GetAddressesBuilder::new(&secret_manager)
+        .with_bech32_hrp(SHIMMER_TESTNET_BECH32_HRP)
+        .with_range(0..1)
+        .finish()
+        .await?;
+    let expected_addresses = GetAddressesBuilder::new(&expected)
+        .with_bech32_hrp(SHIMMER_TESTNET_BECH32_HRP)
+        .with_range(0..1)
+        .finish()
+        .await?;
+
+    assert_eq!(addresses, expected_addresses);
+    // Without passphrase the mnemonic generates a different address.
+    assert_ne!(
+        addresses,
+        vec!["rms1qzev36lk0gzld0k28fd2fauz26qqzh4hd4cwymlqlv96x7phjxcw6v3ea5a".to_string()]
+    );
+
+    Ok(())
+}
+
 #[cfg(feature = "stronghold")]
 #[tokio::test]
 async fn stronghold_secret_manager_dto() -> Result<()> {
@@ -93,3 +143,74 @@ async fn stronghold_mnemonic_missing() -> Result<()> {
     std::fs::remove_dir_all("stronghold_mnemonic_missing").unwrap_or(());
     Ok(())
 }
+
+// Delegates to a mnemonic secret manager and counts the generated addresses.
+struct CountingSecretManager {
+    inner: MnemonicSecretManager,
+    generated_addresses: Arc<AtomicUsize>,
+}
+
+#[async_trait::async_trait]
+impl SecretManage for CountingSecretManager {
+    async fn generate_addresses(
+        &self,
+        coin_type: u32,
+        account_index: u32,
+        address_indexes: Range<u32>,
+        internal: bool,
+        options: Option<GenerateAddressOptions>,
+    ) -> Result<Vec<Address>> {
+        self.generated_addresses
+            .fetch_add(address_indexes.len(), Ordering::SeqCst);
+        self.inner
+            .generate_addresses(coin_type, account_index, address_indexes, internal, options)
+            .await
+    }
+
+    async fn signature_unlock(
+        &self,
+        input: &InputSigningData,
+        essence_hash: &[u8; 32],
+        remainder: &Option<RemainderData>,
+    ) -> Result<Unlock> {
+        self.inner.signature_unlock(input, essence_hash, remainder).await
+    }
+
+    async fn sign_ed25519(&self, msg: &[u8], chain: &Chain) -> Result<Ed25519Signature> {
+        self.inner.sign_ed25519(msg, chain).await
+    }
+}
+
+#[tokio::test]
+async fn custom_secret_manager() -> Result<()> {
+    let generated_addresses = Arc::new(AtomicUsize::new(0));
+    let secret_manager = SecretManager::custom(CountingSecretManager {
+        inner: MnemonicSecretManager::try_from_mnemonic(
+            "acoustic trophy damage hint search taste love bicycle foster cradle brown govern endless depend situate athlete pudding blame question genius transfer van random vast",
+        )?,
+        generated_addresses: generated_addresses.clone(),
+    });
+
+    let addresses = GetAddressesBuilder::new(&secret_manager)
+        .with_bech32_hrp(SHIMMER_TESTNET_BECH32_HRP)
+        .with_account_index(0)
+        .with_range(0..2)
+        .finish()
+        .await?;
+
+    assert_eq!(
+        addresses[0],
+        "rms1qzev36lk0gzld0k28fd2fauz26qqzh4hd4cwymlqlv96x7phjxcw6v3ea5a".to_string()
+    );
+    assert_eq!(generated_addresses.load(Ordering::SeqCst), 2);
+
+    // A custom secret manager can be described, but not created, by its DTO.
+    let dto = SecretManagerDto::from(&secret_manager);
+    assert!(matches!(dto, SecretManagerDto::Custom));
+    assert!(matches!(
+        SecretManager::try_from(&dto),
+        Err(Error::CustomSecretManagerDto)
+    ));
+
+    Ok(())
+}
@@ -20,7 +20,7 @@ use iota_client::{
     },
     constants::{HD_WALLET_TYPE, SHIMMER_COIN_TYPE, SHIMMER_TESTNET_BECH32_HRP},
     secret::{SecretManage, SecretManageExt, SecretManager},
-    Client, Result,
+    Client, MnemonicLength, Result,
 };
 use iota_types::block::output::AliasId;
 
@@ -32,7 +32,7 @@ use crate::{
 
 #[tokio::test]
 async fn sign_alias_state_transition() -> Result<()> {
-    let secret_manager = SecretManager::try_from_mnemonic(&Client::generate_mnemonic()?)?;
+    let secret_manager = SecretManager::try_from_mnemonic(&Client::generate_mnemonic(MnemonicLength::Words24)?)?;
 
     let bech32_address_0 = &secret_manager
         .generate_addresses(SHIMMER_COIN_TYPE, 0, 0..1, false, None)
@@ -121,7 +121,7 @@ async fn sign_alias_state_transition() -> Result<()> {
 
 #[tokio::test]
 async fn sign_alias_governance_transition() -> Result<()> {
-    let secret_manager = SecretManager::try_from_mnemonic(&Client::generate_mnemonic()?)?;
+    let secret_manager = SecretManager::try_from_mnemonic(&Client::generate_mnemonic(MnemonicLength::Words24)?)?;
 
     let bech32_address_0 = &secret_manager
         .generate_addresses(SHIMMER_COIN_TYPE, 0, 0..1, false, None)
@@ -210,7 +210,7 @@ async fn sign_alias_governance_transition() -> Result<()> {
 
 #[tokio::test]
 async fn alias_reference_unlocks() -> Result<()> {
-    let secret_manager = SecretManager::try_from_mnemonic(&Client::generate_mnemonic()?)?;
+    let secret_manager = SecretManager::try_from_mnemonic(&Client::generate_mnemonic(MnemonicLength::Words24)?)?;
 
     let bech32_address_0 = &secret_manager
         .generate_addresses(SHIMMER_COIN_TYPE, 0, 0..1, false, None)
@@ -1,30 +1,36 @@
 // Copyright 2023 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+use std::str::FromStr;
+
 use crypto::keys::slip10::Chain;
 use iota_client::{
-    api::{transaction::validate_transaction_payload_length, verify_semantic, PreparedTransactionData},
+    api::{
+        transaction::validate_transaction_payload_length, verify_semantic, verify_semantic_with_diagnostic,
+        InMemoryLedger, PreparedTransactionData, TransactionValidator,
+    },
     block::{
         input::{Input, UtxoInput},
-        output::InputsCommitment,
+        output::{InputsCommitment, TokenId},
         payload::{
             transaction::{RegularTransactionEssence, TransactionEssence},
             TransactionPayload,
         },
         protocol::protocol_parameters,
-        semantic::ConflictReason,
-        unlock::{SignatureUnlock, Unlock},
+        semantic::{ConflictDiagnostic, ConflictReason, NativeTokenAmountMismatch},
+        unlock::{ReferenceUnlock, SignatureUnlock, Unlock, Unlocks},
     },
     constants::{HD_WALLET_TYPE, SHIMMER_COIN_TYPE, SHIMMER_TESTNET_BECH32_HRP},
     secret::{SecretManage, SecretManageExt, SecretManager},
-    Client, Result,
+    Client, MnemonicLength, Result,
 };
+use primitive_types::U256;
 
-use crate::{build_inputs, build_outputs, Build::Basic};
+use crate::{build_inputs, build_outputs, Build::Basic, TOKEN_ID_1, TOKEN_ID_2};
 
 #[tokio::test]
 async fn single_ed25519_unlock() -> Result<()> {
-    let secret_manager = SecretManager::try_from_mnemonic(&Client::generate_mnemonic()?)?;
+    let secret_manager = SecretManager::try_from_mnemonic(&Client::generate_mnemonic(MnemonicLength::Words24)?)?;
 
     let bech32_address_0 = &secret_manager
         .generate_addresses(SHIMMER_COIN_TYPE, 0, 0..1, false, None)
@@ -112,7 +118,7 @@ async fn single_ed25519_unlock() -> Result<()> {
 
 #[tokio::test]
 async fn ed25519_reference_unlocks() -> Result<()> {
-    let secret_manager = SecretManager::try_from_mnemonic(&Client::generate_mnemonic()?)?;
+    let secret_manager = SecretManager::try_from_mnemonic(&Client::generate_mnemonic(MnemonicLength::Words24)?)?;
 
     let bech32_address_0 = &secret_manager
         .generate_addresses(SHIMMER_COIN_TYPE, 0, 0..1, false, None)
@@ -246,7 +252,7 @@ async fn ed25519_reference_unlocks() -> Result<()> {
 
 #[tokio::test]
 async fn two_signature_unlocks() -> Result<()> {
-    let secret_manager = SecretManager::try_from_mnemonic(&Client::generate_mnemonic()?)?;
+    let secret_manager = SecretManager::try_from_mnemonic(&Client::generate_mnemonic(MnemonicLength::Words24)?)?;
 
     let bech32_address_0 = &secret_manager
         .generate_addresses(SHIMMER_COIN_TYPE, 0, 0..1, false, None)
@@ -354,3 +360,364 @@ async fn two_signature_unlocks() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn ed25519_reference_unlock_to_other_signature() -> Result<()> {
+    let secret_manager = SecretManager::try_from_mnemonic(&Client::generate_mnemonic(MnemonicLength::Words24)?)?;
+
+    let bech32_addresses = secret_manager
+        .generate_addresses(SHIMMER_COIN_TYPE, 0, 0..2, false, None)
+        .await?;
+    let bech32_address_0 = &bech32_addresses[0].to_bech32(SHIMMER_TESTNET_BECH32_HRP);
+    let bech32_address_1 = &bech32_addresses[1].to_bech32(SHIMMER_TESTNET_BECH32_HRP);
+
+    let protocol_parameters = protocol_parameters();
+
+    let inputs = build_inputs(
+        [(bech32_address_0, 0), (bech32_address_1, 1), (bech32_address_0, 0)]
+            .into_iter()
+            .map(|(bech32_address, address_index)| {
+                Basic(
+                    1_000_000,
+                    bech32_address,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    Some(Chain::from_u32_hardened(vec![
+                        HD_WALLET_TYPE,
+                        SHIMMER_COIN_TYPE,
+                        0,
+                        0,
+                        address_index,
+                    ])),
+                )
+            })
+            .collect(),
+    );
+
+    let outputs = build_outputs(vec![Basic(
+        3_000_000,
+        bech32_address_0,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    )]);
+
+    let essence = TransactionEssence::Regular(
+        RegularTransactionEssence::builder(
+            protocol_parameters.network_id(),
+            InputsCommitment::new(inputs.iter().map(|i| &i.output)),
+        )
+        .with_inputs(
+            inputs
+                .iter()
+                .map(|i| Input::Utxo(UtxoInput::from(*i.output_metadata.output_id())))
+                .collect(),
+        )
+        .with_outputs(outputs)
+        .finish(&protocol_parameters)?,
+    );
+
+    let prepared_transaction_data = PreparedTransactionData {
+        essence,
+        inputs_data: inputs,
+        remainder: None,
+    };
+
+    let unlocks = secret_manager
+        .sign_transaction_essence(&prepared_transaction_data, Some(0))
+        .await?;
+
+    assert!(matches!((*unlocks).get(2).unwrap(), Unlock::Reference(r) if r.index() == 0));
+
+    // The third input belongs to the first address, so referencing the signature of the second address is invalid.
+    let mut unlocks = unlocks.to_vec();
+    unlocks[2] = Unlock::Reference(ReferenceUnlock::new(1)?);
+    let tx_payload = TransactionPayload::new(prepared_transaction_data.essence.clone(), Unlocks::new(unlocks)?)?;
+
+    let conflict = verify_semantic(&prepared_transaction_data.inputs_data, &tx_payload, 100)?;
+
+    assert_eq!(conflict, ConflictReason::InvalidUnlock);
+    assert_eq!(
+        verify_semantic_with_diagnostic(&prepared_transaction_data.inputs_data, &tx_payload, 100)?,
+        Some(ConflictDiagnostic::InvalidUnlock {
+            input_index: 2,
+            reason: ConflictReason::InvalidUnlock
+        })
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn transaction_validator() -> Result<()> {
+    let secret_manager = SecretManager::try_from_mnemonic(&Client::generate_mnemonic(MnemonicLength::Words24)?)?;
+
+    let bech32_address_0 = &secret_manager
+        .generate_addresses(SHIMMER_COIN_TYPE, 0, 0..1, false, None)
+        .await?[0]
+        .to_bech32(SHIMMER_TESTNET_BECH32_HRP);
+
+    let protocol_parameters = protocol_parameters();
+
+    let inputs = build_inputs(vec![Basic(
+        1_000_000,
+        bech32_address_0,
+        None,
+        None,
+        None,
+        None,
+        None,
+        Some(Chain::from_u32_hardened(vec![
+            HD_WALLET_TYPE,
+            SHIMMER_COIN_TYPE,
+            0,
+            0,
+            0,
+        ])),
+    )]);
+
+    let outputs = build_outputs(vec![Basic(
+        1_000_000,
+        bech32_address_0,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    )]);
+
+    let essence = TransactionEssence::Regular(
+        RegularTransactionEssence::builder(
+            protocol_parameters.network_id(),
+            InputsCommitment::new(inputs.iter().map(|i| &i.output)),
+        )
+        .with_inputs(
+            inputs
+                .iter()
+                .map(|i| Input::Utxo(UtxoInput::from(*i.output_metadata.output_id())))
+                .collect(),
+        )
+        .with_outputs(outputs)
+        .finish(&protocol_parameters)?,
+    );
+
+    let prepared_transaction_data = PreparedTransactionData {
+        essence,
+        inputs_data: inputs,
+        remainder: None,
+    };
+
+    let unlocks = secret_manager
+        .sign_transaction_essence(&prepared_transaction_data, Some(0))
+        .await?;
+    let tx_payload = TransactionPayload::new(prepared_transaction_data.essence.clone(), unlocks)?;
+
+    let output_id = *prepared_transaction_data.inputs_data[0].output_id();
+    let output = prepared_transaction_data.inputs_data[0].output.clone();
+
+    let validator = TransactionValidator::new(InMemoryLedger::new(100));
+    assert_eq!(
+        validator.validate(&tx_payload).await?,
+        ConflictReason::InputUtxoNotFound
+    );
+    assert_eq!(
+        validator.validate_with_diagnostic(&tx_payload).await?,
+        Some(ConflictDiagnostic::InputUtxoNotFound { input_index: 0 })
+    );
+
+    let mut ledger = InMemoryLedger::new(100).with_output(output_id, output.clone());
+    assert_eq!(
+        TransactionValidator::new(&ledger).validate(&tx_payload).await?,
+        ConflictReason::None
+    );
+
+    ledger.insert_output(output_id, output, true);
+    assert_eq!(
+        TransactionValidator::new(&ledger).validate(&tx_payload).await?,
+        ConflictReason::InputUtxoAlreadySpent
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn amount_mismatch_diagnostic() -> Result<()> {
+    let secret_manager = SecretManager::try_from_mnemonic(&Client::generate_mnemonic(MnemonicLength::Words24)?)?;
+
+    let bech32_address_0 = &secret_manager
+        .generate_addresses(SHIMMER_COIN_TYPE, 0, 0..1, false, None)
+        .await?[0]
+        .to_bech32(SHIMMER_TESTNET_BECH32_HRP);
+
+    let protocol_parameters = protocol_parameters();
+
+    let inputs = build_inputs(vec![Basic(
+        1_000_000,
+        bech32_address_0,
+        None,
+        None,
+        None,
+        None,
+        None,
+        Some(Chain::from_u32_hardened(vec![
+            HD_WALLET_TYPE,
+            SHIMMER_COIN_TYPE,
+            0,
+            0,
+            0,
+        ])),
+    )]);
+
+    let outputs = build_outputs(vec![Basic(
+        2_000_000,
+        bech32_address_0,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    )]);
+
+    let essence = TransactionEssence::Regular(
+        RegularTransactionEssence::builder(
+            protocol_parameters.network_id(),
+            InputsCommitment::new(inputs.iter().map(|i| &i.output)),
+        )
+        .with_inputs(
+            inputs
+                .iter()
+                .map(|i| Input::Utxo(UtxoInput::from(*i.output_metadata.output_id())))
+                .collect(),
+        )
+        .with_outputs(outputs)
+        .finish(&protocol_parameters)?,
+    );
+
+    let prepared_transaction_data = PreparedTransactionData {
+        essence,
+        inputs_data: inputs,
+        remainder: None,
+    };
+
+    let unlocks = secret_manager
+        .sign_transaction_essence(&prepared_transaction_data, Some(0))
+        .await?;
+    let tx_payload = TransactionPayload::new(prepared_transaction_data.essence.clone(), unlocks)?;
+
+    let diagnostic = verify_semantic_with_diagnostic(&prepared_transaction_data.inputs_data, &tx_payload, 100)?;
+
+    assert_eq!(
+        diagnostic,
+        Some(ConflictDiagnostic::CreatedConsumedAmountMismatch {
+            consumed: 1_000_000,
+            created: 2_000_000
+        })
+    );
+    assert_eq!(
+        diagnostic.unwrap().conflict_reason(),
+        verify_semantic(&prepared_transaction_data.inputs_data, &tx_payload, 100)?
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn native_tokens_amount_mismatch_diagnostic() -> Result<()> {
+    let secret_manager = SecretManager::try_from_mnemonic(&Client::generate_mnemonic(MnemonicLength::Words24)?)?;
+
+    let bech32_address_0 = &secret_manager
+        .generate_addresses(SHIMMER_COIN_TYPE, 0, 0..1, false, None)
+        .await?[0]
+        .to_bech32(SHIMMER_TESTNET_BECH32_HRP);
+
+    let protocol_parameters = protocol_parameters();
+
+    let inputs = build_inputs(vec![Basic(
+        1_000_000,
+        bech32_address_0,
+        Some(vec![(TOKEN_ID_1, 50)]),
+        None,
+        None,
+        None,
+        None,
+        Some(Chain::from_u32_hardened(vec![
+            HD_WALLET_TYPE,
+            SHIMMER_COIN_TYPE,
+            0,
+            0,
+            0,
+        ])),
+    )]);
+
+    let outputs = build_outputs(vec![Basic(
+        1_000_000,
+        bech32_address_0,
+        Some(vec![(TOKEN_ID_2, 200), (TOKEN_ID_1, 100)]),
+        None,
+        None,
+        None,
+        None,
+        None,
+    )]);
+
+    let essence = TransactionEssence::Regular(
+        RegularTransactionEssence::builder(
+            protocol_parameters.network_id(),
+            InputsCommitment::new(inputs.iter().map(|i| &i.output)),
+        )
+        .with_inputs(
+            inputs
+                .iter()
+                .map(|i| Input::Utxo(UtxoInput::from(*i.output_metadata.output_id())))
+                .collect(),
+        )
+        .with_outputs(outputs)
+        .finish(&protocol_parameters)?,
+    );
+
+    let prepared_transaction_data = PreparedTransactionData {
+        essence,
+        inputs_data: inputs,
+        remainder: None,
+    };
+
+    let unlocks = secret_manager
+        .sign_transaction_essence(&prepared_transaction_data, Some(0))
+        .await?;
+    let tx_payload = TransactionPayload::new(prepared_transaction_data.essence.clone(), unlocks)?;
+
+    let diagnostic = verify_semantic_with_diagnostic(&prepared_transaction_data.inputs_data, &tx_payload, 100)?;
+
+    // All mismatching native tokens are reported, ordered by their ID.
+    assert_eq!(
+        diagnostic,
+        Some(ConflictDiagnostic::NativeTokensAmountMismatch {
+            mismatches: vec![
+                NativeTokenAmountMismatch {
+                    token_id: TokenId::from_str(TOKEN_ID_1).unwrap(),
+                    consumed: U256::from(50),
+                    created: U256::from(100),
+                },
+                NativeTokenAmountMismatch {
+                    token_id: TokenId::from_str(TOKEN_ID_2).unwrap(),
+                    consumed: U256::zero(),
+                    created: U256::from(200),
+                },
+            ]
+        })
+    );
+    assert_eq!(
+        diagnostic.unwrap().conflict_reason(),
+        verify_semantic(&prepared_transaction_data.inputs_data, &tx_payload, 100)?
+    );
+
+    Ok(())
+}
@@ -20,7 +20,7 @@ use iota_client::{
     },
     constants::{HD_WALLET_TYPE, SHIMMER_COIN_TYPE, SHIMMER_TESTNET_BECH32_HRP},
     secret::{SecretManage, SecretManageExt, SecretManager},
-    Client, Result,
+    Client, MnemonicLength, Result,
 };
 
 use crate::{
@@ -31,7 +31,7 @@ use crate::{
 
 #[tokio::test]
 async fn nft_reference_unlocks() -> Result<()> {
-    let secret_manager = SecretManager::try_from_mnemonic(&Client::generate_mnemonic()?)?;
+    let secret_manager = SecretManager::try_from_mnemonic(&Client::generate_mnemonic(MnemonicLength::Words24)?)?;
 
     let bech32_address_0 = &secret_manager
         .generate_addresses(SHIMMER_COIN_TYPE, 0, 0..1, false, None)
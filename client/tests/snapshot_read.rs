@@ -0,0 +1,125 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+mod common;
+
+use std::{
+    str::FromStr,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use iota_client::{node_api::indexer::SnapshotReadMode, Client, Error};
+use iota_types::block::output::OutputId;
+
+use self::common::{json, mock_client_builder, not_found, ok, output_with_metadata, MockTransport, NODE_LOCAL};
+
+const OUTPUT_ID_1: &str = "0x1e857d380f813d8035e487b6dfd2ff4740b6775273ba1b576f01381ba2a1a44c0000";
+const OUTPUT_ID_2: &str = "0x1e857d380f813d8035e487b6dfd2ff4740b6775273ba1b576f01381ba2a1a44c0100";
+
+// Returns two pages of output IDs, the ledger index moves from 10 to 11 between the pages on the first pagination.
+// Outputs are returned at ledger index 9.
+fn client(snapshot_read: SnapshotReadMode) -> Client {
+    let first_pagination = AtomicBool::new(true);
+    let transport = MockTransport::new(move |request| {
+        let path = request.url.path();
+
+        if path == "/api/indexer/v1/outputs/basic" {
+            if request.url.query().unwrap_or_default().contains("cursor=") {
+                let ledger_index = if first_pagination.swap(false, Ordering::SeqCst) {
+                    11
+                } else {
+                    10
+                };
+                ok(format!(
+                    "{{\"ledgerIndex\":{ledger_index},\"cursor\":null,\"items\":[\"{OUTPUT_ID_2}\"]}}"
+                ))
+            } else {
+                ok(format!(
+                    "{{\"ledgerIndex\":10,\"cursor\":\"1.{OUTPUT_ID_2}.1\",\"items\":[\"{OUTPUT_ID_1}\"]}}"
+                ))
+            }
+        } else if let Some(output_id) = path.strip_prefix("/api/core/v2/outputs/") {
+            json(&output_with_metadata(&OutputId::from_str(output_id).unwrap(), false, 9))
+        } else {
+            not_found()
+        }
+    });
+
+    mock_client_builder(&[NODE_LOCAL], &transport)
+        .with_snapshot_read(snapshot_read)
+        .finish()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn snapshot_read_disabled() {
+    let client = client(SnapshotReadMode::Disabled);
+
+    // Pages of different ledger states are merged.
+    let output_ids_response = client.basic_output_ids(Vec::new()).await.unwrap();
+    assert_eq!(output_ids_response.ledger_index, 11);
+    assert_eq!(output_ids_response.items.len(), 2);
+
+    let outputs = client.get_outputs_for_listing(output_ids_response).await.unwrap();
+    assert_eq!(outputs.len(), 2);
+}
+
+#[tokio::test]
+async fn output_at_least_at_ledger_index() {
+    let client = client(SnapshotReadMode::Disabled);
+    let output_id = OutputId::from_str(OUTPUT_ID_1).unwrap();
+
+    let output = client.get_output_at_least_at_ledger_index(&output_id, 9).await.unwrap();
+    assert_eq!(output.metadata.ledger_index, 9);
+
+    // The ledger of the node is behind the required ledger index.
+    assert!(matches!(
+        client.get_output_at_least_at_ledger_index(&output_id, 10).await,
+        Err(Error::LedgerIndexBehind {
+            required: 10,
+            actual: 9
+        })
+    ));
+}
+
+#[tokio::test]
+async fn snapshot_read_fail() {
+    let client = client(SnapshotReadMode::Fail);
+
+    assert!(matches!(
+        client.basic_output_ids(Vec::new()).await,
+        Err(Error::LedgerIndexChanged {
+            expected: 10,
+            actual: 11
+        })
+    ));
+}
+
+#[tokio::test]
+async fn snapshot_read_restart() {
+    let client = client(SnapshotReadMode::Restart(1));
+
+    let output_ids_response = client.basic_output_ids(Vec::new()).await.unwrap();
+    assert_eq!(output_ids_response.ledger_index, 10);
+    assert_eq!(
+        output_ids_response
+            .items
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>(),
+        vec![OUTPUT_ID_1, OUTPUT_ID_2]
+    );
+
+    // The outputs were read at an older ledger index than the listing.
+    match client.get_outputs_for_listing(output_ids_response).await {
+        Err(Error::StaleOutput {
+            ledger_index,
+            listing_ledger_index,
+            ..
+        }) => {
+            assert_eq!(ledger_index, 9);
+            assert_eq!(listing_ledger_index, 10);
+        }
+        res => panic!("expected stale output, got {res:?}"),
+    }
+}
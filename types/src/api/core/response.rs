@@ -303,8 +303,8 @@ pub struct AddPeerResponse(pub PeerDto);
 )]
 pub struct PeerResponse(pub PeerDto);
 
-/// Response of GET /api/plugins/debug/whiteflag.
-/// Returns the computed merkle tree hash for the given white flag traversal.
+/// Response of POST /api/core/v2/whiteflag.
+/// Returns the computed merkle roots for the given white flag traversal.
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[cfg_attr(
     feature = "serde",
@@ -312,7 +312,33 @@ pub struct PeerResponse(pub PeerDto);
     serde(rename_all = "camelCase")
 )]
 pub struct WhiteFlagResponse {
-    pub merkle_tree_hash: String,
+    pub inclusion_merkle_root: String,
+    pub applied_merkle_root: String,
+}
+
+/// Response of POST /api/core/v2/control/database/prune.
+/// Returns the index of the current pruning target.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct PruneDatabaseResponse {
+    pub index: u32,
+}
+
+/// Response of POST /api/core/v2/control/snapshots/create.
+/// Returns the index and the file path of the created snapshot.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct CreateSnapshotsResponse {
+    pub index: u32,
+    pub file_path: String,
 }
 
 /// Response of GET /api/routes.
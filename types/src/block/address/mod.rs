@@ -135,10 +135,11 @@ impl Address {
                 }
 
                 context.unlocked_addresses.insert(*self);
+                context.signature_unlocks.insert(*self, context.unlock_index);
             }
-            (Self::Ed25519(_ed25519_address), Unlock::Reference(_unlock)) => {
-                // TODO actually check that it was unlocked by the same signature.
-                if !context.unlocked_addresses.contains(self) {
+            (Self::Ed25519(_ed25519_address), Unlock::Reference(unlock)) => {
+                // The reference has to point to the exact signature unlock that unlocked the address.
+                if context.signature_unlocks.get(self) != Some(&unlock.index()) {
                     return Err(ConflictReason::InvalidUnlock);
                 }
             }
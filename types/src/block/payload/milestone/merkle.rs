@@ -1,7 +1,14 @@
 // Copyright 2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::block::Error;
+use alloc::vec::Vec;
+
+use crypto::hashes::{blake2b::Blake2b256, Digest};
+
+use crate::block::{payload::milestone::MilestoneEssence, BlockId, Error};
+
+const LEAF_HASH_PREFIX: u8 = 0;
+const NODE_HASH_PREFIX: u8 = 1;
 
 /// A Merkle root of a list of hashes.
 #[derive(Clone, Copy, Eq, PartialEq, packable::Packable, derive_more::From, derive_more::AsRef)]
@@ -50,3 +57,157 @@ impl core::str::FromStr for MerkleRoot {
         Ok(Self::new(prefix_hex::decode(s).map_err(Error::Hex)?))
     }
 }
+
+/// Computes the Merkle roots of the block IDs referenced by milestones.
+///
+/// As defined by TIP-4: BLAKE2b-256 hashes with different prefixes for leaves and nodes, where each node splits its
+/// leaves at the largest power of two.
+pub struct MerkleHasher;
+
+impl MerkleHasher {
+    /// Computes the Merkle root of the block IDs, which have to be in the order they were applied by the milestone.
+    pub fn digest(block_ids: &[BlockId]) -> MerkleRoot {
+        match block_ids {
+            [] => MerkleRoot::new(Blake2b256::new().finalize().into()),
+            [block_id] => leaf_hash(block_id),
+            _ => {
+                let split = split_index(block_ids.len());
+                node_hash(&Self::digest(&block_ids[..split]), &Self::digest(&block_ids[split..]))
+            }
+        }
+    }
+
+    /// Creates a proof that the block ID at `index` is part of the Merkle tree of the block IDs, `None` if the index
+    /// is out of bounds.
+    pub fn proof(block_ids: &[BlockId], index: usize) -> Option<MerkleProof> {
+        let block_id = *block_ids.get(index)?;
+        let mut path = Vec::new();
+        audit_path(block_ids, index, &mut path);
+
+        Some(MerkleProof::new(block_id, index, block_ids.len(), path))
+    }
+}
+
+/// A proof that a block ID is part of a Merkle tree, e.g. the one of the inclusion Merkle root of a milestone.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct MerkleProof {
+    block_id: BlockId,
+    index: usize,
+    leaf_count: usize,
+    path: Vec<MerkleRoot>,
+}
+
+impl MerkleProof {
+    /// Creates a new [`MerkleProof`] from the hashes of the sibling subtrees, starting at the leaf.
+    pub fn new(block_id: BlockId, index: usize, leaf_count: usize, path: Vec<MerkleRoot>) -> Self {
+        Self {
+            block_id,
+            index,
+            leaf_count,
+            path,
+        }
+    }
+
+    /// Returns the proven block ID.
+    pub fn block_id(&self) -> &BlockId {
+        &self.block_id
+    }
+
+    /// Returns the position of the block ID in the Merkle tree.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the amount of block IDs in the Merkle tree.
+    pub fn leaf_count(&self) -> usize {
+        self.leaf_count
+    }
+
+    /// Returns the hashes of the sibling subtrees, starting at the leaf.
+    pub fn path(&self) -> &[MerkleRoot] {
+        &self.path
+    }
+
+    /// Computes the Merkle root of the proof, `None` if the path doesn't fit the index and leaf count.
+    pub fn root(&self) -> Option<MerkleRoot> {
+        if self.index >= self.leaf_count {
+            return None;
+        }
+
+        let (mut index, mut last_index) = (self.index, self.leaf_count - 1);
+        let mut root = leaf_hash(&self.block_id);
+
+        for sibling in &self.path {
+            if last_index == 0 {
+                return None;
+            }
+            if index & 1 == 1 || index == last_index {
+                root = node_hash(sibling, &root);
+                // Skip the levels where the node is the only, left aligned child.
+                while index & 1 == 0 && index != 0 {
+                    index >>= 1;
+                    last_index >>= 1;
+                }
+            } else {
+                root = node_hash(&root, sibling);
+            }
+            index >>= 1;
+            last_index >>= 1;
+        }
+
+        (last_index == 0).then_some(root)
+    }
+
+    /// Verifies that the block ID is part of the Merkle tree with the given root.
+    pub fn verify(&self, merkle_root: &MerkleRoot) -> bool {
+        self.root().as_ref() == Some(merkle_root)
+    }
+
+    /// Verifies that the block ID was referenced by the milestone, using its inclusion Merkle root.
+    pub fn verify_inclusion(&self, milestone_essence: &MilestoneEssence) -> bool {
+        self.verify(milestone_essence.inclusion_merkle_root())
+    }
+}
+
+fn leaf_hash(block_id: &BlockId) -> MerkleRoot {
+    let mut hasher = Blake2b256::new();
+    hasher.update([LEAF_HASH_PREFIX]);
+    hasher.update(block_id);
+
+    MerkleRoot::new(hasher.finalize().into())
+}
+
+fn node_hash(left: &MerkleRoot, right: &MerkleRoot) -> MerkleRoot {
+    let mut hasher = Blake2b256::new();
+    hasher.update([NODE_HASH_PREFIX]);
+    hasher.update(**left);
+    hasher.update(**right);
+
+    MerkleRoot::new(hasher.finalize().into())
+}
+
+// Returns the largest power of two smaller than `len`, which has to be greater than 1.
+fn split_index(len: usize) -> usize {
+    1 << (usize::BITS - 1 - (len - 1).leading_zeros())
+}
+
+// Adds the hashes of the sibling subtrees of the leaf at `index` to the path, starting at the leaf.
+fn audit_path(block_ids: &[BlockId], index: usize, path: &mut Vec<MerkleRoot>) {
+    if block_ids.len() <= 1 {
+        return;
+    }
+
+    let split = split_index(block_ids.len());
+    if index < split {
+        audit_path(&block_ids[..split], index, path);
+        path.push(MerkleHasher::digest(&block_ids[split..]));
+    } else {
+        audit_path(&block_ids[split..], index - split, path);
+        path.push(MerkleHasher::digest(&block_ids[..split]));
+    }
+}
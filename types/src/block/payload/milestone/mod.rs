@@ -12,7 +12,10 @@ mod milestone_id;
 pub mod option;
 
 use alloc::{string::String, vec::Vec};
-use core::{fmt::Debug, ops::RangeInclusive};
+use core::{
+    fmt::{self, Debug},
+    ops::RangeInclusive,
+};
 
 use crypto::{signatures::ed25519, Error as CryptoError};
 use iterator_sorted::is_unique_sorted;
@@ -22,7 +25,7 @@ use packable::{bounded::BoundedU8, prefix::VecPrefix, Packable};
 pub use self::{
     essence::MilestoneEssence,
     index::MilestoneIndex,
-    merkle::MerkleRoot,
+    merkle::{MerkleHasher, MerkleProof, MerkleRoot},
     milestone_id::MilestoneId,
     option::{MilestoneOption, MilestoneOptions, ParametersMilestoneOption, ReceiptMilestoneOption},
 };
@@ -40,6 +43,29 @@ pub enum MilestoneValidationError {
     Crypto(CryptoError),
 }
 
+impl fmt::Display for MilestoneValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidMinThreshold => write!(f, "invalid min threshold: must be greater than 0"),
+            Self::TooFewSignatures(min_threshold, count) => {
+                write!(f, "too few signatures: {count}, expected at least {min_threshold}")
+            }
+            Self::InsufficientApplicablePublicKeys(count, min_threshold) => write!(
+                f,
+                "insufficient applicable public keys: {count}, expected at least {min_threshold}"
+            ),
+            Self::UnapplicablePublicKey(public_key) => write!(f, "unapplicable public key: {public_key}"),
+            Self::InvalidSignature(index, public_key) => {
+                write!(f, "invalid signature {index} for public key {public_key}")
+            }
+            Self::Crypto(e) => write!(f, "cryptographic error: {e}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MilestoneValidationError {}
+
 impl From<CryptoError> for MilestoneValidationError {
     fn from(error: CryptoError) -> Self {
         Self::Crypto(error)
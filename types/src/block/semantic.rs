@@ -1,6 +1,7 @@
 // Copyright 2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+use alloc::vec::Vec;
 use core::{convert::Infallible, fmt};
 
 use hashbrown::{HashMap, HashSet};
@@ -9,7 +10,7 @@ use primitive_types::U256;
 use crate::block::{
     address::Address,
     error::Error,
-    output::{ChainId, FoundryId, InputsCommitment, NativeTokens, Output, OutputId, TokenId},
+    output::{ChainId, FoundryId, InputsCommitment, NativeTokens, Output, OutputId, StateTransitionError, TokenId},
     payload::transaction::{RegularTransactionEssence, TransactionEssence, TransactionId},
     unlock::Unlocks,
 };
@@ -105,6 +106,183 @@ impl TryFrom<u8> for ConflictReason {
     }
 }
 
+/// A structured description of why a transaction conflicts with the ledger state, returned by
+/// [`semantic_validation_with_diagnostic()`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ConflictDiagnostic {
+    /// An input references an output that cannot be found.
+    InputUtxoNotFound {
+        /// The index of the input.
+        input_index: u16,
+    },
+    /// An input references an output that was already spent.
+    InputUtxoAlreadySpent {
+        /// The index of the input.
+        input_index: u16,
+    },
+    /// The inputs commitment of the essence doesn't match the consumed outputs.
+    InputsCommitmentsMismatch {
+        /// The inputs commitment of the consumed outputs.
+        expected: InputsCommitment,
+        /// The inputs commitment of the essence.
+        actual: InputsCommitment,
+    },
+    /// An input couldn't be unlocked by its unlock.
+    InvalidUnlock {
+        /// The index of the input and its unlock.
+        input_index: u16,
+        /// Why the unlock failed, [`ConflictReason::InvalidSignature`] or [`ConflictReason::InvalidUnlock`].
+        reason: ConflictReason,
+    },
+    /// An input is still time locked.
+    TimelockNotExpired {
+        /// The index of the input.
+        input_index: u16,
+        /// The milestone timestamp the transaction was validated against.
+        milestone_timestamp: u32,
+    },
+    /// The sender of an output wasn't unlocked by the inputs.
+    UnverifiedSender {
+        /// The index of the output.
+        output_index: u16,
+        /// The address of the sender feature.
+        sender: Address,
+    },
+    /// The storage deposit required by storage deposit return unlock conditions isn't returned.
+    StorageDepositReturnUnfulfilled {
+        /// The address the storage deposit has to be returned to.
+        return_address: Address,
+        /// The amount that has to be returned.
+        required: u64,
+        /// The amount returned by simple deposits.
+        returned: u64,
+    },
+    /// The created amount does not match the consumed amount.
+    CreatedConsumedAmountMismatch {
+        /// The amount of the inputs.
+        consumed: u64,
+        /// The amount of the outputs.
+        created: u64,
+    },
+    /// More native tokens are created than consumed without a transition of their foundry.
+    NativeTokensAmountMismatch {
+        /// All native tokens with a mismatching amount, ordered by their ID.
+        mismatches: Vec<NativeTokenAmountMismatch>,
+    },
+    /// The inputs and outputs contain too many distinct native tokens.
+    NativeTokensCountExceeded {
+        /// The number of distinct native tokens.
+        count: usize,
+    },
+    /// The state transition of a chain is invalid.
+    InvalidChainStateTransition {
+        /// The ID of the chain.
+        chain_id: ChainId,
+        /// Why the state transition is invalid.
+        error: StateTransitionError,
+    },
+}
+
+/// A native token of which more is created than consumed, see [`ConflictDiagnostic::NativeTokensAmountMismatch`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NativeTokenAmountMismatch {
+    /// The ID of the native token.
+    pub token_id: TokenId,
+    /// The amount of the native token in the inputs.
+    pub consumed: U256,
+    /// The amount of the native token in the outputs.
+    pub created: U256,
+}
+
+impl fmt::Display for NativeTokenAmountMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "created amount {} of native token {} exceeds consumed amount {}",
+            self.created, self.token_id, self.consumed
+        )
+    }
+}
+
+impl ConflictDiagnostic {
+    /// Returns the [`ConflictReason`] the diagnostic maps to.
+    pub fn conflict_reason(&self) -> ConflictReason {
+        match self {
+            Self::InputUtxoNotFound { .. } => ConflictReason::InputUtxoNotFound,
+            Self::InputUtxoAlreadySpent { .. } => ConflictReason::InputUtxoAlreadySpent,
+            Self::InputsCommitmentsMismatch { .. } => ConflictReason::InputsCommitmentsMismatch,
+            Self::InvalidUnlock { reason, .. } => *reason,
+            Self::TimelockNotExpired { .. } => ConflictReason::TimelockNotExpired,
+            Self::UnverifiedSender { .. } => ConflictReason::UnverifiedSender,
+            Self::StorageDepositReturnUnfulfilled { .. } => ConflictReason::StorageDepositReturnUnfulfilled,
+            Self::CreatedConsumedAmountMismatch { .. } => ConflictReason::CreatedConsumedAmountMismatch,
+            Self::NativeTokensAmountMismatch { .. } | Self::NativeTokensCountExceeded { .. } => {
+                ConflictReason::InvalidNativeTokens
+            }
+            Self::InvalidChainStateTransition { .. } => ConflictReason::InvalidChainStateTransition,
+        }
+    }
+}
+
+impl From<&ConflictDiagnostic> for ConflictReason {
+    fn from(diagnostic: &ConflictDiagnostic) -> Self {
+        diagnostic.conflict_reason()
+    }
+}
+
+impl fmt::Display for ConflictDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InputUtxoNotFound { input_index } => write!(f, "output of input {input_index} not found"),
+            Self::InputUtxoAlreadySpent { input_index } => write!(f, "output of input {input_index} already spent"),
+            Self::InputsCommitmentsMismatch { expected, actual } => {
+                write!(f, "inputs commitment mismatch: expected {expected}, got {actual}")
+            }
+            Self::InvalidUnlock { input_index, reason } => {
+                write!(f, "invalid unlock of input {input_index}: {reason:?}")
+            }
+            Self::TimelockNotExpired {
+                input_index,
+                milestone_timestamp,
+            } => write!(
+                f,
+                "input {input_index} is time locked at milestone timestamp {milestone_timestamp}"
+            ),
+            Self::UnverifiedSender { output_index, sender } => {
+                write!(f, "sender {sender:?} of output {output_index} is not unlocked")
+            }
+            Self::StorageDepositReturnUnfulfilled {
+                return_address,
+                required,
+                returned,
+            } => write!(
+                f,
+                "storage deposit return to {return_address:?} unfulfilled: required {required}, returned {returned}"
+            ),
+            Self::CreatedConsumedAmountMismatch { consumed, created } => {
+                write!(f, "created amount {created} doesn't match consumed amount {consumed}")
+            }
+            Self::NativeTokensAmountMismatch { mismatches } => {
+                for (index, mismatch) in mismatches.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{mismatch}")?;
+                }
+                Ok(())
+            }
+            Self::NativeTokensCountExceeded { count } => write!(
+                f,
+                "{count} distinct native tokens exceed the maximum of {}",
+                NativeTokens::COUNT_MAX
+            ),
+            Self::InvalidChainStateTransition { chain_id, error } => {
+                write!(f, "invalid state transition of chain {chain_id}: {error:?}")
+            }
+        }
+    }
+}
+
 ///
 pub struct ValidationContext<'a> {
     ///
@@ -131,6 +309,10 @@ pub struct ValidationContext<'a> {
     pub output_chains: HashMap<ChainId, &'a Output>,
     ///
     pub unlocked_addresses: HashSet<Address>,
+    /// The index of the unlock that is currently being verified.
+    pub unlock_index: u16,
+    /// The index of the signature unlock that unlocked each Ed25519 address.
+    pub signature_unlocks: HashMap<Address, u16>,
     ///
     pub storage_deposit_returns: HashMap<Address, u64>,
     ///
@@ -177,6 +359,8 @@ impl<'a> ValidationContext<'a> {
                 })
                 .collect(),
             unlocked_addresses: HashSet::new(),
+            unlock_index: 0,
+            signature_unlocks: HashMap::new(),
             storage_deposit_returns: HashMap::new(),
             simple_deposits: HashMap::new(),
         }
@@ -185,17 +369,33 @@ impl<'a> ValidationContext<'a> {
 
 ///
 pub fn semantic_validation(
-    mut context: ValidationContext<'_>,
+    context: ValidationContext<'_>,
     inputs: &[(OutputId, &Output)],
     unlocks: &Unlocks,
 ) -> Result<ConflictReason, Error> {
+    Ok(semantic_validation_with_diagnostic(context, inputs, unlocks)?
+        .map_or(ConflictReason::None, |diagnostic| diagnostic.conflict_reason()))
+}
+
+/// Validates the semantic of a transaction like [`semantic_validation()`], but describes the conflict, if there is one,
+/// with a [`ConflictDiagnostic`].
+pub fn semantic_validation_with_diagnostic(
+    mut context: ValidationContext<'_>,
+    inputs: &[(OutputId, &Output)],
+    unlocks: &Unlocks,
+) -> Result<Option<ConflictDiagnostic>, Error> {
     // Validation of the inputs commitment.
     if context.essence.inputs_commitment() != &context.inputs_commitment {
-        return Ok(ConflictReason::InputsCommitmentsMismatch);
+        return Ok(Some(ConflictDiagnostic::InputsCommitmentsMismatch {
+            expected: context.inputs_commitment,
+            actual: *context.essence.inputs_commitment(),
+        }));
     }
 
     // Validation of inputs.
-    for ((output_id, consumed_output), unlock) in inputs.iter().zip(unlocks.iter()) {
+    for (unlock_index, ((output_id, consumed_output), unlock)) in (0u16..).zip(inputs.iter().zip(unlocks.iter())) {
+        context.unlock_index = unlock_index;
+
         let (conflict, amount, consumed_native_tokens, unlock_conditions) = match consumed_output {
             Output::Basic(output) => (
                 output.unlock(output_id, unlock, inputs, &mut context),
@@ -224,12 +424,18 @@ pub fn semantic_validation(
             _ => return Err(Error::UnsupportedOutputKind(consumed_output.kind())),
         };
 
-        if let Err(conflict) = conflict {
-            return Ok(conflict);
+        if let Err(reason) = conflict {
+            return Ok(Some(ConflictDiagnostic::InvalidUnlock {
+                input_index: unlock_index,
+                reason,
+            }));
         }
 
         if unlock_conditions.is_time_locked(context.milestone_timestamp) {
-            return Ok(ConflictReason::TimelockNotExpired);
+            return Ok(Some(ConflictDiagnostic::TimelockNotExpired {
+                input_index: unlock_index,
+                milestone_timestamp: context.milestone_timestamp,
+            }));
         }
 
         if !unlock_conditions.is_expired(context.milestone_timestamp) {
@@ -260,7 +466,7 @@ pub fn semantic_validation(
     }
 
     // Validation of outputs.
-    for created_output in context.essence.outputs() {
+    for (output_index, created_output) in (0u16..).zip(context.essence.outputs()) {
         let (amount, created_native_tokens, features) = match created_output {
             Output::Basic(output) => {
                 if let Some(address) = output.simple_deposit_address() {
@@ -281,7 +487,10 @@ pub fn semantic_validation(
 
         if let Some(sender) = features.sender() {
             if !context.unlocked_addresses.contains(sender.address()) {
-                return Ok(ConflictReason::UnverifiedSender);
+                return Ok(Some(ConflictDiagnostic::UnverifiedSender {
+                    output_index,
+                    sender: *sender.address(),
+                }));
             }
         }
 
@@ -304,21 +513,27 @@ pub fn semantic_validation(
 
     // Validation of storage deposit returns.
     for (return_address, return_amount) in context.storage_deposit_returns.iter() {
-        if let Some(deposit_amount) = context.simple_deposits.get(return_address) {
-            if deposit_amount < return_amount {
-                return Ok(ConflictReason::StorageDepositReturnUnfulfilled);
-            }
-        } else {
-            return Ok(ConflictReason::StorageDepositReturnUnfulfilled);
+        let deposit_amount = context.simple_deposits.get(return_address).copied().unwrap_or_default();
+
+        if deposit_amount < *return_amount {
+            return Ok(Some(ConflictDiagnostic::StorageDepositReturnUnfulfilled {
+                return_address: *return_address,
+                required: *return_amount,
+                returned: deposit_amount,
+            }));
         }
     }
 
     // Validation of amounts.
     if context.input_amount != context.output_amount {
-        return Ok(ConflictReason::CreatedConsumedAmountMismatch);
+        return Ok(Some(ConflictDiagnostic::CreatedConsumedAmountMismatch {
+            consumed: context.input_amount,
+            created: context.output_amount,
+        }));
     }
 
     let mut native_token_ids = HashSet::new();
+    let mut native_token_mismatches = Vec::new();
 
     // Validation of input native tokens.
     for (token_id, _input_amount) in context.input_native_tokens.iter() {
@@ -334,37 +549,54 @@ pub fn semantic_validation(
                 .output_chains
                 .contains_key(&ChainId::from(FoundryId::from(*token_id)))
         {
-            return Ok(ConflictReason::InvalidNativeTokens);
+            native_token_mismatches.push(NativeTokenAmountMismatch {
+                token_id: *token_id,
+                consumed: input_amount,
+                created: *output_amount,
+            });
         }
 
         native_token_ids.insert(token_id);
     }
 
+    if !native_token_mismatches.is_empty() {
+        native_token_mismatches.sort_by_key(|mismatch| mismatch.token_id);
+        return Ok(Some(ConflictDiagnostic::NativeTokensAmountMismatch {
+            mismatches: native_token_mismatches,
+        }));
+    }
+
     if native_token_ids.len() > NativeTokens::COUNT_MAX as usize {
-        return Ok(ConflictReason::InvalidNativeTokens);
+        return Ok(Some(ConflictDiagnostic::NativeTokensCountExceeded {
+            count: native_token_ids.len(),
+        }));
     }
 
     // Validation of state transitions and destructions.
     for (chain_id, current_state) in context.input_chains.iter() {
-        if Output::verify_state_transition(
+        if let Err(error) = Output::verify_state_transition(
             Some(current_state),
             context.output_chains.get(chain_id).map(core::ops::Deref::deref),
             &context,
-        )
-        .is_err()
-        {
-            return Ok(ConflictReason::InvalidChainStateTransition);
+        ) {
+            return Ok(Some(ConflictDiagnostic::InvalidChainStateTransition {
+                chain_id: *chain_id,
+                error,
+            }));
         }
     }
 
     // Validation of state creations.
     for (chain_id, next_state) in context.output_chains.iter() {
-        if context.input_chains.get(chain_id).is_none()
-            && Output::verify_state_transition(None, Some(next_state), &context).is_err()
-        {
-            return Ok(ConflictReason::InvalidChainStateTransition);
+        if context.input_chains.get(chain_id).is_none() {
+            if let Err(error) = Output::verify_state_transition(None, Some(next_state), &context) {
+                return Ok(Some(ConflictDiagnostic::InvalidChainStateTransition {
+                    chain_id: *chain_id,
+                    error,
+                }));
+            }
         }
     }
 
-    Ok(ConflictReason::None)
+    Ok(None)
 }
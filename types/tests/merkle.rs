@@ -0,0 +1,138 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use core::str::FromStr;
+
+use iota_types::block::{
+    payload::milestone::{MerkleHasher, MerkleProof, MerkleRoot, MilestoneEssence, MilestoneIndex, MilestoneOptions},
+    protocol::protocol_parameters,
+    rand::{
+        block::{rand_block_id, rand_block_ids},
+        milestone::{rand_merkle_root, rand_milestone_id},
+        parents::rand_parents,
+    },
+    BlockId,
+};
+
+// Test vector of TIP-4.
+const BLOCK_IDS: [&str; 7] = [
+    "0x52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c649",
+    "0x81855ad8681d0d86d1e91e00167939cb6694d2c422acd208a0072939487f6999",
+    "0xeb9d18a44784045d87f3c67cf22746e995af5a25367951baa2ff6cd471c483f1",
+    "0x5fb90badb37c5821b6d95526a41a9504680b4e7c8b763a1b1d49d4955c848621",
+    "0x6325253fec738dd7a9e28bf921119c160f0702448615bbda08313f6a8eb668d2",
+    "0x0bf5059875921e668a5bdf2c7fc4844592d2572bcd0668d2d6c52f5054e2d083",
+    "0x6bf84c7174cb7476364cc3dbd968b0f7172ed85794bb358b0c3b525da1786f9f",
+];
+const ROOT: &str = "0xbf67ce7ba23e8c0951b5abaec4f5524360d2c26d971ff226d3359fa70cdb0beb";
+
+fn block_ids() -> Vec<BlockId> {
+    BLOCK_IDS
+        .iter()
+        .map(|block_id| BlockId::from_str(block_id).unwrap())
+        .collect()
+}
+
+fn merkle_root(hex: &str) -> MerkleRoot {
+    MerkleRoot::from_str(hex).unwrap()
+}
+
+#[test]
+fn digest_tip_4() {
+    assert_eq!(MerkleHasher::digest(&block_ids()), merkle_root(ROOT));
+}
+
+#[test]
+fn digest_empty() {
+    assert_eq!(
+        MerkleHasher::digest(&[]),
+        merkle_root("0x0e5751c026e543b2e8ab2eb06099daa1d1e5df47778f7787faab45cdf12fe3a8")
+    );
+}
+
+#[test]
+fn proof_tip_4() {
+    let block_ids = block_ids();
+    let proof = MerkleHasher::proof(&block_ids, 2).unwrap();
+
+    assert_eq!(*proof.block_id(), block_ids[2]);
+    assert_eq!(proof.index(), 2);
+    assert_eq!(proof.leaf_count(), 7);
+    assert_eq!(
+        proof.path(),
+        [
+            merkle_root("0xffb3a7c6bea8f9fdcfb26f4701ad6e912a6076e1a40663607dbe110ebfc9a571"),
+            merkle_root("0xae4505f4cfae93586e23958ca88d35d2f34d43def49786b6d0d4224b819f4cda"),
+            merkle_root("0xce22d5bc728023e7ab6a9eb8f58baf62b9565fc8baeef4b377daa6709dbe598c"),
+        ]
+    );
+    assert!(proof.verify(&merkle_root(ROOT)));
+}
+
+#[test]
+fn proof_out_of_bounds() {
+    assert!(MerkleHasher::proof(&block_ids(), 7).is_none());
+    assert!(MerkleHasher::proof(&[], 0).is_none());
+}
+
+#[test]
+fn proof_roundtrip() {
+    for len in 1..=33 {
+        let block_ids = rand_block_ids(len);
+        let root = MerkleHasher::digest(&block_ids);
+
+        for index in 0..len {
+            let proof = MerkleHasher::proof(&block_ids, index).unwrap();
+            assert_eq!(proof.root(), Some(root));
+            assert!(proof.verify(&root));
+        }
+    }
+}
+
+#[test]
+fn proof_tampered() {
+    let block_ids = block_ids();
+    let root = merkle_root(ROOT);
+    let proof = MerkleHasher::proof(&block_ids, 4).unwrap();
+
+    let other_block_id = MerkleProof::new(rand_block_id(), 4, 7, proof.path().to_vec());
+    assert!(!other_block_id.verify(&root));
+
+    let other_index = MerkleProof::new(*proof.block_id(), 3, 7, proof.path().to_vec());
+    assert!(!other_index.verify(&root));
+
+    let mut path = proof.path().to_vec();
+    path.pop();
+    let short_path = MerkleProof::new(*proof.block_id(), 4, 7, path);
+    assert_eq!(short_path.root(), None);
+
+    let mut path = proof.path().to_vec();
+    path.push(rand_merkle_root());
+    let long_path = MerkleProof::new(*proof.block_id(), 4, 7, path);
+    assert_eq!(long_path.root(), None);
+
+    let invalid_index = MerkleProof::new(*proof.block_id(), 7, 7, proof.path().to_vec());
+    assert_eq!(invalid_index.root(), None);
+}
+
+#[test]
+fn verify_inclusion() {
+    let block_ids = block_ids();
+    let essence = MilestoneEssence::new(
+        MilestoneIndex(0),
+        0,
+        protocol_parameters().protocol_version(),
+        rand_milestone_id(),
+        rand_parents(),
+        MerkleHasher::digest(&block_ids),
+        rand_merkle_root(),
+        vec![],
+        MilestoneOptions::new(vec![]).unwrap(),
+    )
+    .unwrap();
+
+    assert!(MerkleHasher::proof(&block_ids, 6).unwrap().verify_inclusion(&essence));
+    assert!(!MerkleHasher::proof(&rand_block_ids(7), 6)
+        .unwrap()
+        .verify_inclusion(&essence));
+}